@@ -1,8 +1,36 @@
 use proc_macro2::Ident;
-use syn::{parse_quote, DeriveInput};
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_quote, DeriveInput, Token};
 
-pub fn derive(input: DeriveInput, ident: Ident) -> DeriveInput {
+/// Arguments of `#[borsh_derive(Other)]` and `#[borsh_derive(Other, schema)]`.
+///
+/// `Other` is the type converted from, and `schema` is passed on to the `#[borsh_serde]` of the
+/// `Btr*` type so it derives `borsh::BorshSchema` as well.
+pub struct DeriveArgs {
+    pub ident: Ident,
+    pub serde: SerdeArgs,
+}
+
+impl Parse for DeriveArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident = input.parse()?;
+        if input.is_empty() {
+            return Ok(Self {
+                ident,
+                serde: SerdeArgs::default(),
+            });
+        }
+        input.parse::<Token![,]>()?;
+        Ok(Self {
+            ident,
+            serde: input.parse()?,
+        })
+    }
+}
+
+pub fn derive(input: DeriveInput, args: DeriveArgs) -> DeriveInput {
     let name = input.ident.to_string();
+    let ident = args.ident;
 
     if !name.contains("Btr") {
         match input.data {
@@ -15,9 +43,14 @@ pub fn derive(input: DeriveInput, ident: Ident) -> DeriveInput {
             _ => panic!("doesn't work for enums and unions"),
         }
     } else {
+        let serde: syn::Attribute = if args.serde.schema {
+            parse_quote!(#[btr_macros::borsh_serde(schema)])
+        } else {
+            parse_quote!(#[btr_macros::borsh_serde])
+        };
         match input.data {
             syn::Data::Struct(_) => parse_quote! {
-                #[btr_macros::borsh_serde]
+                #serde
                 #[derive(derive_from_ext::From)]
                 #[from(#ident)]
                 #input
@@ -27,7 +60,35 @@ pub fn derive(input: DeriveInput, ident: Ident) -> DeriveInput {
     }
 }
 
-pub fn serde_impl(input: DeriveInput) -> DeriveInput {
+/// Arguments of `#[borsh_serde(...)]`.
+///
+/// `#[borsh_serde(schema)]` additionally derives `borsh::BorshSchema`, pointing `U256`/`I256`
+/// fields at `better_secret_math::borsh_schema`. The plain `#[borsh_serde]` doesn't, so callers
+/// whose fields lack a schema (or that don't depend on `borsh`/`better_secret_math`) keep working.
+#[derive(Default)]
+pub struct SerdeArgs {
+    pub schema: bool,
+}
+
+impl Parse for SerdeArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self::default();
+        if input.is_empty() {
+            return Ok(args);
+        }
+        let flag: Ident = input.parse()?;
+        if flag != "schema" {
+            return Err(syn::Error::new(flag.span(), "expected `schema`"));
+        }
+        args.schema = true;
+        Ok(args)
+    }
+}
+
+pub fn serde_impl(input: DeriveInput, args: SerdeArgs) -> DeriveInput {
+    if args.schema {
+        return schema_impl(with_ethnum_schema(input));
+    }
     match input.data {
         syn::Data::Struct(_) => parse_quote! {
             #[derive(
@@ -59,18 +120,133 @@ pub fn serde_impl(input: DeriveInput) -> DeriveInput {
     }
 }
 
+fn schema_impl(input: DeriveInput) -> DeriveInput {
+    let mut input = serde_impl(input, SerdeArgs::default());
+    input
+        .attrs
+        .insert(0, parse_quote!(#[derive(borsh::BorshSchema)]));
+    input
+}
+
+/// ethnum types can't implement `BorshSchema`, so fields of those types point the derive at the
+/// schema functions in `better_secret_math::borsh_schema` instead.
+///
+/// Only direct `U256`/`I256` fields are handled (not `Option<U256>`, `Vec<U256>`, ...).
+fn with_ethnum_schema(mut input: DeriveInput) -> DeriveInput {
+    let fields: Vec<&mut syn::Field> = match &mut input.data {
+        syn::Data::Struct(data) => data.fields.iter_mut().collect(),
+        syn::Data::Enum(data) => data
+            .variants
+            .iter_mut()
+            .flat_map(|variant| variant.fields.iter_mut())
+            .collect(),
+        syn::Data::Union(_) => vec![],
+    };
+    for field in fields {
+        if let Some(module) = ethnum_schema_module(&field.ty) {
+            let declaration = format!("better_secret_math::borsh_schema::{}::declaration", module);
+            let definitions = format!(
+                "better_secret_math::borsh_schema::{}::add_definitions_recursively",
+                module
+            );
+            field.attrs.push(parse_quote! {
+                #[borsh(schema(with_funcs(declaration = #declaration, definitions = #definitions)))]
+            });
+        }
+    }
+    input
+}
+
+fn ethnum_schema_module(ty: &syn::Type) -> Option<&'static str> {
+    match ty {
+        syn::Type::Path(path) => match path.path.segments.last()?.ident.to_string().as_str() {
+            "U256" => Some("u256"),
+            "I256" => Some("i256"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn serde_structs() {
-        let expanded = serde_impl(parse_quote! {
+        let expanded = serde_impl(
+            parse_quote! {
+                pub struct InstantiateMsg {
+                    pub verifier: String,
+                    pub beneficiary: String,
+                }
+            },
+            SerdeArgs::default(),
+        );
+
+        let expected = parse_quote! {
+            #[derive(
+                serde::Serialize,
+                serde::Deserialize,
+                borsh_derive::BorshSerialize,
+                borsh_derive::BorshDeserialize,
+                Clone,
+                Debug,
+                PartialEq,
+            )]
+            #[serde(deny_unknown_fields)]
             pub struct InstantiateMsg {
                 pub verifier: String,
                 pub beneficiary: String,
             }
-        });
+        };
+
+        assert_eq!(expanded, expected);
+    }
+
+    #[test]
+    fn derive_passes_schema_on() {
+        let input: DeriveInput = parse_quote! {
+            pub struct BtrPosition {
+                pub size: U256,
+            }
+        };
+        let expanded = derive(input.clone(), parse_quote!(Position, schema));
+
+        let expected = parse_quote! {
+            #[btr_macros::borsh_serde(schema)]
+            #[derive(derive_from_ext::From)]
+            #[from(Position)]
+            pub struct BtrPosition {
+                pub size: U256,
+            }
+        };
+
+        assert_eq!(expanded, expected);
+
+        let expanded = derive(input, parse_quote!(Position));
+
+        let expected = parse_quote! {
+            #[btr_macros::borsh_serde]
+            #[derive(derive_from_ext::From)]
+            #[from(Position)]
+            pub struct BtrPosition {
+                pub size: U256,
+            }
+        };
+
+        assert_eq!(expanded, expected);
+        assert!(syn::parse2::<DeriveArgs>(quote::quote!(Position, schemas)).is_err());
+    }
+
+    #[test]
+    fn schema_is_opt_in() {
+        let input: DeriveInput = parse_quote! {
+            pub struct Position {
+                pub size: U256,
+            }
+        };
+        let expanded = serde_impl(input.clone(), SerdeArgs::default());
 
         let expected = parse_quote! {
             #[derive(
@@ -83,9 +259,58 @@ mod tests {
                 PartialEq,
             )]
             #[serde(deny_unknown_fields)]
-            pub struct InstantiateMsg {
-                pub verifier: String,
-                pub beneficiary: String,
+            pub struct Position {
+                pub size: U256,
+            }
+        };
+
+        assert_eq!(expanded, expected);
+        assert!(
+            syn::parse2::<SerdeArgs>(quote::quote!(schema))
+                .unwrap()
+                .schema
+        );
+        assert!(!syn::parse2::<SerdeArgs>(quote::quote!()).unwrap().schema);
+        assert!(syn::parse2::<SerdeArgs>(quote::quote!(schemas)).is_err());
+    }
+
+    #[test]
+    fn ethnum_fields_use_schema_funcs() {
+        let expanded = serde_impl(
+            parse_quote! {
+                pub struct Position {
+                    pub size: U256,
+                    pub pnl: ethnum::I256,
+                    pub owner: String,
+                }
+            },
+            SerdeArgs { schema: true },
+        );
+
+        let expected = parse_quote! {
+            #[derive(borsh::BorshSchema)]
+            #[derive(
+                serde::Serialize,
+                serde::Deserialize,
+                borsh_derive::BorshSerialize,
+                borsh_derive::BorshDeserialize,
+                Clone,
+                Debug,
+                PartialEq,
+            )]
+            #[serde(deny_unknown_fields)]
+            pub struct Position {
+                #[borsh(schema(with_funcs(
+                    declaration = "better_secret_math::borsh_schema::u256::declaration",
+                    definitions = "better_secret_math::borsh_schema::u256::add_definitions_recursively"
+                )))]
+                pub size: U256,
+                #[borsh(schema(with_funcs(
+                    declaration = "better_secret_math::borsh_schema::i256::declaration",
+                    definitions = "better_secret_math::borsh_schema::i256::add_definitions_recursively"
+                )))]
+                pub pnl: ethnum::I256,
+                pub owner: String,
             }
         };
 
@@ -35,20 +35,21 @@ pub fn borsh_derive(
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let ident = parse_macro_input!(attr as Ident);
-    let expanded = borsh::derive(input, ident).into_token_stream();
+    let args = parse_macro_input!(attr as borsh::DeriveArgs);
+    let expanded = borsh::derive(input, args).into_token_stream();
 
     proc_macro::TokenStream::from(expanded)
 }
 
 #[proc_macro_attribute]
 pub fn borsh_serde(
-    _attr: proc_macro::TokenStream,
+    attr: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    let args = parse_macro_input!(attr as borsh::SerdeArgs);
 
-    let expanded = borsh::serde_impl(input).into_token_stream();
+    let expanded = borsh::serde_impl(input, args).into_token_stream();
 
     proc_macro::TokenStream::from(expanded)
 }
@@ -0,0 +1,56 @@
+//! [BorshSchema](borsh::BorshSchema) definitions for the ethnum types, which can't implement the
+//! trait themselves.
+//!
+//! `#[btr_macros::borsh_serde(schema)]` points `U256`/`I256` fields at these through
+//! `#[borsh(schema(with_funcs(...)))]`, so structs holding them still get a schema (and a storage
+//! layout fingerprint).
+
+use borsh::schema::{add_definition, Declaration, Definition};
+use std::collections::BTreeMap;
+
+macro_rules! impl_schema_funcs {
+    ($module: ident, $name: literal, $size: expr) => {
+        pub mod $module {
+            use super::*;
+
+            pub fn declaration() -> Declaration {
+                $name.into()
+            }
+
+            pub fn add_definitions_recursively(
+                definitions: &mut BTreeMap<Declaration, Definition>,
+            ) {
+                add_definition(declaration(), Definition::Primitive($size), definitions);
+            }
+        }
+    };
+}
+
+impl_schema_funcs!(u256, "U256", 32);
+impl_schema_funcs!(i256, "I256", 32);
+
+#[cfg(test)]
+mod tests {
+    use crate::SimpleRebase;
+    use borsh::schema::{Definition, Fields};
+    use borsh::schema_container_of;
+
+    #[test]
+    fn btr_structs_have_schema() {
+        let container = schema_container_of::<SimpleRebase>();
+        assert_eq!(container.declaration(), "SimpleRebase");
+        assert_eq!(
+            container.get_definition("SimpleRebase"),
+            Some(&Definition::Struct {
+                fields: Fields::NamedFields(vec![
+                    ("elastic".to_string(), "U256".to_string()),
+                    ("base".to_string(), "U256".to_string()),
+                ])
+            })
+        );
+        assert_eq!(
+            container.get_definition("U256"),
+            Some(&Definition::Primitive(32))
+        );
+    }
+}
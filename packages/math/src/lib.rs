@@ -1,10 +1,14 @@
 //! Math library for advanced fixed-point math that works with numbers which are considered to have 18 trailing decimals.
 //! Uses U256 and I256 for better performance.
 
+// lets the paths emitted by `btr_macros` resolve inside this crate too
+extern crate self as better_secret_math;
+
 pub mod macros;
 pub use ethnum::*;
 pub use primitives::*;
 pub mod asserter;
+pub mod borsh_schema;
 mod primitives;
 
 pub mod common;
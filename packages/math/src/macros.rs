@@ -36,21 +36,42 @@ macro_rules! make_btr {
 }
 
 /// Same as [make_btr] but also derives Borsh serializer
+///
+/// Starting with `schema` also derives `borsh::BorshSchema` for the `Btr` struct, so its storage
+/// layout can be fingerprinted:
+/// ```
+/// use cosmwasm_std::Uint256;
+/// use ethnum::U256;
+/// use better_secret_math::make_borsh;
+/// make_borsh!{
+///     schema Rebase {
+///        elastic: Uint256, U256, "";
+///        base: Uint256, U256, ""
+///     }
+/// }
+///
+/// let _ = borsh::schema_container_of::<BtrRebase>();
+/// ```
 #[macro_export]
 macro_rules! make_borsh {
+    (schema $(#[$meta:meta])* $struct:ident {$($element: ident: $ty: ty, $btr_ty: ty, $doc:expr); *}) => {
+        $crate::make_better! {
+            $(#[$meta])* "borsh" (schema) $struct {$($element: $ty, $btr_ty, $doc); *}
+        }
+    };
     ($(#[$meta:meta])* $struct:ident {$($element: ident: $ty: ty, $btr_ty: ty, $doc:expr); *}) => {
         $crate::make_better! {
             $(#[$meta])* "borsh" $struct {$($element: $ty, $btr_ty, $doc); *}
         }
-    }
+    };
 }
 
 #[macro_export]
 macro_rules! make_better {
-    ($(#[$meta:meta])* $type:literal $struct:ident {$($element: ident: $ty: ty, $btr_ty: ty, $doc:expr); *}) => {
+    ($(#[$meta:meta])* $type:literal $(($arg:ident))? $struct:ident {$($element: ident: $ty: ty, $btr_ty: ty, $doc:expr); *}) => {
         paste::paste! {
             $(#[$meta])*
-            #[btr_macros::[<$type _derive>]([<Btr $struct>])]
+            #[btr_macros::[<$type _derive>]([<Btr $struct>] $(, $arg)?)]
             pub struct $struct {
                 $(
                     #[doc=$doc]
@@ -64,7 +85,7 @@ macro_rules! make_better {
             }
             $(#[$meta])*
             #[doc = "[" $struct "] optimized for math and storage (via support for either Borsh or Bincode2 serialization)."]
-            #[btr_macros::[<$type _derive>]($struct)]
+            #[btr_macros::[<$type _derive>]($struct $(, $arg)?)]
             pub struct [<Btr $struct>] {
                 $(
                     #[doc=$doc]
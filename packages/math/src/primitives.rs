@@ -1,9 +1,13 @@
 //! Helper classes to serialize and deserialize large integer types into base-10 string
 //! representations.
 //! NOTE: JSON standard can only work with integer up to 53 bits. So we need helper classes for
-//! 64-bit and 128-bit integers.
+//! 64-bit, 128-bit and 256-bit integers.
+//!
+//! The `JsonSchema` impls are gated behind the `abi` feature (enabled by default) so the wrappers
+//! can be used inside `cw_serde` messages.
 
 use borsh_derive::{BorshDeserialize, BorshSerialize};
+use ethnum::{I256, U256};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 macro_rules! impl_str_type {
@@ -70,6 +74,8 @@ impl_str_type!(U128, u128);
 impl_str_type!(U64, u64);
 impl_str_type!(I128, i128);
 impl_str_type!(I64, i64);
+impl_str_type!(U256Str, U256);
+impl_str_type!(I256Str, I256);
 
 #[cfg(test)]
 mod tests {
@@ -137,4 +143,103 @@ mod tests {
         test_serde!(I64, i64, i64::min_value());
         assert!(I64::from(i64::min_value()) < I64::from(i64::max_value()));
     }
+
+    #[test]
+    fn test_u256() {
+        test_serde!(U256Str, U256, U256::ZERO);
+        test_serde!(U256Str, U256, U256::ONE);
+        test_serde!(U256Str, U256, U256::new(123));
+        test_serde!(U256Str, U256, U256::new(10u128.pow(18)));
+        test_serde!(U256Str, U256, U256::ONE << 200);
+        test_serde!(U256Str, U256, U256::MAX);
+        assert!(U256Str::from(U256::MIN) < U256Str::from(U256::MAX));
+        assert_eq!(
+            serde_json::to_string(&U256Str(U256::MAX)).unwrap(),
+            format!("\"{}\"", U256::MAX)
+        );
+    }
+
+    #[test]
+    fn test_i256() {
+        test_serde!(I256Str, I256, I256::ZERO);
+        test_serde!(I256Str, I256, I256::ONE);
+        test_serde!(I256Str, I256, I256::MINUS_ONE);
+        test_serde!(I256Str, I256, I256::new(123));
+        test_serde!(I256Str, I256, I256::new(10i128.pow(18)));
+        test_serde!(I256Str, I256, I256::ONE << 200);
+        test_serde!(I256Str, I256, -(I256::ONE << 200));
+        test_serde!(I256Str, I256, I256::MAX);
+        test_serde!(I256Str, I256, I256::MIN);
+        assert!(I256Str::from(I256::MIN) < I256Str::from(I256::MAX));
+    }
+
+    #[test]
+    fn deserialize_invalid_str_errs() {
+        assert!(serde_json::from_str::<U256Str>("\"-1\"").is_err());
+        assert!(serde_json::from_str::<U256Str>("\"abc\"").is_err());
+        assert!(serde_json::from_str::<I256Str>("123").is_err());
+    }
+
+    #[cfg(feature = "abi")]
+    mod abi {
+        use super::*;
+        use cosmwasm_schema::{cw_serde, schema_for};
+        use schemars::schema::{InstanceType, Schema, SingleOrVec};
+
+        #[cw_serde]
+        struct Position {
+            size: U256Str,
+            pnl: I256Str,
+            fee: U128,
+        }
+
+        fn assert_string_schema(schema: &Schema) {
+            match schema {
+                Schema::Object(obj) => assert_eq!(
+                    obj.instance_type,
+                    Some(SingleOrVec::Single(Box::new(InstanceType::String)))
+                ),
+                s => panic!("Unexpected schema {:?}", s),
+            }
+        }
+
+        #[test]
+        fn str_types_are_string_schemas() {
+            for root in [
+                schema_for!(U256Str),
+                schema_for!(I256Str),
+                schema_for!(U128),
+                schema_for!(I128),
+                schema_for!(U64),
+                schema_for!(I64),
+            ] {
+                assert_string_schema(&Schema::Object(root.schema));
+                assert!(root.definitions.is_empty());
+            }
+        }
+
+        #[test]
+        fn str_types_work_in_cw_serde_messages() {
+            let root = schema_for!(Position);
+            let props = &root.schema.object.as_ref().unwrap().properties;
+            assert_eq!(props.len(), 3);
+            props.values().for_each(assert_string_schema);
+
+            let position = Position {
+                size: U256Str(U256::MAX),
+                pnl: I256Str(I256::MIN),
+                fee: U128(1),
+            };
+            let json = serde_json::to_string(&position).unwrap();
+            assert_eq!(
+                json,
+                format!(
+                    r#"{{"size":"{}","pnl":"{}","fee":"1"}}"#,
+                    U256::MAX,
+                    I256::MIN
+                )
+            );
+            assert_eq!(serde_json::from_str::<Position>(&json).unwrap(), position);
+        }
+    }
 }
@@ -102,7 +102,7 @@ pub trait Rebase {
     }
 }
 
-#[borsh_serde]
+#[borsh_serde(schema)]
 #[derive(Default)]
 pub struct SimpleRebase {
     pub elastic: U256,
@@ -0,0 +1,181 @@
+//! Helpers to move state written by `cw_storage_plus` collections to borsh in place. Every value is
+//! decoded with the old format, encoded with borsh, and checked to decode back to the same value
+//! before it is written. The collections are given by their new borsh declarations, which must
+//! use the same keys as the old ones.
+use borsh::{BorshDeserialize, BorshSerialize};
+use cosmwasm_std::{StdResult, Storage};
+use cw_storage_plus::{Borsh, KeyDeserialize, Keymap, MigrationReport, PrimaryKey, Serde};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{BorshAppendStore as AppendStore, BorshMap as Map};
+
+/// Re-encodes the item stored at `storage_key` from `Old` to borsh.
+pub fn migrate_item<T, Old>(
+    storage: &mut dyn Storage,
+    storage_key: &str,
+) -> StdResult<MigrationReport>
+where
+    T: Serialize + DeserializeOwned + BorshSerialize + BorshDeserialize,
+    Old: Serde,
+{
+    cw_storage_plus::migrate_item::<T, Old, Borsh>(storage, storage_key)
+}
+
+/// Re-encodes the values of `map` at the given keys from `Old` to borsh.
+pub fn migrate_map<'a, K, T, Old>(
+    storage: &mut dyn Storage,
+    map: &Map<'a, K, T>,
+    keys: impl IntoIterator<Item = K>,
+) -> StdResult<MigrationReport>
+where
+    K: PrimaryKey<'a>,
+    T: Serialize + DeserializeOwned + BorshSerialize + BorshDeserialize,
+    Old: Serde,
+{
+    cw_storage_plus::migrate_map(storage, &map.with_format::<Old>(), map, keys)
+}
+
+/// Re-encodes the values of `map` at the keys of one page of `index` from `Old` to borsh. The
+/// keymap is only used as an index of the keys of the map, its own values are left untouched.
+pub fn migrate_map_keymap_page<'a, 'k, K, T, Old, KK, V, KSer>(
+    storage: &mut dyn Storage,
+    map: &Map<'a, K, T>,
+    index: &Keymap<'k, KK, V, KSer>,
+    start_page: u32,
+    size: u32,
+) -> StdResult<MigrationReport>
+where
+    K: PrimaryKey<'a>,
+    T: Serialize + DeserializeOwned + BorshSerialize + BorshDeserialize,
+    Old: Serde,
+    KK: PrimaryKey<'k> + KeyDeserialize,
+    V: Serialize + DeserializeOwned,
+    KSer: Serde,
+{
+    cw_storage_plus::migrate_map_keymap_page(
+        storage,
+        &map.with_format::<Old>(),
+        map,
+        index,
+        start_page,
+        size,
+    )
+}
+
+/// Re-encodes one page of the storage entries of an append store from `Old` to borsh. Pages
+/// count storage entries, which are whole buckets for stores created with
+/// `new_with_bucket_size`.
+pub fn migrate_append_store<T, Old>(
+    storage: &mut dyn Storage,
+    store: &AppendStore<T>,
+    start_page: u32,
+    size: u32,
+) -> StdResult<MigrationReport>
+where
+    T: Serialize + DeserializeOwned + BorshSerialize + BorshDeserialize,
+    Old: Serde,
+{
+    cw_storage_plus::migrate_append_store(
+        storage,
+        &store.with_format::<Old>(),
+        store,
+        start_page,
+        size,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BorshItem as Item;
+    use borsh_derive::{BorshDeserialize, BorshSerialize};
+    use cosmwasm_std::testing::MockStorage;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+    struct Data {
+        name: String,
+        age: i32,
+    }
+
+    fn data(i: u32) -> Data {
+        Data {
+            name: format!("user{}", i),
+            age: i as i32,
+        }
+    }
+
+    #[test]
+    fn migrates_items_and_maps() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        cw_storage_plus::Item::<Data, cw_storage_plus::Json>::new("config")
+            .save(&mut storage, &data(0))?;
+        let old: cw_storage_plus::Map<(&str, u32), Data> = cw_storage_plus::Map::new("people");
+        let index: Keymap<(String, u32), bool> = Keymap::new("people_index");
+        for (i, name) in ["alice", "bob", "carol"].iter().enumerate() {
+            old.save(&mut storage, (*name, i as u32), &data(i as u32))?;
+            index.insert(&mut storage, (name.to_string(), i as u32), &true)?;
+        }
+
+        let report = migrate_item::<Data, cw_storage_plus::Json>(&mut storage, "config")?;
+        assert_eq!(report.migrated, 1);
+        assert_eq!(Item::<Data>::new("config").load(&storage)?, data(0));
+
+        let map: Map<(&str, u32), Data> = Map::new("people");
+        let report = migrate_map_keymap_page::<_, _, cw_storage_plus::Json, _, _, _>(
+            &mut storage,
+            &map,
+            &index,
+            0,
+            2,
+        )?;
+        assert_eq!(report.migrated, 2);
+        let report = migrate_map::<_, _, cw_storage_plus::Json>(
+            &mut storage,
+            &map,
+            [("carol", 2), ("dave", 3)],
+        )?;
+        assert_eq!(
+            report,
+            MigrationReport {
+                migrated: 1,
+                missing: 1
+            }
+        );
+        assert_eq!(map.load(&storage, ("bob", 1))?, data(1));
+        assert_eq!(map.load(&storage, ("carol", 2))?, data(2));
+        Ok(())
+    }
+
+    #[test]
+    fn migrates_append_stores() -> StdResult<()> {
+        for bucket_size in [1, 3] {
+            let mut storage = MockStorage::new();
+            let old: cw_storage_plus::AppendStore<Data, cw_storage_plus::Bincode2> =
+                cw_storage_plus::AppendStore::new_with_bucket_size("people", bucket_size);
+            for i in 0..7 {
+                old.push(&mut storage, &data(i))?;
+            }
+
+            let store: AppendStore<Data> = AppendStore::new_with_bucket_size("people", bucket_size);
+            let mut page = 0;
+            while migrate_append_store::<_, cw_storage_plus::Bincode2>(
+                &mut storage,
+                &store,
+                page,
+                2,
+            )?
+            .migrated
+                > 0
+            {
+                page += 1;
+            }
+            assert_eq!(
+                store.iter(&storage)?.collect::<StdResult<Vec<_>>>()?,
+                (0..7).map(data).collect::<Vec<_>>()
+            );
+        }
+        Ok(())
+    }
+}
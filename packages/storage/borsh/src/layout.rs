@@ -0,0 +1,389 @@
+//! Storage layout fingerprints for borsh encoded state.
+//!
+//! Borsh is not self-describing, so reordering or retyping the fields of a type that already lives
+//! in storage silently corrupts it. [layout_fingerprint] hashes the [BorshSchema] of a type, and
+//! [assert_layout_snapshot] compares it against a snapshot file committed next to the contract, so
+//! a test fails as soon as the layout of a stored type changes.
+//!
+//! A snapshot file starts with one `name = fingerprint` line per stored type. Blank lines and `#`
+//! comments are ignored. When a layout change is intended, the stored data has to be migrated and
+//! the migration is declared by appending a line with the previous fingerprint as well:
+//! `name = new_fingerprint <- old_fingerprint`. The lines of a type form its layout history: every
+//! migration has to start from the fingerprint recorded before it, and the last one has to match
+//! the current layout.
+use std::collections::BTreeMap;
+
+use borsh::schema::{BorshSchemaContainer, Declaration, Definition, Fields};
+use borsh::{schema_container_of, BorshSchema};
+use cosmwasm_std::{StdError, StdResult};
+
+const MIGRATION_SEPARATOR: &str = "<-";
+
+/// Renders the layout of `T` in the canonical text form that [layout_fingerprint] hashes.
+///
+/// Only what decides the encoded bytes is described: primitive sizes, sequences, enum tags and the
+/// order of fields. Types are numbered in the order they are reached from `T` instead of being
+/// named, so renaming a type, a field or a variant doesn't change the layout.
+pub fn layout_description<T: BorshSchema + ?Sized>() -> String {
+    describe(&schema_container_of::<T>())
+}
+
+/// Returns a stable 64 bit hash (as hex) of the borsh layout of `T`.
+pub fn layout_fingerprint<T: BorshSchema + ?Sized>() -> String {
+    format!("{:016x}", fnv1a(layout_description::<T>().as_bytes()))
+}
+
+fn describe(container: &BorshSchemaContainer) -> String {
+    let mut order = vec![];
+    number(container, container.declaration(), &mut order);
+    let index = |declaration: &Declaration| -> String {
+        match order.iter().position(|known| known == declaration) {
+            Some(i) => format!("#{}", i),
+            None => unreachable!("every referenced declaration is numbered"),
+        }
+    };
+    order
+        .iter()
+        .enumerate()
+        .map(|(i, declaration)| {
+            let definition = match container.get_definition(declaration) {
+                Some(definition) => describe_definition(definition, &index),
+                // types without a definition can only be told apart by their name
+                None => format!("opaque({})", declaration),
+            };
+            format!("{} = {}", i, definition)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Numbers the types reachable from `declaration` depth first, in the order of their fields
+fn number(
+    container: &BorshSchemaContainer,
+    declaration: &Declaration,
+    order: &mut Vec<Declaration>,
+) {
+    if order.contains(declaration) {
+        return;
+    }
+    order.push(declaration.clone());
+    for referenced in references(container.get_definition(declaration)) {
+        number(container, referenced, order);
+    }
+}
+
+fn references(definition: Option<&Definition>) -> Vec<&Declaration> {
+    match definition {
+        None | Some(Definition::Primitive(_)) => vec![],
+        Some(Definition::Sequence { elements, .. }) => vec![elements],
+        Some(Definition::Tuple { elements }) => elements.iter().collect(),
+        Some(Definition::Enum { variants, .. }) => variants
+            .iter()
+            .map(|(_, _, declaration)| declaration)
+            .collect(),
+        Some(Definition::Struct { fields }) => match fields {
+            Fields::NamedFields(fields) => {
+                fields.iter().map(|(_, declaration)| declaration).collect()
+            }
+            Fields::UnnamedFields(fields) => fields.iter().collect(),
+            Fields::Empty => vec![],
+        },
+    }
+}
+
+fn describe_definition(definition: &Definition, index: &dyn Fn(&Declaration) -> String) -> String {
+    match definition {
+        Definition::Primitive(size) => format!("prim({})", size),
+        Definition::Sequence {
+            length_width,
+            length_range,
+            elements,
+        } => format!(
+            "seq({}, {}..={}, {})",
+            length_width,
+            length_range.start(),
+            length_range.end(),
+            index(elements)
+        ),
+        Definition::Enum {
+            tag_width,
+            variants,
+        } => format!(
+            "enum({}; {})",
+            tag_width,
+            variants
+                .iter()
+                .map(|(discriminant, _, declaration)| format!(
+                    "{}: {}",
+                    discriminant,
+                    index(declaration)
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        // structs and tuples are encoded as their fields one after the other, so they share a form
+        Definition::Tuple { .. } | Definition::Struct { .. } => format!(
+            "fields({})",
+            references(Some(definition))
+                .into_iter()
+                .map(index)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+// 64 bit FNV-1a, small and stable across platforms and compiler versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// A recorded layout fingerprint, optionally with the fingerprint it was migrated from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutEntry {
+    pub fingerprint: String,
+    pub migrated_from: Option<String>,
+}
+
+/// The parsed contents of a layout snapshot file: the layout history of every recorded type.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LayoutSnapshot {
+    entries: BTreeMap<String, Vec<LayoutEntry>>,
+}
+
+impl LayoutSnapshot {
+    /// Parses a snapshot file, checking that the migrations recorded for every type chain from
+    /// its first fingerprint to its last one.
+    pub fn parse(contents: &str) -> StdResult<Self> {
+        let mut entries: BTreeMap<String, Vec<LayoutEntry>> = BTreeMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line.split_once('=').ok_or_else(|| {
+                StdError::generic_err(format!("invalid layout snapshot line {}: {}", i + 1, line))
+            })?;
+            let name = name.trim();
+            let entry = match value.split_once(MIGRATION_SEPARATOR) {
+                Some((fingerprint, from)) => LayoutEntry {
+                    fingerprint: fingerprint.trim().to_string(),
+                    migrated_from: Some(from.trim().to_string()),
+                },
+                None => LayoutEntry {
+                    fingerprint: value.trim().to_string(),
+                    migrated_from: None,
+                },
+            };
+            let history = entries.entry(name.to_string()).or_default();
+            if let Some(previous) = history.last() {
+                if entry.migrated_from.as_ref() != Some(&previous.fingerprint) {
+                    return Err(StdError::generic_err(format!(
+                        "layout snapshot line {} doesn't follow the recorded layout of {}; \
+                         a layout change has to be appended as `{} = {} {} {}`",
+                        i + 1,
+                        name,
+                        name,
+                        entry.fingerprint,
+                        MIGRATION_SEPARATOR,
+                        previous.fingerprint
+                    )));
+                }
+            }
+            history.push(entry);
+        }
+        Ok(Self { entries })
+    }
+
+    /// Returns the current layout recorded under `name`
+    pub fn get(&self, name: &str) -> Option<&LayoutEntry> {
+        self.entries.get(name).and_then(|history| history.last())
+    }
+
+    /// Returns every layout recorded under `name`, oldest first
+    pub fn history(&self, name: &str) -> &[LayoutEntry] {
+        self.entries.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Checks the current layout of `T` against the last fingerprint recorded under `name`.
+    pub fn check<T: BorshSchema + ?Sized>(&self, name: &str) -> StdResult<()> {
+        let fingerprint = layout_fingerprint::<T>();
+        let entry = match self.get(name) {
+            Some(entry) => entry,
+            None => {
+                return Err(StdError::generic_err(format!(
+                    "no storage layout recorded for {}; add `{} = {}` to the snapshot",
+                    name, name, fingerprint
+                )))
+            }
+        };
+        if entry.fingerprint != fingerprint {
+            return Err(StdError::generic_err(format!(
+                "storage layout of {} changed from {} to {} without a declared migration; \
+                 migrate the stored data and append `{} = {} {} {}` to the snapshot\n{}",
+                name,
+                entry.fingerprint,
+                fingerprint,
+                name,
+                fingerprint,
+                MIGRATION_SEPARATOR,
+                entry.fingerprint,
+                layout_description::<T>()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Test helper that panics if the layout of `T` doesn't match the last entry recorded under
+/// `name` in the snapshot file at `path`, or if the recorded migrations don't chain.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn assert_layout_snapshot<T: BorshSchema + ?Sized>(
+    path: impl AsRef<std::path::Path>,
+    name: &str,
+) {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("could not read {}: {}", path.display(), err));
+    let snapshot = LayoutSnapshot::parse(&contents)
+        .unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+    if let Err(err) = snapshot.check::<T>(name) {
+        panic!("{}: {}", path.display(), err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod v1 {
+        use borsh_derive::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+        #[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
+        pub struct Balance {
+            pub owner: String,
+            pub amount: u64,
+        }
+    }
+
+    mod renamed {
+        use borsh_derive::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+        #[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
+        pub struct Account {
+            pub holder: String,
+            pub funds: u64,
+        }
+    }
+
+    mod v2 {
+        use borsh_derive::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+        #[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
+        pub struct Balance {
+            pub amount: u64,
+            pub owner: String,
+        }
+    }
+
+    const SNAPSHOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/layouts.snap");
+
+    #[test]
+    fn description_is_canonical() {
+        assert_eq!(
+            layout_description::<v1::Balance>(),
+            "0 = fields(#1, #3)\n\
+             1 = seq(4, 0..=4294967295, #2)\n\
+             2 = prim(1)\n\
+             3 = prim(8)"
+        );
+        assert_eq!(layout_fingerprint::<v1::Balance>(), "051963e648696769");
+    }
+
+    #[test]
+    fn reordered_fields_change_fingerprint() {
+        assert_ne!(
+            layout_fingerprint::<v1::Balance>(),
+            layout_fingerprint::<v2::Balance>()
+        );
+        assert_eq!(layout_fingerprint::<v2::Balance>(), "6ba4b9a79e16fa0b");
+    }
+
+    #[test]
+    fn renames_keep_fingerprint() {
+        assert_eq!(
+            layout_fingerprint::<v1::Balance>(),
+            layout_fingerprint::<renamed::Account>()
+        );
+    }
+
+    #[test]
+    fn committed_snapshot_matches() {
+        assert_layout_snapshot::<v1::Balance>(SNAPSHOT, "Balance");
+    }
+
+    #[test]
+    #[should_panic(expected = "without a declared migration")]
+    fn committed_snapshot_catches_reorder() {
+        assert_layout_snapshot::<v2::Balance>(SNAPSHOT, "Balance");
+    }
+
+    #[test]
+    fn declared_migration() -> StdResult<()> {
+        let snapshot = LayoutSnapshot::parse(
+            "# balances
+            Balance = 051963e648696769
+            Balance = 6ba4b9a79e16fa0b <- 051963e648696769",
+        )?;
+        assert_eq!(
+            snapshot.get("Balance"),
+            Some(&LayoutEntry {
+                fingerprint: "6ba4b9a79e16fa0b".to_string(),
+                migrated_from: Some("051963e648696769".to_string()),
+            })
+        );
+        assert_eq!(snapshot.history("Balance").len(), 2);
+        snapshot.check::<v2::Balance>("Balance")?;
+        assert!(snapshot.check::<v1::Balance>("Balance").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn history_must_chain() {
+        // a new layout can't replace the recorded one without declaring where it migrates from
+        let err = LayoutSnapshot::parse(
+            "Balance = 051963e648696769
+            Balance = 6ba4b9a79e16fa0b",
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("`Balance = 6ba4b9a79e16fa0b <- 051963e648696769`"));
+
+        assert!(LayoutSnapshot::parse(
+            "Balance = 051963e648696769
+            Balance = 6ba4b9a79e16fa0b <- 0000000000000000"
+        )
+        .is_err());
+
+        // the current layout is the end of the chain, not any fingerprint in it
+        let snapshot = LayoutSnapshot::parse(
+            "Balance = 6ba4b9a79e16fa0b
+            Balance = 051963e648696769 <- 6ba4b9a79e16fa0b",
+        )
+        .unwrap();
+        assert!(snapshot.check::<v2::Balance>("Balance").is_err());
+    }
+
+    #[test]
+    fn missing_and_invalid_entries() {
+        let snapshot = LayoutSnapshot::parse("Balance = 051963e648696769").unwrap();
+        let err = snapshot.check::<v1::Balance>("Other").unwrap_err();
+        assert!(err.to_string().contains("`Other = 051963e648696769`"));
+
+        assert!(LayoutSnapshot::parse("Balance 051963e648696769").is_err());
+        assert!(LayoutSnapshot::parse("Balance = a\nBalance = b").is_err());
+    }
+}
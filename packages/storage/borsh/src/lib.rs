@@ -13,18 +13,97 @@ For more information on this package, please check out the
 [README](https://github.com/CosmWasm/cw-plus/blob/main/packages/storage-plus/README.md).
 */
 
-mod append_store;
-mod deque;
-mod deque_store;
-mod helpers;
-mod item;
-mod map;
-mod path;
+mod format_migration;
+mod layout;
 mod traits;
 
-pub use append_store::AppendStore as BorshAppendStore;
-pub use deque::Deque as BorshDeque;
-pub use deque_store::DequeStore as BorshDequeStore;
-pub use item::Item as BorshItem;
-pub use map::Map as BorshMap;
-pub use path::Path as BorshPath;
+use borsh::{BorshDeserialize, BorshSerialize};
+use cosmwasm_std::StdResult;
+
+pub use cw_storage_plus::Borsh;
+// counters store their value as raw key bytes, so they are the same for both crates
+pub use cw_storage_plus::{Counter as BorshCounter, Sequence as BorshSequence};
+// key sets, index traits and expirations don't depend on the format of the values
+pub use cw_storage_plus::{
+    ChangeSet as BorshChangeSet, Expiration, Index as BorshIndex, IndexList as BorshIndexList,
+    Keyset as BorshKeyset, KeysetIter as BorshKeysetIter,
+};
+pub use format_migration::{
+    migrate_append_store as migrate_append_store_to_borsh, migrate_item as migrate_item_to_borsh,
+    migrate_map as migrate_map_to_borsh,
+    migrate_map_keymap_page as migrate_map_keymap_page_to_borsh,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use layout::assert_layout_snapshot;
+pub use layout::{layout_description, layout_fingerprint, LayoutEntry, LayoutSnapshot};
+
+// the collections are the ones of cw_storage_plus with the Borsh format
+pub type BorshAppendStore<'a, T> = cw_storage_plus::AppendStore<'a, T, Borsh>;
+pub type BorshDeque<'a, T> = cw_storage_plus::Deque<'a, T, Borsh>;
+pub type BorshDequeStore<'a, T> = cw_storage_plus::DequeStore<'a, T, Borsh>;
+pub type BorshEntry<'s, T> = cw_storage_plus::Entry<'s, T, Borsh>;
+pub type BorshOccupiedEntry<'s, T> = cw_storage_plus::OccupiedEntry<'s, T, Borsh>;
+pub type BorshVacantEntry<'s, T> = cw_storage_plus::VacantEntry<'s, T, Borsh>;
+pub type BorshExpiringItem<'a, T> = cw_storage_plus::ExpiringItem<'a, T, Borsh>;
+pub type BorshExpiringMap<'a, K, V> = cw_storage_plus::ExpiringMap<'a, K, V, Borsh>;
+pub type BorshIndexedMap<'a, K, T, I> = cw_storage_plus::IndexedMap<'a, K, T, I, Borsh>;
+pub type BorshItem<'a, T> = cw_storage_plus::Item<'a, T, Borsh>;
+pub type BorshKeymap<'a, K, V> = cw_storage_plus::Keymap<'a, K, V, Borsh>;
+pub type BorshKeymapIter<'a, 'b, K, V> = cw_storage_plus::KeymapIter<'a, 'b, K, V, Borsh>;
+pub type BorshMap<'a, K, T> = cw_storage_plus::Map<'a, K, T, Borsh>;
+pub type BorshMerkleAppendStore<'a, T> = cw_storage_plus::MerkleAppendStore<'a, T, Borsh>;
+pub type BorshMultiIndex<'a, IK, T, PK = ()> = cw_storage_plus::MultiIndex<'a, IK, T, PK, Borsh>;
+pub type BorshPath<T> = cw_storage_plus::Path<T, Borsh>;
+pub type BorshSnapshotItem<'a, T> = cw_storage_plus::SnapshotItem<'a, T, Borsh>;
+pub type BorshSnapshotMap<'a, K, T> = cw_storage_plus::SnapshotMap<'a, K, T, Borsh>;
+pub type BorshSortedMap<'a, K, V> = cw_storage_plus::SortedMap<'a, K, V, Borsh>;
+pub type BorshSortedMapIter<'a, 'b, K, V> = cw_storage_plus::SortedMapIter<'a, 'b, K, V, Borsh>;
+pub type BorshStorageHeap<'a, T> = cw_storage_plus::StorageHeap<'a, T, Borsh>;
+pub type BorshUniqueIndex<'a, IK, T, PK = ()> = cw_storage_plus::UniqueIndex<'a, IK, T, PK, Borsh>;
+pub type BorshVersionedItem<'a, T> = cw_storage_plus::VersionedItem<'a, T, Borsh>;
+pub type BorshVersionedMap<'a, K, T> = cw_storage_plus::VersionedMap<'a, K, T, Borsh>;
+
+/// Returns true if `proof` proves that `item` is stored at `pos` in a [`BorshMerkleAppendStore`]
+/// with the given root.
+pub fn borsh_verify_merkle_proof<T: BorshSerialize + BorshDeserialize>(
+    root: &[u8; 32],
+    pos: u32,
+    item: &T,
+    proof: &[[u8; 32]],
+) -> StdResult<bool> {
+    cw_storage_plus::verify_merkle_proof::<T, Borsh>(root, pos, item, proof)
+}
+
+/// Decodes `data` as `Old`, converts it with `convert` and serializes the result with borsh.
+///
+/// ```rust
+/// use borsh_derive::{BorshDeserialize, BorshSerialize};
+/// use cw_storage_plus::Migration;
+/// use secret_borsh_storage::{borsh_migrate, BorshVersionedItem};
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// struct ConfigV1 {
+///     owner: String,
+/// }
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// struct Config {
+///     owner: String,
+///     paused: bool,
+/// }
+///
+/// const MIGRATIONS: &[Migration] = &[|data| {
+///     borsh_migrate::<ConfigV1, Config>(data, |old| Config {
+///         owner: old.owner,
+///         paused: false,
+///     })
+/// }];
+/// const CONFIG: BorshVersionedItem<Config> = BorshVersionedItem::new("config", MIGRATIONS);
+/// ```
+pub fn borsh_migrate<Old, New>(data: &[u8], convert: impl FnOnce(Old) -> New) -> StdResult<Vec<u8>>
+where
+    Old: BorshDeserialize,
+    New: BorshSerialize,
+{
+    Borsh::serialize(&convert(Borsh::deserialize(data)?))
+}
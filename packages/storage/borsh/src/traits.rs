@@ -1,20 +1,8 @@
-use std::any::type_name;
-
 use crate::{BorshItem as Item, BorshMap as Map};
 use borsh::{BorshDeserialize, BorshSerialize};
 use cosmwasm_std::{StdError, StdResult, Storage};
 use cw_storage_plus::PrimaryKey;
 
-pub struct Borsh;
-impl Borsh {
-    pub fn serialize<T: BorshSerialize>(obj: &T) -> StdResult<Vec<u8>> {
-        borsh::to_vec(obj).map_err(|err| StdError::serialize_err(type_name::<T>(), err))
-    }
-
-    pub fn deserialize<T: BorshDeserialize>(data: &[u8]) -> StdResult<T> {
-        T::try_from_slice(data).map_err(|err| StdError::parse_err(type_name::<T>(), err))
-    }
-}
 pub trait NaiveItemStorage: BorshSerialize + BorshDeserialize {
     fn load(storage: &dyn Storage, item: Item<Self>) -> StdResult<Self> {
         item.load(storage)
@@ -5,7 +5,7 @@ use criterion::{black_box, criterion_group, Criterion};
 use secret_borsh_storage::BorshItem;
 use serde::{Deserialize, Serialize};
 
-use secret_storage_plus::{Bincode2, Item, Json, Serde};
+use secret_storage_plus::{Bincode2, Cbor, Compressed, Item, Json, Postcard, Serde};
 
 #[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize, PartialEq, Debug)]
 struct Config {
@@ -49,10 +49,16 @@ impl BigChungis {
 
 const CONFIG: Item<Config, Bincode2> = Item::new("blahblahbalh");
 const CONFIG_JSON: Item<Config, Json> = Item::new("blahblahbalh");
+const CONFIG_POSTCARD: Item<Config, Postcard> = Item::new("blahblahbalh");
+const CONFIG_CBOR: Item<Config, Cbor> = Item::new("blahblahbalh");
+const CONFIG_COMPRESSED: Item<Config, Compressed<Bincode2>> = Item::new("blahblahbalh");
 const CONFIG_BORSH: BorshItem<Config> = BorshItem::new("adwadwa");
 
 const BIG: Item<BigChungis, Bincode2> = Item::new("blahblahbalh");
 const BIG_JSON: Item<BigChungis, Json> = Item::new("blahblahbalh");
+const BIG_POSTCARD: Item<BigChungis, Postcard> = Item::new("blahblahbalh");
+const BIG_CBOR: Item<BigChungis, Cbor> = Item::new("blahblahbalh");
+const BIG_COMPRESSED: Item<BigChungis, Compressed<Bincode2>> = Item::new("blahblahbalh");
 const BIG_BORSH: BorshItem<BigChungis> = BorshItem::new("adwadwa");
 
 fn big_save_and_load(item: Item<BigChungis, impl Serde>) {
@@ -104,7 +110,7 @@ fn save_and_load(item: Item<Config, impl Serde>) {
 }
 
 fn bench_bincode2_json(c: &mut Criterion) {
-    let mut group = c.benchmark_group("Bincode vs Json");
+    let mut group = c.benchmark_group("Serialization formats");
 
     group.bench_function("bincode2 save & load", |b| {
         b.iter(|| save_and_load(black_box(CONFIG)))
@@ -118,6 +124,18 @@ fn bench_bincode2_json(c: &mut Criterion) {
         b.iter(|| borsh_save_and_load(black_box(CONFIG_BORSH)))
     });
 
+    group.bench_function("postcard save & load", |b| {
+        b.iter(|| save_and_load(black_box(CONFIG_POSTCARD)))
+    });
+
+    group.bench_function("cbor save & load", |b| {
+        b.iter(|| save_and_load(black_box(CONFIG_CBOR)))
+    });
+
+    group.bench_function("compressed bincode2 save & load", |b| {
+        b.iter(|| save_and_load(black_box(CONFIG_COMPRESSED)))
+    });
+
     group.bench_function("bincode2 big save & load", |b| {
         b.iter(|| big_save_and_load(black_box(BIG)))
     });
@@ -130,6 +148,18 @@ fn bench_bincode2_json(c: &mut Criterion) {
         b.iter(|| borsh_big_save_and_load(black_box(BIG_BORSH)))
     });
 
+    group.bench_function("postcard big save & load", |b| {
+        b.iter(|| big_save_and_load(black_box(BIG_POSTCARD)))
+    });
+
+    group.bench_function("cbor big save & load", |b| {
+        b.iter(|| big_save_and_load(black_box(BIG_CBOR)))
+    });
+
+    group.bench_function("compressed bincode2 big save & load", |b| {
+        b.iter(|| big_save_and_load(black_box(BIG_COMPRESSED)))
+    });
+
     group.finish();
 }
 
@@ -3,41 +3,63 @@
 //!
 //! This is achieved by storing each item in a separate storage entry. A special key is reserved
 //! for storing the length of the collection so far.
+//!
+//! A store can also be built with [`AppendStore::new_with_bucket_size`], in which case every
+//! storage entry (bucket) holds up to `bucket_size` consecutive items. Reads and writes then touch
+//! a single bucket, and iterating or paging reads each bucket only once.
 use std::any::type_name;
+use std::borrow::Borrow;
+use std::cell::Cell;
+use std::cmp::Ordering;
 use std::convert::TryInto;
 use std::marker::PhantomData;
-use std::sync::Mutex;
-
-use serde::{de::DeserializeOwned, Serialize};
+use std::ops::RangeBounds;
 
-use crate::{Json, Serde};
+use crate::helpers::{parse_raw_u32, query_raw, resolve_range};
+use crate::serialization::owned_copy;
+use crate::{Format, Json};
 
-use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
 const LEN_KEY: &[u8] = b"len";
 
+/// The formats an [`AppendStore`] of `T` can be stored with. Besides the items, the format encodes
+/// the buckets of a bucketed store. Every [`Serde`](crate::Serde) format implements it for serde
+/// items.
+pub trait AppendStoreFormat<T>: Format<T> + Format<Vec<T>> {}
+
+impl<T, Ser> AppendStoreFormat<T> for Ser where Ser: Format<T> + Format<Vec<T>> {}
+
 pub struct AppendStore<'a, T, Ser = Json>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: AppendStoreFormat<T>,
 {
     /// prefix of the newly constructed Storage
     namespace: &'a [u8],
     /// needed if any suffixes were added to the original namespace.
     /// therefore it is not necessarily same as the namespace.
     prefix: Option<Vec<u8>>,
-    length: Mutex<Option<u32>>,
+    /// number of items stored in a single storage entry
+    bucket_size: u32,
     item_type: PhantomData<T>,
     serialization_type: PhantomData<Ser>,
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
+impl<'a, T, Ser: AppendStoreFormat<T>> AppendStore<'a, T, Ser> {
     /// constructor
     pub const fn new(prefix: &'a str) -> Self {
+        Self::new_with_bucket_size(prefix, 1)
+    }
+    /// constructor for a store that packs up to `bucket_size` items into each storage entry.
+    ///
+    /// The bucket size is part of the storage layout, so it must not change once items have been
+    /// stored. A bucket size of 1 uses the same layout as `new`.
+    pub const fn new_with_bucket_size(prefix: &'a str, bucket_size: u32) -> Self {
+        assert!(bucket_size > 0, "bucket size must be at least 1");
         Self {
             namespace: prefix.as_bytes(),
             prefix: None,
-            length: Mutex::new(None),
+            bucket_size,
             item_type: PhantomData,
             serialization_type: PhantomData,
         }
@@ -53,33 +75,59 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
         Self {
             namespace: self.namespace,
             prefix: Some(prefix),
-            length: Mutex::new(None),
+            bucket_size: self.bucket_size,
             item_type: self.item_type,
             serialization_type: self.serialization_type,
         }
     }
+    /// Builds an AppendStore directly on top of an already computed prefix, e.g. a length-prefixed
+    /// namespace of another collection.
+    pub(crate) fn prefixed(prefix: Vec<u8>) -> Self {
+        Self {
+            namespace: &[],
+            prefix: Some(prefix),
+            bucket_size: 1,
+            item_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+    /// Returns a store with the same keys and bucket size that uses another serialization format,
+    /// e.g. to migrate its items in place
+    pub fn with_format<S: AppendStoreFormat<T>>(&self) -> AppendStore<'a, T, S> {
+        AppendStore {
+            namespace: self.namespace,
+            prefix: self.prefix.clone(),
+            bucket_size: self.bucket_size,
+            item_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+    /// Binds the store to `storage` for one execution, see [`CachedAppendStore`]
+    pub fn cached<'s>(&self, storage: &'s mut dyn Storage) -> CachedAppendStore<'a, 's, T, Ser> {
+        CachedAppendStore {
+            store: self.clone(),
+            storage,
+            len: Cell::new(None),
+        }
+    }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
-    /// gets the length from storage, and otherwise sets it to 0
+impl<'a, T, Ser: AppendStoreFormat<T>> AppendStore<'a, T, Ser> {
+    /// gets the length from storage, and otherwise returns 0
+    ///
+    /// The length is read on every call rather than cached in the store, so a `const` store stays
+    /// correct when it is used against several storages or after a rolled back transaction. Use
+    /// [`AppendStore::cached`] to keep it in memory for one execution.
     pub fn get_len(&self, storage: &dyn Storage) -> StdResult<u32> {
-        let mut may_len = self.length.lock().unwrap();
-        if let Some(len) = *may_len {
-            Ok(len)
+        let len_key = [self.as_slice(), LEN_KEY].concat();
+        if let Some(len_vec) = storage.get(&len_key) {
+            let len_bytes = len_vec
+                .as_slice()
+                .try_into()
+                .map_err(|err| StdError::parse_err("u32", err))?;
+            Ok(u32::from_be_bytes(len_bytes))
         } else {
-            let len_key = [self.as_slice(), LEN_KEY].concat();
-            if let Some(len_vec) = storage.get(&len_key) {
-                let len_bytes = len_vec
-                    .as_slice()
-                    .try_into()
-                    .map_err(|err| StdError::parse_err("u32", err))?;
-                let len = u32::from_be_bytes(len_bytes);
-                *may_len = Some(len);
-                Ok(len)
-            } else {
-                *may_len = Some(0);
-                Ok(0)
-            }
+            Ok(0)
         }
     }
     /// checks if the collection has any elements
@@ -89,24 +137,28 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
     /// gets the element at pos if within bounds
     pub fn get_at(&self, storage: &dyn Storage, pos: u32) -> StdResult<T> {
         let len = self.get_len(storage)?;
-        if pos > len {
+        if pos >= len {
             return Err(StdError::generic_err("AppendStore access out of bounds"));
         }
         self.get_at_unchecked(storage, pos)
     }
     /// tries to get the element at pos
-    fn get_at_unchecked(&self, storage: &dyn Storage, pos: u32) -> StdResult<T> {
-        let key = pos.to_be_bytes();
-        self.load_impl(storage, &key)
+    pub(crate) fn get_at_unchecked(&self, storage: &dyn Storage, pos: u32) -> StdResult<T> {
+        if self.bucket_size == 1 {
+            let key = pos.to_be_bytes();
+            return self.load_impl(storage, &key);
+        }
+        let (bucket, slot) = self.bucket_pos(pos);
+        self.load_bucket(storage, bucket)?
+            .into_iter()
+            .nth(slot)
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))
     }
 
     /// Set the length of the collection
-    fn set_len(&self, storage: &mut dyn Storage, len: u32) {
+    pub(crate) fn set_len(&self, storage: &mut dyn Storage, len: u32) {
         let len_key = [self.as_slice(), LEN_KEY].concat();
         storage.set(&len_key, &len.to_be_bytes());
-
-        let mut may_len = self.length.lock().unwrap();
-        *may_len = Some(len);
     }
     /// Clear the collection
     pub fn clear(&self, storage: &mut dyn Storage) {
@@ -121,8 +173,25 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
         self.set_at_unchecked(storage, pos, item)
     }
     /// Sets data at a given index
-    fn set_at_unchecked(&self, storage: &mut dyn Storage, pos: u32, item: &T) -> StdResult<()> {
-        self.save_impl(storage, &pos.to_be_bytes(), item)
+    pub(crate) fn set_at_unchecked(
+        &self,
+        storage: &mut dyn Storage,
+        pos: u32,
+        item: &T,
+    ) -> StdResult<()> {
+        if self.bucket_size == 1 {
+            return self.save_impl(storage, &pos.to_be_bytes(), item);
+        }
+        let (bucket, slot) = self.bucket_pos(pos);
+        let mut items = self.load_bucket(storage, bucket)?;
+        match slot.cmp(&items.len()) {
+            Ordering::Less => items[slot] = owned_copy::<T, Ser>(item)?,
+            Ordering::Equal => items.push(owned_copy::<T, Ser>(item)?),
+            Ordering::Greater => {
+                return Err(StdError::generic_err("AppendStore bucket is missing items"))
+            }
+        }
+        self.save_bucket(storage, bucket, items)
     }
     /// Pushes an item to AppendStorage
     pub fn push(&self, storage: &mut dyn Storage, item: &T) -> StdResult<()> {
@@ -155,6 +224,11 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
         if pos >= len {
             return Err(StdError::generic_err("DequeStorage access out of bounds"));
         }
+        if self.bucket_size > 1 {
+            let item = self.remove_from_buckets(storage, pos, len)?;
+            self.set_len(storage, len - 1);
+            return Ok(item);
+        }
         let item = self.get_at_unchecked(storage, pos);
 
         for i in pos..(len - 1) {
@@ -164,6 +238,130 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
         self.set_len(storage, len - 1);
         item
     }
+    /// Shifts every item after `pos` one position down, a bucket at a time, and returns the item
+    /// that was at `pos`.
+    fn remove_from_buckets(&self, storage: &mut dyn Storage, pos: u32, len: u32) -> StdResult<T> {
+        let (first, slot) = self.bucket_pos(pos);
+        let (last, last_slot) = self.bucket_pos(len - 1);
+        let load_live = |bucket: u32| -> StdResult<Vec<T>> {
+            let mut items = self.load_bucket(storage, bucket)?;
+            if bucket == last {
+                // ignore items left behind by earlier pops
+                items.truncate(last_slot + 1);
+            }
+            Ok(items)
+        };
+
+        let mut current = load_live(first)?;
+        if slot >= current.len() {
+            return Err(StdError::not_found(type_name::<T>()));
+        }
+        let item = current.remove(slot);
+        let mut buckets = vec![];
+        for bucket in (first + 1)..=last {
+            let mut next = load_live(bucket)?;
+            if !next.is_empty() {
+                current.push(next.remove(0));
+            }
+            buckets.push(current);
+            current = next;
+        }
+        buckets.push(current);
+
+        for (bucket, items) in (first..=last).zip(buckets) {
+            self.save_bucket(storage, bucket, items)?;
+        }
+        Ok(item)
+    }
+    /// Pushes every item of `items`, writing the length only once
+    pub fn extend<I>(&self, storage: &mut dyn Storage, items: I) -> StdResult<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        let items: Vec<I::Item> = items.into_iter().collect();
+        if items.is_empty() {
+            return Ok(());
+        }
+        let len = self.get_len(storage)?;
+        let refs: Vec<&T> = items.iter().map(<I::Item as Borrow<T>>::borrow).collect();
+        self.save_run(storage, len, &refs)?;
+        self.set_len(storage, len + refs.len() as u32);
+        Ok(())
+    }
+    /// Shortens the collection to its first `len` items. Does nothing if it isn't longer than
+    /// that.
+    pub fn truncate(&self, storage: &mut dyn Storage, len: u32) -> StdResult<()> {
+        if len < self.get_len(storage)? {
+            self.set_len(storage, len);
+        }
+        Ok(())
+    }
+    /// Removes the element at the specified position and returns it, moving the last element
+    /// into its place.
+    ///
+    /// Unlike `remove`, this has a constant cost, but it doesn't preserve the order of the
+    /// collection.
+    pub fn swap_remove(&self, storage: &mut dyn Storage, pos: u32) -> StdResult<T> {
+        let len = self.get_len(storage)?;
+        if pos >= len {
+            return Err(StdError::generic_err("AppendStore access out of bounds"));
+        }
+        let item = self.get_at_unchecked(storage, pos)?;
+        let last = len - 1;
+        if pos != last {
+            let last_item = self.get_at_unchecked(storage, last)?;
+            self.set_at_unchecked(storage, pos, &last_item)?;
+        }
+        self.set_len(storage, last);
+        Ok(item)
+    }
+    /// Keeps only the elements for which `keep` returns true, preserving their order.
+    ///
+    /// Every element is read once, and the kept elements are written from the first removed
+    /// position on.
+    pub fn retain<F>(&self, storage: &mut dyn Storage, mut keep: F) -> StdResult<()>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let items = self.iter(storage)?.collect::<StdResult<Vec<T>>>()?;
+        let mut first_removed = None;
+        let mut kept = vec![];
+        for (pos, item) in (0..).zip(items) {
+            if keep(&item) {
+                if first_removed.is_some() {
+                    kept.push(item);
+                }
+            } else if first_removed.is_none() {
+                first_removed = Some(pos);
+            }
+        }
+        if let Some(first) = first_removed {
+            let len = first + kept.len() as u32;
+            self.save_owned_run(storage, first, kept)?;
+            self.set_len(storage, len);
+        }
+        Ok(())
+    }
+    /// Removes the elements in `range` and returns them. The elements after the range are
+    /// shifted down in storage.
+    pub fn drain(
+        &self,
+        storage: &mut dyn Storage,
+        range: impl RangeBounds<u32>,
+    ) -> StdResult<Vec<T>> {
+        let len = self.get_len(storage)?;
+        let (start, end) = resolve_range(range, len)?;
+        if start == end {
+            return Ok(vec![]);
+        }
+        let mut drained =
+            AppendStoreIter::new(self, storage, start, len).collect::<StdResult<Vec<T>>>()?;
+        let rest = drained.split_off((end - start) as usize);
+        self.save_owned_run(storage, start, rest)?;
+        self.set_len(storage, len - (end - start));
+        Ok(drained)
+    }
     /// Returns a readonly iterator
     pub fn iter(&self, storage: &'a dyn Storage) -> StdResult<AppendStoreIter<T, Ser>> {
         let len = self.get_len(storage)?;
@@ -179,20 +377,157 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
     }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> Clone for AppendStore<'a, T, Ser> {
+impl<'a, T, Ser: AppendStoreFormat<T>> AppendStore<'a, T, Ser> {
+    /// If you import the proper AppendStore from the remote contract, this will let you read the
+    /// length of the remote collection using a raw query
+    pub fn query_len<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<u32> {
+        let len_key = [self.as_slice(), LEN_KEY].concat();
+        let result = query_raw(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            len_key.into(),
+        )?;
+        parse_raw_u32(&result)
+    }
+    /// gets the element at pos of the remote collection if within bounds
+    pub fn query_at<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        pos: u32,
+    ) -> StdResult<T> {
+        let len = self.query_len(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+        )?;
+        if pos >= len {
+            return Err(StdError::generic_err("AppendStore access out of bounds"));
+        }
+        self.query_at_unchecked(querier, remote_contract, remote_contract_code_hash, pos)
+    }
+    /// does paging over the remote collection with the given parameters
+    pub fn query_paging<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<T>> {
+        let len = self.query_len(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+        )?;
+        let start = start_page.saturating_mul(size).min(len);
+        let end = start.saturating_add(size).min(len);
+        if self.bucket_size > 1 && start < end {
+            // query every bucket of the page once
+            let first = self.bucket_pos(start).0;
+            let last = self.bucket_pos(end - 1).0;
+            let mut items = Vec::with_capacity((end - start) as usize);
+            for bucket in first..=last {
+                let bucket_items = self.query_bucket(
+                    querier,
+                    remote_contract.clone(),
+                    remote_contract_code_hash.clone(),
+                    bucket,
+                )?;
+                let bucket_start = bucket * self.bucket_size;
+                items.extend(
+                    bucket_items
+                        .into_iter()
+                        .zip(bucket_start..)
+                        .filter(|(_, pos)| (start..end).contains(pos))
+                        .map(|(item, _)| item),
+                );
+            }
+            if items.len() != (end - start) as usize {
+                return Err(StdError::not_found(type_name::<T>()));
+            }
+            return Ok(items);
+        }
+        (start..end)
+            .map(|pos| {
+                self.query_at_unchecked(
+                    querier,
+                    remote_contract.clone(),
+                    remote_contract_code_hash.clone(),
+                    pos,
+                )
+            })
+            .collect()
+    }
+    fn query_at_unchecked<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        pos: u32,
+    ) -> StdResult<T> {
+        if self.bucket_size > 1 {
+            let (bucket, slot) = self.bucket_pos(pos);
+            return self
+                .query_bucket(querier, remote_contract, remote_contract_code_hash, bucket)?
+                .into_iter()
+                .nth(slot)
+                .ok_or_else(|| StdError::not_found(type_name::<T>()));
+        }
+        let key = [self.as_slice(), &pos.to_be_bytes()].concat();
+        let result = query_raw(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            key.into(),
+        )?;
+        if result.is_empty() {
+            return Err(StdError::not_found(type_name::<T>()));
+        }
+        <Ser as Format<T>>::decode(&result)
+    }
+    fn query_bucket<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        bucket: u32,
+    ) -> StdResult<Vec<T>> {
+        let key = [self.as_slice(), &bucket.to_be_bytes()].concat();
+        let result = query_raw(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            key.into(),
+        )?;
+        if result.is_empty() {
+            return Ok(vec![]);
+        }
+        <Ser as Format<Vec<T>>>::decode(&result)
+    }
+}
+
+impl<'a, T, Ser: AppendStoreFormat<T>> Clone for AppendStore<'a, T, Ser> {
     fn clone(&self) -> Self {
         Self {
             namespace: self.namespace,
             prefix: self.prefix.clone(),
-            length: Mutex::new(None),
+            bucket_size: self.bucket_size,
             item_type: self.item_type,
             serialization_type: self.serialization_type,
         }
     }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
-    fn as_slice(&self) -> &[u8] {
+impl<'a, T, Ser: AppendStoreFormat<T>> AppendStore<'a, T, Ser> {
+    pub(crate) fn as_slice(&self) -> &[u8] {
         if let Some(prefix) = &self.prefix {
             prefix
         } else {
@@ -209,7 +544,7 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
     /// * `key` - a byte slice representing the key to access the stored item
     fn load_impl(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<T> {
         let prefixed_key = [self.as_slice(), key].concat();
-        Ser::deserialize(
+        <Ser as Format<T>>::decode(
             &storage
                 .get(&prefixed_key)
                 .ok_or_else(|| StdError::not_found(type_name::<T>()))?,
@@ -225,27 +560,286 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
     /// * `value` - a reference to the item to store
     fn save_impl(&self, storage: &mut dyn Storage, key: &[u8], value: &T) -> StdResult<()> {
         let prefixed_key = [self.as_slice(), key].concat();
-        storage.set(&prefixed_key, &Ser::serialize(value)?);
+        storage.set(&prefixed_key, &<Ser as Format<T>>::encode(value)?);
+        Ok(())
+    }
+
+    /// Returns the number of storage entries holding the items, which is the length rounded up to
+    /// whole buckets
+    pub(crate) fn entry_count(&self, storage: &dyn Storage) -> StdResult<u32> {
+        let len = self.get_len(storage)?;
+        Ok(len / self.bucket_size + u32::from(len % self.bucket_size != 0))
+    }
+
+    /// Returns the storage key of an entry, which holds a single item or a whole bucket
+    pub(crate) fn entry_key(&self, entry: u32) -> Vec<u8> {
+        [self.as_slice(), &entry.to_be_bytes()].concat()
+    }
+
+    /// Returns true if both stores keep their items at the same storage keys
+    pub(crate) fn same_layout<U, S>(&self, other: &AppendStore<U, S>) -> bool
+    where
+        S: AppendStoreFormat<U>,
+    {
+        self.as_slice() == other.as_slice() && self.bucket_size == other.bucket_size
+    }
+
+    pub(crate) fn is_bucketed(&self) -> bool {
+        self.bucket_size > 1
+    }
+
+    /// Returns the bucket holding the item at pos, and the item's index within that bucket
+    fn bucket_pos(&self, pos: u32) -> (u32, usize) {
+        (pos / self.bucket_size, (pos % self.bucket_size) as usize)
+    }
+
+    /// Returns the items stored in a bucket, or an empty bucket if nothing was stored there yet.
+    /// A bucket may hold stale items past the end of the collection.
+    fn load_bucket(&self, storage: &dyn Storage, bucket: u32) -> StdResult<Vec<T>> {
+        let key = [self.as_slice(), &bucket.to_be_bytes()].concat();
+        match storage.get(&key) {
+            Some(bytes) => <Ser as Format<Vec<T>>>::decode(&bytes),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Writes borrowed items to consecutive positions
+    fn save_run(&self, storage: &mut dyn Storage, pos: u32, items: &[&T]) -> StdResult<()> {
+        if self.bucket_size == 1 {
+            for (pos, item) in (pos..).zip(items) {
+                self.save_impl(storage, &pos.to_be_bytes(), item)?;
+            }
+            return Ok(());
+        }
+        let items = items
+            .iter()
+            .map(|item| owned_copy::<T, Ser>(item))
+            .collect::<StdResult<Vec<T>>>()?;
+        self.save_to_buckets(storage, pos, items)
+    }
+
+    /// Writes items that were read from the store to consecutive positions, e.g. to shift them
+    fn save_owned_run(&self, storage: &mut dyn Storage, pos: u32, items: Vec<T>) -> StdResult<()> {
+        if self.bucket_size == 1 {
+            for (pos, item) in (pos..).zip(&items) {
+                self.save_impl(storage, &pos.to_be_bytes(), item)?;
+            }
+            return Ok(());
+        }
+        self.save_to_buckets(storage, pos, items)
+    }
+
+    /// Writes items to consecutive positions, reading and writing every bucket they touch only
+    /// once
+    fn save_to_buckets(&self, storage: &mut dyn Storage, pos: u32, items: Vec<T>) -> StdResult<()> {
+        let total = items.len();
+        let mut items = items.into_iter();
+        let mut written = 0;
+        while written < total {
+            let (bucket, slot) = self.bucket_pos(pos + written as u32);
+            let mut stored = self.load_bucket(storage, bucket)?;
+            if slot > stored.len() {
+                return Err(StdError::generic_err("AppendStore bucket is missing items"));
+            }
+            let count = (self.bucket_size as usize - slot).min(total - written);
+            for (index, item) in (slot..).zip(items.by_ref().take(count)) {
+                if index < stored.len() {
+                    stored[index] = item;
+                } else {
+                    stored.push(item);
+                }
+            }
+            self.save_bucket(storage, bucket, stored)?;
+            written += count;
+        }
+        Ok(())
+    }
+
+    /// Overwrites a bucket with the given items
+    fn save_bucket(&self, storage: &mut dyn Storage, bucket: u32, items: Vec<T>) -> StdResult<()> {
+        let key = [self.as_slice(), &bucket.to_be_bytes()].concat();
+        storage.set(&key, &<Ser as Format<Vec<T>>>::encode(&items)?);
         Ok(())
     }
 }
 
+/// An [`AppendStore`] bound to one storage for a single execution, built with
+/// [`AppendStore::cached`].
+///
+/// The length is read from storage the first time it is needed and then kept in the handle, so
+/// pushing, popping and reading items doesn't read it again. The handle holds the only borrow of
+/// the storage, so nothing else can change the length while it is alive, and a new execution
+/// starts with a new handle, which never sees the length of another storage or of a rolled back
+/// transaction.
+///
+/// ```rust
+/// # use cosmwasm_std::testing::MockStorage;
+/// use secret_storage_plus::AppendStore;
+///
+/// const LOG: AppendStore<u64> = AppendStore::new("log");
+///
+/// # let mut storage = MockStorage::new();
+/// let mut log = LOG.cached(&mut storage);
+/// for i in 0..10 {
+///     log.push(&i)?;
+/// }
+/// assert_eq!(log.pop()?, 9);
+/// assert_eq!(log.get_len()?, 9);
+/// # Ok::<(), cosmwasm_std::StdError>(())
+/// ```
+pub struct CachedAppendStore<'a, 's, T, Ser = Json>
+where
+    Ser: AppendStoreFormat<T>,
+{
+    store: AppendStore<'a, T, Ser>,
+    storage: &'s mut dyn Storage,
+    len: Cell<Option<u32>>,
+}
+
+impl<'a, 's, T, Ser: AppendStoreFormat<T>> CachedAppendStore<'a, 's, T, Ser> {
+    /// gets the length, reading it from storage only the first time
+    pub fn get_len(&self) -> StdResult<u32> {
+        match self.len.get() {
+            Some(len) => Ok(len),
+            None => {
+                let len = self.store.get_len(self.storage)?;
+                self.len.set(Some(len));
+                Ok(len)
+            }
+        }
+    }
+    /// checks if the collection has any elements
+    pub fn is_empty(&self) -> StdResult<bool> {
+        Ok(self.get_len()? == 0)
+    }
+    /// gets the element at pos if within bounds
+    pub fn get_at(&self, pos: u32) -> StdResult<T> {
+        if pos >= self.get_len()? {
+            return Err(StdError::generic_err("AppendStore access out of bounds"));
+        }
+        self.store.get_at_unchecked(self.storage, pos)
+    }
+    /// Replaces data at a position within bounds
+    pub fn set_at(&mut self, pos: u32, item: &T) -> StdResult<()> {
+        if pos >= self.get_len()? {
+            return Err(StdError::generic_err("AppendStore access out of bounds"));
+        }
+        self.store.set_at_unchecked(self.storage, pos, item)
+    }
+    /// Pushes an item to the end of the collection
+    pub fn push(&mut self, item: &T) -> StdResult<()> {
+        let len = self.get_len()?;
+        self.store.set_at_unchecked(self.storage, len, item)?;
+        self.set_len(len + 1);
+        Ok(())
+    }
+    /// Pops an item from the end of the collection
+    pub fn pop(&mut self) -> StdResult<T> {
+        if let Some(len) = self.get_len()?.checked_sub(1) {
+            let item = self.store.get_at_unchecked(self.storage, len);
+            self.set_len(len);
+            item
+        } else {
+            Err(StdError::generic_err("Can not pop from empty AppendStore"))
+        }
+    }
+    /// Pushes every item of `items`, writing the length only once
+    pub fn extend<I>(&mut self, items: I) -> StdResult<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        let items: Vec<I::Item> = items.into_iter().collect();
+        if items.is_empty() {
+            return Ok(());
+        }
+        let len = self.get_len()?;
+        let refs: Vec<&T> = items.iter().map(<I::Item as Borrow<T>>::borrow).collect();
+        self.store.save_run(self.storage, len, &refs)?;
+        self.set_len(len + refs.len() as u32);
+        Ok(())
+    }
+    /// Shortens the collection to its first `len` items. Does nothing if it isn't longer than
+    /// that.
+    pub fn truncate(&mut self, len: u32) -> StdResult<()> {
+        if len < self.get_len()? {
+            self.set_len(len);
+        }
+        Ok(())
+    }
+    /// Clear the collection
+    pub fn clear(&mut self) {
+        self.set_len(0);
+    }
+    /// Removes an element, see [`AppendStore::remove`]
+    pub fn remove(&mut self, pos: u32) -> StdResult<T> {
+        let item = self.store.remove(self.storage, pos)?;
+        self.len.set(self.len.get().map(|len| len - 1));
+        Ok(item)
+    }
+    /// Removes the element at the specified position and returns it, moving the last element
+    /// into its place
+    pub fn swap_remove(&mut self, pos: u32) -> StdResult<T> {
+        let len = self.get_len()?;
+        if pos >= len {
+            return Err(StdError::generic_err("AppendStore access out of bounds"));
+        }
+        let item = self.store.get_at_unchecked(self.storage, pos)?;
+        let last = len - 1;
+        if pos != last {
+            let last_item = self.store.get_at_unchecked(self.storage, last)?;
+            self.store.set_at_unchecked(self.storage, pos, &last_item)?;
+        }
+        self.set_len(last);
+        Ok(item)
+    }
+    /// Keeps only the elements for which `keep` returns true, see [`AppendStore::retain`]
+    pub fn retain<F>(&mut self, keep: F) -> StdResult<()>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.len.set(None);
+        self.store.retain(self.storage, keep)
+    }
+    /// Removes the elements in `range` and returns them, see [`AppendStore::drain`]
+    pub fn drain(&mut self, range: impl RangeBounds<u32>) -> StdResult<Vec<T>> {
+        self.len.set(None);
+        self.store.drain(self.storage, range)
+    }
+    /// Returns a readonly iterator
+    pub fn iter(&self) -> StdResult<AppendStoreIter<T, Ser>> {
+        let len = self.get_len()?;
+        Ok(AppendStoreIter::new(&self.store, self.storage, 0, len))
+    }
+    /// does paging with the given parameters
+    pub fn paging(&self, start_page: u32, size: u32) -> StdResult<Vec<T>> {
+        self.iter()?
+            .skip((start_page as usize) * (size as usize))
+            .take(size as usize)
+            .collect()
+    }
+    fn set_len(&mut self, len: u32) {
+        self.store.set_len(self.storage, len);
+        self.len.set(Some(len));
+    }
+}
+
 /// An iterator over the contents of the append store.
 pub struct AppendStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: AppendStoreFormat<T>,
 {
     append_store: &'a AppendStore<'a, T, Ser>,
     storage: &'a dyn Storage,
     start: u32,
     end: u32,
+    /// the last bucket read by a bucketed store, so each bucket is only loaded once
+    bucket: Option<(u32, Vec<Option<T>>)>,
 }
 
 impl<'a, T, Ser> AppendStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: AppendStoreFormat<T>,
 {
     /// constructor
     pub fn new(
@@ -259,14 +853,32 @@ where
             storage,
             start,
             end,
+            bucket: None,
+        }
+    }
+
+    fn get_at(&mut self, pos: u32) -> StdResult<T> {
+        if self.append_store.bucket_size == 1 {
+            // `start..end` is within the length read when the iterator was built
+            return self.append_store.get_at_unchecked(self.storage, pos);
         }
+        let (bucket, slot) = self.append_store.bucket_pos(pos);
+        if !matches!(&self.bucket, Some((cached, _)) if *cached == bucket) {
+            let items = self.append_store.load_bucket(self.storage, bucket)?;
+            self.bucket = Some((bucket, items.into_iter().map(Some).collect()));
+        }
+        // every position is yielded once, so the item can be moved out of the cached bucket
+        self.bucket
+            .as_mut()
+            .and_then(|(_, items)| items.get_mut(slot))
+            .and_then(Option::take)
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))
     }
 }
 
 impl<'a, T, Ser> Iterator for AppendStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: AppendStoreFormat<T>,
 {
     type Item = StdResult<T>;
 
@@ -274,7 +886,7 @@ where
         if self.start >= self.end {
             return None;
         }
-        let item = self.append_store.get_at(self.storage, self.start);
+        let item = self.get_at(self.start);
         self.start += 1;
         Some(item)
     }
@@ -299,15 +911,14 @@ where
 
 impl<'a, T, Ser> DoubleEndedIterator for AppendStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: AppendStoreFormat<T>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         if self.start >= self.end {
             return None;
         }
         self.end -= 1;
-        let item = self.append_store.get_at(self.storage, self.end);
+        let item = self.get_at(self.end);
         Some(item)
     }
 
@@ -324,19 +935,16 @@ where
 }
 
 // This enables writing `append_store.iter().skip(n).rev()`
-impl<'a, T, Ser> ExactSizeIterator for AppendStoreIter<'a, T, Ser>
-where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
-{
-}
+impl<'a, T, Ser> ExactSizeIterator for AppendStoreIter<'a, T, Ser> where Ser: AppendStoreFormat<T> {}
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::MockStorage;
 
     use super::*;
-    use crate::Json;
+    use crate::helpers::{mock_remote_querier, CountingStorage};
+    use crate::{Bincode2, Json};
+    use cosmwasm_std::Empty;
 
     #[test]
     fn test_push_pop() -> StdResult<()> {
@@ -361,34 +969,51 @@ mod tests {
         let mut storage = MockStorage::new();
         let append_store: AppendStore<i32> = AppendStore::new("test");
 
-        assert!(append_store.length.lock().unwrap().eq(&None));
         assert_eq!(append_store.get_len(&storage)?, 0);
-        assert!(append_store.length.lock().unwrap().eq(&Some(0)));
 
         append_store.push(&mut storage, &1234)?;
         append_store.push(&mut storage, &2143)?;
         append_store.push(&mut storage, &3412)?;
         append_store.push(&mut storage, &4321)?;
-        assert!(append_store.length.lock().unwrap().eq(&Some(4)));
         assert_eq!(append_store.get_len(&storage)?, 4);
 
         assert_eq!(append_store.pop(&mut storage), Ok(4321));
         assert_eq!(append_store.pop(&mut storage), Ok(3412));
-        assert!(append_store.length.lock().unwrap().eq(&Some(2)));
         assert_eq!(append_store.get_len(&storage)?, 2);
 
         assert_eq!(append_store.pop(&mut storage), Ok(2143));
         assert_eq!(append_store.pop(&mut storage), Ok(1234));
-        assert!(append_store.length.lock().unwrap().eq(&Some(0)));
         assert_eq!(append_store.get_len(&storage)?, 0);
 
         assert!(append_store.pop(&mut storage).is_err());
-        assert!(append_store.length.lock().unwrap().eq(&Some(0)));
         assert_eq!(append_store.get_len(&storage)?, 0);
 
         Ok(())
     }
 
+    #[test]
+    fn test_length_follows_storage() -> StdResult<()> {
+        // a single (e.g. const) store used with two storages must not mix up their lengths
+        let mut storage = MockStorage::new();
+        let mut other_storage = MockStorage::new();
+        let append_store: AppendStore<i32> = AppendStore::new("test");
+
+        append_store.push(&mut storage, &1234)?;
+        append_store.push(&mut storage, &2143)?;
+        assert_eq!(append_store.get_len(&other_storage)?, 0);
+
+        append_store.push(&mut other_storage, &3412)?;
+        assert_eq!(append_store.get_len(&storage)?, 2);
+        assert_eq!(append_store.get_len(&other_storage)?, 1);
+
+        assert_eq!(append_store.pop(&mut storage)?, 2143);
+        assert_eq!(append_store.pop(&mut other_storage)?, 3412);
+        assert!(append_store.pop(&mut other_storage).is_err());
+        assert_eq!(append_store.get_len(&storage)?, 1);
+
+        Ok(())
+    }
+
     #[test]
     fn test_iterator() -> StdResult<()> {
         let mut storage = MockStorage::new();
@@ -691,4 +1316,261 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_remote_queries() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<u32, Bincode2> = AppendStore::new("test");
+        for i in 0..12 {
+            append_store.push(&mut storage, &i)?;
+        }
+
+        let remote = mock_remote_querier(storage);
+        let querier = QuerierWrapper::<Empty>::new(&remote);
+        let contract = Addr::unchecked("remote");
+        let hash = "hash".to_string();
+
+        assert_eq!(
+            append_store.query_len(&querier, contract.clone(), hash.clone())?,
+            12
+        );
+        assert_eq!(
+            append_store.query_at(&querier, contract.clone(), hash.clone(), 7)?,
+            7
+        );
+        assert!(append_store
+            .query_at(&querier, contract.clone(), hash.clone(), 12)
+            .is_err());
+        assert_eq!(
+            append_store.query_paging(&querier, contract.clone(), hash.clone(), 1, 5)?,
+            vec![5, 6, 7, 8, 9]
+        );
+        assert_eq!(
+            append_store.query_paging(&querier, contract.clone(), hash.clone(), 2, 5)?,
+            vec![10, 11]
+        );
+        assert!(append_store
+            .query_paging(&querier, contract, hash, 3, 5)?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_operations() -> StdResult<()> {
+        for bucket_size in [1, 3] {
+            let mut storage = MockStorage::new();
+            let namespace = format!("test{}", bucket_size);
+            let append_store: AppendStore<u32> =
+                AppendStore::new_with_bucket_size(&namespace, bucket_size);
+            append_store.extend(&mut storage, [0, 1, 2, 3])?;
+            append_store.extend(&mut storage, &[4, 5, 6, 7, 8, 9])?;
+            assert_eq!(append_store.get_len(&storage)?, 10);
+            assert_eq!(
+                append_store.paging(&storage, 0, 10)?,
+                vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+            );
+
+            assert_eq!(append_store.swap_remove(&mut storage, 1)?, 1);
+            assert_eq!(append_store.swap_remove(&mut storage, 8)?, 8);
+            assert!(append_store.swap_remove(&mut storage, 8).is_err());
+            assert_eq!(
+                append_store.paging(&storage, 0, 10)?,
+                vec![0, 9, 2, 3, 4, 5, 6, 7]
+            );
+
+            append_store.retain(&mut storage, |item| item % 3 != 0)?;
+            assert_eq!(append_store.paging(&storage, 0, 10)?, vec![2, 4, 5, 7]);
+
+            append_store.extend(&mut storage, [10, 11, 12])?;
+            assert_eq!(append_store.drain(&mut storage, 1..3)?, vec![4, 5]);
+            assert_eq!(
+                append_store.paging(&storage, 0, 10)?,
+                vec![2, 7, 10, 11, 12]
+            );
+            assert_eq!(append_store.drain(&mut storage, 3..)?, vec![11, 12]);
+            assert!(append_store.drain(&mut storage, ..4).is_err());
+            assert_eq!(append_store.drain(&mut storage, 1..1)?, vec![]);
+
+            append_store.truncate(&mut storage, 5)?;
+            assert_eq!(append_store.get_len(&storage)?, 3);
+            append_store.truncate(&mut storage, 1)?;
+            append_store.push(&mut storage, &20)?;
+            assert_eq!(append_store.paging(&storage, 0, 10)?, vec![2, 20]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_reads_length_once() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let append_store: AppendStore<u32> = AppendStore::new("test");
+        append_store.extend(&mut storage, [0, 1, 2, 3, 4])?;
+
+        // the length is read once, then one read per item
+        storage.reads.set(0);
+        assert_eq!(
+            append_store
+                .iter(&storage)?
+                .collect::<StdResult<Vec<_>>>()?
+                .len(),
+            5
+        );
+        assert_eq!(storage.reads.get(), 1 + 5);
+
+        storage.reads.set(0);
+        assert_eq!(append_store.paging(&storage, 1, 2)?, vec![2, 3]);
+        assert_eq!(storage.reads.get(), 1 + 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cached_handle() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let append_store: AppendStore<u32> = AppendStore::new("test");
+        append_store.extend(&mut storage, [0, 1, 2])?;
+
+        storage.reads.set(0);
+        {
+            let mut cached = append_store.cached(&mut storage);
+            cached.push(&3)?;
+            cached.push(&4)?;
+            assert_eq!(cached.get_at(4)?, 4);
+            assert!(cached.get_at(5).is_err());
+            assert_eq!(cached.pop()?, 4);
+            assert_eq!(cached.paging(0, 10)?, vec![0, 1, 2, 3]);
+        }
+        // the length is read once, then one read per loaded item
+        assert_eq!(storage.reads.get(), 1 + 1 + 1 + 4);
+        assert_eq!(append_store.paging(&storage, 0, 10)?, vec![0, 1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_operations_write_length_once() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let append_store: AppendStore<u32> = AppendStore::new("test");
+        append_store.extend(&mut storage, [0, 1, 2, 3, 4])?;
+        // one write per item and one for the length
+        assert_eq!(storage.writes, 6);
+
+        storage.writes = 0;
+        append_store.retain(&mut storage, |item| *item != 1)?;
+        assert_eq!(storage.writes, 4);
+
+        storage.writes = 0;
+        append_store.drain(&mut storage, ..2)?;
+        assert_eq!(storage.writes, 3);
+
+        let bucketed: AppendStore<u32> = AppendStore::new_with_bucket_size("bucketed", 4);
+        storage.writes = 0;
+        bucketed.extend(&mut storage, [0, 1, 2, 3, 4, 5])?;
+        assert_eq!(storage.writes, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_at_len_after_pop() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        for bucket_size in [1, 4] {
+            let namespace = format!("test{}", bucket_size);
+            let append_store: AppendStore<u32> =
+                AppendStore::new_with_bucket_size(&namespace, bucket_size);
+            append_store.extend(&mut storage, [0, 1, 2])?;
+            assert_eq!(append_store.pop(&mut storage)?, 2);
+            // the popped item is still in storage, but it is out of bounds
+            assert!(append_store.get_at(&storage, 2).is_err());
+            assert_eq!(append_store.get_at(&storage, 1)?, 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bucketed() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<u32> = AppendStore::new_with_bucket_size("test", 4);
+        for i in 0..10 {
+            append_store.push(&mut storage, &i)?;
+        }
+        assert_eq!(append_store.get_len(&storage)?, 10);
+
+        // items are packed four to a storage entry
+        let key = [append_store.as_slice(), &1_u32.to_be_bytes()].concat();
+        assert_eq!(storage.get(&key), Some(b"[4,5,6,7]".to_vec()));
+        let key = [append_store.as_slice(), &5_u32.to_be_bytes()].concat();
+        assert_eq!(storage.get(&key), None);
+
+        assert_eq!(append_store.get_at(&storage, 5)?, 5);
+        append_store.set_at(&mut storage, 5, &50)?;
+        assert_eq!(append_store.get_at(&storage, 5)?, 50);
+
+        assert_eq!(append_store.pop(&mut storage)?, 9);
+        assert_eq!(append_store.pop(&mut storage)?, 8);
+        append_store.push(&mut storage, &80)?;
+        assert_eq!(append_store.get_len(&storage)?, 9);
+
+        assert_eq!(append_store.remove(&mut storage, 2)?, 2);
+        assert_eq!(
+            append_store
+                .iter(&storage)?
+                .collect::<StdResult<Vec<_>>>()?,
+            vec![0, 1, 3, 4, 50, 6, 7, 80]
+        );
+        assert_eq!(
+            append_store
+                .iter(&storage)?
+                .rev()
+                .collect::<StdResult<Vec<_>>>()?,
+            vec![80, 7, 6, 50, 4, 3, 1, 0]
+        );
+        assert_eq!(append_store.paging(&storage, 1, 3)?, vec![4, 50, 6]);
+        assert_eq!(append_store.paging(&storage, 2, 3)?, vec![7, 80]);
+
+        assert_eq!(append_store.remove(&mut storage, 7)?, 80);
+        assert_eq!(append_store.remove(&mut storage, 0)?, 0);
+        assert_eq!(
+            append_store.paging(&storage, 0, 10)?,
+            vec![1, 3, 4, 50, 6, 7]
+        );
+
+        let suffixed = append_store.add_suffix("user");
+        suffixed.push(&mut storage, &1)?;
+        assert_eq!(suffixed.get_at(&storage, 0)?, 1);
+        assert_eq!(append_store.get_len(&storage)?, 6);
+
+        append_store.clear(&mut storage);
+        append_store.push(&mut storage, &100)?;
+        assert_eq!(append_store.paging(&storage, 0, 10)?, vec![100]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bucketed_remote_queries() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<u32, Bincode2> = AppendStore::new_with_bucket_size("test", 5);
+        for i in 0..12 {
+            append_store.push(&mut storage, &i)?;
+        }
+
+        let remote = mock_remote_querier(storage);
+        let querier = QuerierWrapper::<Empty>::new(&remote);
+        let contract = Addr::unchecked("remote");
+        let hash = "hash".to_string();
+
+        assert_eq!(
+            append_store.query_at(&querier, contract.clone(), hash.clone(), 7)?,
+            7
+        );
+        assert_eq!(
+            append_store.query_paging(&querier, contract.clone(), hash.clone(), 1, 4)?,
+            vec![4, 5, 6, 7]
+        );
+        assert_eq!(
+            append_store.query_paging(&querier, contract, hash, 2, 4)?,
+            vec![8, 9, 10, 11]
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,355 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{StdResult, Storage};
+
+use crate::Format;
+
+/// A cached key
+struct Slot {
+    /// the serialized value, `None` if nothing is stored at the key
+    raw: Option<Vec<u8>>,
+    /// the deserialized value and the format it was serialized with, if it was loaded or saved
+    /// through the typed methods
+    typed: Option<(Box<dyn Any>, TypeId)>,
+    /// whether the slot differs from the underlying storage
+    dirty: bool,
+}
+
+/// Storage that caches the reads of a contract execution and buffers its writes until `flush`.
+///
+/// It implements `Storage`, so `Item`, `Map`, `AppendStore` and the other collections can use it
+/// in place of `deps.storage`. Every key is read from the underlying storage at most once, and
+/// every changed key is written once, when the cache is flushed. Dropping the cache without
+/// flushing it discards the writes.
+///
+/// `load` and `save` keep typed values in the cache next to their serialization, so that a value
+/// loaded several times with the same format is only deserialized once.
+///
+/// ```rust
+/// # use cosmwasm_std::testing::MockStorage;
+/// use secret_storage_plus::{CachedStorage, Item};
+///
+/// const COUNT: Item<u64> = Item::new("count");
+///
+/// # let mut storage = MockStorage::new();
+/// let mut cache = CachedStorage::new(&mut storage);
+/// for _ in 0..10 {
+///     let count = COUNT.may_load(&cache)?.unwrap_or_default();
+///     COUNT.save(&mut cache, &(count + 1))?;
+/// }
+/// cache.flush()?;
+/// # Ok::<(), cosmwasm_std::StdError>(())
+/// ```
+pub struct CachedStorage<'a> {
+    inner: &'a mut dyn Storage,
+    slots: RefCell<BTreeMap<Vec<u8>, Slot>>,
+}
+
+impl<'a> CachedStorage<'a> {
+    pub fn new(inner: &'a mut dyn Storage) -> Self {
+        Self {
+            inner,
+            slots: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Loads and deserializes the value at `key` with `Ser`. The value is kept deserialized in the
+    /// cache, so loading it again with the same format only clones it. A value cached with
+    /// another format is deserialized from its bytes.
+    pub fn load<T, Ser>(&self, key: &[u8]) -> StdResult<Option<T>>
+    where
+        T: Clone + 'static,
+        Ser: Format<T> + 'static,
+    {
+        let mut slots = self.slots.borrow_mut();
+        let slot = slots.entry(key.to_vec()).or_insert_with(|| Slot {
+            raw: self.inner.get(key),
+            typed: None,
+            dirty: false,
+        });
+        if let Some(value) = slot
+            .typed
+            .as_ref()
+            .filter(|(_, format)| *format == TypeId::of::<Ser>())
+            .and_then(|(value, _)| value.downcast_ref::<T>())
+        {
+            return Ok(Some(value.clone()));
+        }
+
+        let value = match &slot.raw {
+            Some(bytes) => Ser::decode(bytes)?,
+            None => return Ok(None),
+        };
+        slot.typed = Some((Box::new(value.clone()), TypeId::of::<Ser>()));
+        Ok(Some(value))
+    }
+
+    /// Serializes `value` with `Ser` and saves it at `key`. The value is kept deserialized in the
+    /// cache as well, so loading it with the same format doesn't deserialize it.
+    pub fn save<T, Ser>(&mut self, key: &[u8], value: T) -> StdResult<()>
+    where
+        T: Clone + 'static,
+        Ser: Format<T> + 'static,
+    {
+        let raw = Ser::encode(&value)?;
+        self.slots.get_mut().insert(
+            key.to_vec(),
+            Slot {
+                raw: Some(raw),
+                typed: Some((Box::new(value), TypeId::of::<Ser>())),
+                dirty: true,
+            },
+        );
+        Ok(())
+    }
+
+    /// Returns true if there are writes that were not flushed yet
+    pub fn is_dirty(&self) -> bool {
+        self.slots.borrow().values().any(|slot| slot.dirty)
+    }
+
+    /// Writes every changed key to the underlying storage
+    pub fn flush(mut self) -> StdResult<()> {
+        for (key, slot) in std::mem::take(self.slots.get_mut()) {
+            if !slot.dirty {
+                continue;
+            }
+            match slot.raw {
+                Some(value) => self.inner.set(&key, &value),
+                None => self.inner.remove(&key),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Storage for CachedStorage<'a> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let mut slots = self.slots.borrow_mut();
+        let slot = slots.entry(key.to_vec()).or_insert_with(|| Slot {
+            raw: self.inner.get(key),
+            typed: None,
+            dirty: false,
+        });
+        slot.raw.clone()
+    }
+
+    #[cfg(feature = "iterator")]
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = cosmwasm_std::Record> + 'b> {
+        // merge the pending writes into the records of the underlying storage
+        let mut records: BTreeMap<Vec<u8>, Vec<u8>> = self
+            .inner
+            .range(start, end, cosmwasm_std::Order::Ascending)
+            .collect();
+        for (key, slot) in self.slots.borrow().iter() {
+            let in_range = start.map_or(true, |start| key.as_slice() >= start)
+                && end.map_or(true, |end| key.as_slice() < end);
+            if !slot.dirty || !in_range {
+                continue;
+            }
+            match &slot.raw {
+                Some(value) => records.insert(key.clone(), value.clone()),
+                None => records.remove(key),
+            };
+        }
+        match order {
+            cosmwasm_std::Order::Ascending => Box::new(records.into_iter()),
+            cosmwasm_std::Order::Descending => Box::new(records.into_iter().rev()),
+        }
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.slots.get_mut().insert(
+            key.to_vec(),
+            Slot {
+                raw: Some(value.to_vec()),
+                typed: None,
+                dirty: true,
+            },
+        );
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.slots.get_mut().insert(
+            key.to_vec(),
+            Slot {
+                raw: None,
+                typed: None,
+                dirty: true,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::helpers::CountingStorage;
+    use crate::{AppendStore, Bincode2, Item, Json, Map};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Config {
+        owner: String,
+        max_tokens: i32,
+    }
+
+    const CONFIG: Item<Config> = Item::new("config");
+    const BALANCES: Map<&str, u64> = Map::new("balances");
+
+    #[test]
+    fn reads_and_writes_once() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        BALANCES.save(&mut storage, "alice", &1)?;
+        storage.writes = 0;
+
+        let mut cache = CachedStorage::new(&mut storage);
+        for _ in 0..5 {
+            let balance = BALANCES.load(&cache, "alice")?;
+            BALANCES.save(&mut cache, "alice", &(balance + 1))?;
+            assert_eq!(BALANCES.may_load(&cache, "bob")?, None);
+        }
+        BALANCES.remove(&mut cache, "alice");
+        BALANCES.save(&mut cache, "alice", &10)?;
+        assert!(cache.is_dirty());
+        cache.flush()?;
+
+        // one read per key, one write for the key that changed
+        assert_eq!(storage.reads.get(), 2);
+        assert_eq!(storage.writes, 1);
+        assert_eq!(BALANCES.load(&storage, "alice")?, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn dropping_discards_writes() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        {
+            let mut cache = CachedStorage::new(&mut storage);
+            BALANCES.save(&mut cache, "alice", &1)?;
+        }
+        assert_eq!(storage.writes, 0);
+        assert!(!BALANCES.has(&storage, "alice"));
+        Ok(())
+    }
+
+    #[test]
+    fn typed_values_are_cached() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let config = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1,
+        };
+        CONFIG.save(&mut storage, &config)?;
+
+        let mut cache = CachedStorage::new(&mut storage);
+        for i in 2..5 {
+            let mut config = cache.load::<Config, Json>(CONFIG.as_slice())?.unwrap();
+            config.max_tokens = i;
+            cache.save::<_, Json>(CONFIG.as_slice(), config)?;
+        }
+        // collections read the typed value through its serialization
+        assert_eq!(CONFIG.load(&cache)?.max_tokens, 4);
+        assert_eq!(cache.load::<Config, Json>(b"missing")?, None);
+        cache.flush()?;
+
+        assert_eq!(CONFIG.load(&storage)?.max_tokens, 4);
+        assert_eq!(storage.writes, 2);
+
+        // values saved with another format are deserialized from their bytes
+        let mut cache = CachedStorage::new(&mut storage);
+        cache.save::<_, Bincode2>(b"count", 7u64)?;
+        assert_eq!(cache.load::<u64, Bincode2>(b"count")?, Some(7));
+        assert!(cache.load::<String, Bincode2>(b"count").is_err());
+        cache.flush()?;
+        assert_eq!(Item::<u64, Bincode2>::new("count").load(&storage)?, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn loads_with_another_format_read_the_bytes() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let mut cache = CachedStorage::new(&mut storage);
+        cache.save::<_, Bincode2>(b"count", 7u64)?;
+        assert_eq!(cache.load::<u64, Bincode2>(b"count")?, Some(7));
+        // the cached value was serialized with Bincode2, which is not valid json
+        assert!(cache.load::<u64, Json>(b"count").is_err());
+        assert_eq!(cache.load::<u64, Bincode2>(b"count")?, Some(7));
+        Ok(())
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct Unserializable;
+
+    impl Serialize for Unserializable {
+        fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("unserializable"))
+        }
+    }
+
+    #[test]
+    fn save_fails_without_caching() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let mut cache = CachedStorage::new(&mut storage);
+        assert!(cache.save::<_, Json>(b"value", Unserializable).is_err());
+        assert!(!cache.is_dirty());
+        assert_eq!(cache.get(b"value"), None);
+        cache.flush()?;
+        assert_eq!(storage.writes, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn works_with_append_stores() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let store: AppendStore<u32> = AppendStore::new("numbers");
+
+        let mut cache = CachedStorage::new(&mut storage);
+        for i in 0..5 {
+            store.push(&mut cache, &i)?;
+        }
+        assert_eq!(store.pop(&mut cache)?, 4);
+        assert_eq!(store.get_len(&cache)?, 4);
+        cache.flush()?;
+
+        // the length and the 5 items, one of them being stale after the pop
+        assert_eq!(storage.writes, 6);
+        assert_eq!(
+            store.iter(&storage)?.collect::<StdResult<Vec<_>>>()?,
+            vec![0, 1, 2, 3]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "iterator")]
+    #[test]
+    fn range_merges_pending_writes() -> StdResult<()> {
+        use cosmwasm_std::Order;
+
+        let mut storage = CountingStorage::default();
+        BALANCES.save(&mut storage, "alice", &1)?;
+        BALANCES.save(&mut storage, "bob", &2)?;
+
+        let mut cache = CachedStorage::new(&mut storage);
+        BALANCES.remove(&mut cache, "alice");
+        BALANCES.save(&mut cache, "carol", &3)?;
+        BALANCES.save(&mut cache, "bob", &4)?;
+
+        let all = BALANCES
+            .range(&cache, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        assert_eq!(all, vec![("bob".to_string(), 4), ("carol".to_string(), 3)]);
+        let all = BALANCES
+            .range(&cache, None, None, Order::Descending)
+            .collect::<StdResult<Vec<_>>>()?;
+        assert_eq!(all, vec![("carol".to_string(), 3), ("bob".to_string(), 4)]);
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
+
+use crate::helpers::query_raw;
+use crate::int_key::IntKey;
+
+/// Integers that a [`Counter`] can count with
+pub trait CounterInt: IntKey {
+    const ZERO: Self;
+
+    fn checked_increment(self) -> Option<Self>;
+    fn checked_decrement(self) -> Option<Self>;
+}
+
+macro_rules! counter_int {
+    (for $($t:ty),+) => {
+        $(impl CounterInt for $t {
+            const ZERO: Self = 0;
+
+            #[inline]
+            fn checked_increment(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+
+            #[inline]
+            fn checked_decrement(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+        })*
+    }
+}
+
+counter_int!(for i8, u8, i16, u16, i32, u32, i64, u64, i128, u128);
+
+/// Counter hands out consecutive values, starting at zero. It is meant to replace the `Item<u64>`
+/// every contract keeps to generate IDs.
+///
+/// The next value is stored as the big-endian key bytes of `T`, so it doesn't depend on a
+/// serialization format.
+pub struct Counter<'a, T> {
+    storage_key: &'a [u8],
+    // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
+    data_type: PhantomData<T>,
+}
+
+/// Counter of u64 IDs
+pub type Sequence<'a> = Counter<'a, u64>;
+
+impl<'a, T> Counter<'a, T> {
+    pub const fn new(storage_key: &'a str) -> Self {
+        Counter {
+            storage_key: storage_key.as_bytes(),
+            data_type: PhantomData,
+        }
+    }
+
+    // this gets the path of the data to use elsewhere
+    pub fn as_slice(&self) -> &[u8] {
+        self.storage_key
+    }
+}
+
+impl<'a, T: CounterInt> Counter<'a, T> {
+    /// Returns the value the next call to `next` will return, without changing it
+    pub fn peek(&self, store: &dyn Storage) -> StdResult<T> {
+        match store.get(self.storage_key) {
+            Some(bytes) => Self::parse(&bytes),
+            None => Ok(T::ZERO),
+        }
+    }
+
+    /// Returns the next value and advances the counter. Returns an error instead of wrapping
+    /// around, so the maximum value of `T` is never handed out.
+    pub fn next(&self, store: &mut dyn Storage) -> StdResult<T> {
+        let value = self.peek(store)?;
+        let next = value.checked_increment().ok_or_else(|| {
+            StdError::generic_err(format!("{} counter overflow", type_name::<T>()))
+        })?;
+        self.set(store, next);
+        Ok(value)
+    }
+
+    /// Moves the counter back by one and returns the value the next call to `next` will return.
+    pub fn decrement(&self, store: &mut dyn Storage) -> StdResult<T> {
+        let value = self.peek(store)?.checked_decrement().ok_or_else(|| {
+            StdError::generic_err(format!("{} counter underflow", type_name::<T>()))
+        })?;
+        self.set(store, value);
+        Ok(value)
+    }
+
+    /// Sets the value the next call to `next` will return
+    pub fn set(&self, store: &mut dyn Storage, value: T) {
+        store.set(self.storage_key, value.to_cw_bytes().as_ref());
+    }
+
+    /// Returns the value the next call to `next` will return on a remote contract
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<T> {
+        let result = query_raw(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            self.storage_key.into(),
+        )?;
+        if result.is_empty() {
+            Ok(T::ZERO)
+        } else {
+            Self::parse(&result)
+        }
+    }
+
+    fn parse(bytes: &[u8]) -> StdResult<T> {
+        let mut buf = T::Buf::default();
+        if buf.as_ref().len() != bytes.len() {
+            return Err(StdError::parse_err(
+                type_name::<T>(),
+                "counter value has the wrong length",
+            ));
+        }
+        buf.as_mut().copy_from_slice(bytes);
+        Ok(T::from_cw_bytes(buf))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::helpers::mock_remote_querier;
+    use crate::Map;
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Empty;
+
+    crate::int_key_newtype! {
+        /// ID of a token
+        pub struct TokenId(u32);
+    }
+
+    #[test]
+    fn hands_out_consecutive_values() -> StdResult<()> {
+        let mut store = MockStorage::new();
+        const IDS: Sequence = Sequence::new("ids");
+
+        assert_eq!(IDS.peek(&store)?, 0);
+        assert_eq!(IDS.next(&mut store)?, 0);
+        assert_eq!(IDS.next(&mut store)?, 1);
+        assert_eq!(IDS.peek(&store)?, 2);
+
+        assert_eq!(IDS.decrement(&mut store)?, 1);
+        assert_eq!(IDS.next(&mut store)?, 1);
+
+        IDS.set(&mut store, 10);
+        assert_eq!(IDS.next(&mut store)?, 10);
+        assert_eq!(store.get(b"ids"), Some(11u64.to_be_bytes().to_vec()));
+        Ok(())
+    }
+
+    #[test]
+    fn checks_bounds() -> StdResult<()> {
+        let mut store = MockStorage::new();
+        let counter: Counter<u8> = Counter::new("counter");
+        assert!(counter.decrement(&mut store).is_err());
+
+        counter.set(&mut store, u8::MAX - 1);
+        assert_eq!(counter.next(&mut store)?, u8::MAX - 1);
+        assert!(counter.next(&mut store).is_err());
+        assert_eq!(counter.peek(&store)?, u8::MAX);
+
+        let signed: Counter<i32> = Counter::new("signed");
+        signed.set(&mut store, -2);
+        assert_eq!(signed.next(&mut store)?, -2);
+        assert_eq!(signed.next(&mut store)?, -1);
+        assert_eq!(signed.peek(&store)?, 0);
+        signed.set(&mut store, i32::MIN);
+        assert!(signed.decrement(&mut store).is_err());
+
+        // values of another width are rejected
+        store.set(b"counter", &1u64.to_be_bytes());
+        assert!(counter.peek(&store).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn typed_ids_are_map_keys() -> StdResult<()> {
+        let mut store = MockStorage::new();
+        let ids: Counter<TokenId> = Counter::new("token_ids");
+        let owners: Map<TokenId, String> = Map::new("owners");
+
+        for owner in ["alice", "bob"] {
+            let id = ids.next(&mut store)?;
+            owners.save(&mut store, id, &owner.to_string())?;
+        }
+        assert_eq!(ids.peek(&store)?, TokenId(2));
+        assert_eq!(owners.load(&store, TokenId(1))?, "bob");
+        // a typed ID uses the same key as its integer
+        assert_eq!(Map::<u32, String>::new("owners").load(&store, 0)?, "alice");
+
+        #[cfg(feature = "iterator")]
+        {
+            use cosmwasm_std::Order;
+            let keys = owners
+                .keys(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?;
+            assert_eq!(keys, vec![TokenId(0), TokenId(1)]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn remote_query_works() -> StdResult<()> {
+        let mut store = MockStorage::new();
+        const IDS: Sequence = Sequence::new("ids");
+        IDS.next(&mut store)?;
+        IDS.next(&mut store)?;
+
+        let remote = mock_remote_querier(store);
+        let querier = QuerierWrapper::<Empty>::new(&remote);
+        assert_eq!(
+            IDS.query(&querier, Addr::unchecked("remote"), "hash".to_string())?,
+            2
+        );
+        let other: Sequence = Sequence::new("other");
+        assert_eq!(
+            other.query(&querier, Addr::unchecked("remote"), "hash".to_string())?,
+            0
+        );
+        Ok(())
+    }
+}
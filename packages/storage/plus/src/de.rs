@@ -1,7 +1,7 @@
 use std::array::TryFromSliceError;
 use std::convert::TryInto;
 
-use cosmwasm_std::{Addr, StdError, StdResult};
+use cosmwasm_std::{Addr, StdError, StdResult, Timestamp, Uint128, Uint256, Uint64};
 
 use crate::int_key::IntKey;
 
@@ -112,6 +112,34 @@ macro_rules! integer_de {
 
 integer_de!(for i8, u8, i16, u16, i32, u32, i64, u64, i128, u128);
 
+integer_de!(for Uint64, Uint128, Uint256, Timestamp);
+
+#[cfg(feature = "ethnum")]
+integer_de!(for ethnum::U256, ethnum::I256);
+
+impl KeyDeserialize for bool {
+    type Output = bool;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        match value.as_slice() {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(StdError::parse_err("bool", "invalid bool key")),
+        }
+    }
+}
+
+impl KeyDeserialize for char {
+    type Output = char;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        let code = u32::from_vec(value)?;
+        char::from_u32(code).ok_or_else(|| StdError::parse_err("char", "invalid char key"))
+    }
+}
+
 fn parse_length(value: &[u8]) -> StdResult<usize> {
     Ok(u16::from_be_bytes(
         value
@@ -151,6 +179,34 @@ impl<T: KeyDeserialize, U: KeyDeserialize, V: KeyDeserialize> KeyDeserialize for
     }
 }
 
+impl<T: KeyDeserialize, U: KeyDeserialize, V: KeyDeserialize, W: KeyDeserialize> KeyDeserialize
+    for (T, U, V, W)
+{
+    type Output = (T::Output, U::Output, V::Output, W::Output);
+
+    #[inline(always)]
+    fn from_vec(mut value: Vec<u8>) -> StdResult<Self::Output> {
+        let mut tuvw = value.split_off(2);
+        let t_len = parse_length(&value)?;
+        let mut len_uvw = tuvw.split_off(t_len);
+
+        let mut uvw = len_uvw.split_off(2);
+        let u_len = parse_length(&len_uvw)?;
+        let mut len_vw = uvw.split_off(u_len);
+
+        let mut vw = len_vw.split_off(2);
+        let v_len = parse_length(&len_vw)?;
+        let w = vw.split_off(v_len);
+
+        Ok((
+            T::from_vec(tuvw)?,
+            U::from_vec(uvw)?,
+            V::from_vec(vw)?,
+            W::from_vec(w)?,
+        ))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -265,4 +321,51 @@ mod test {
             (BYTES.to_vec(), 1234, STRING.to_string())
         );
     }
+
+    #[test]
+    fn deserialize_quadruple_works() {
+        assert_eq!(
+            <(&[u8], u32, &str, bool)>::from_slice(
+                (BYTES, 1234u32, STRING, true).joined_key().as_slice()
+            )
+            .unwrap(),
+            (BYTES.to_vec(), 1234, STRING.to_string(), true)
+        );
+    }
+
+    #[test]
+    fn deserialize_cosmwasm_types_works() {
+        assert_eq!(
+            Uint64::from_slice(&Uint64::new(1234).joined_key()).unwrap(),
+            Uint64::new(1234)
+        );
+        assert_eq!(
+            Uint128::from_slice(&Uint128::new(1234).joined_key()).unwrap(),
+            Uint128::new(1234)
+        );
+        assert_eq!(
+            Uint256::from_slice(&Uint256::from(1234u32).joined_key()).unwrap(),
+            Uint256::from(1234u32)
+        );
+        assert_eq!(
+            Timestamp::from_slice(&Timestamp::from_seconds(5).joined_key()).unwrap(),
+            Timestamp::from_seconds(5)
+        );
+        assert!(Uint128::from_slice(&[0, 1]).is_err());
+    }
+
+    #[test]
+    fn deserialize_bool_and_char_works() {
+        assert!(!bool::from_slice(&false.joined_key()).unwrap());
+        assert!(bool::from_slice(&true.joined_key()).unwrap());
+        assert!(bool::from_slice(&[2]).is_err());
+
+        assert_eq!(char::from_slice(&'x'.joined_key()).unwrap(), 'x');
+        assert_eq!(
+            char::from_slice(&'\u{1f600}'.joined_key()).unwrap(),
+            '\u{1f600}'
+        );
+        // surrogates are not chars
+        assert!(char::from_slice(&0xd800u32.to_be_bytes()).is_err());
+    }
 }
@@ -1,9 +1,8 @@
-use crate::{
-    helpers::{may_deserialize, namespaces_with_key},
-    traits::Borsh,
-};
-use borsh::{BorshDeserialize, BorshSerialize};
-use cosmwasm_std::{StdError, StdResult, Storage};
+//! A deque that keeps the positions of its first and last item, so that pushing and popping on
+//! both ends leaves no gaps and doesn't move any item.
+use crate::helpers::{may_deserialize, namespaces_with_key, parse_raw_u32, query_raw};
+use crate::{Format, Json};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 use std::{any::type_name, convert::TryInto, marker::PhantomData};
 
 // metadata keys need to have different length than the position type (4 bytes) to prevent collisions
@@ -15,23 +14,28 @@ const HEAD_KEY: &[u8] = b"h";
 ///
 /// It has a maximum capacity of `u32::MAX - 1`. Make sure to never exceed that number when using this type.
 /// If you do, the methods won't work as intended anymore.
-pub struct Deque<'a, T: BorshSerialize + BorshDeserialize> {
+pub struct Deque<'a, T, Ser = Json>
+where
+    Ser: Format<T>,
+{
     // prefix of the deque items
     namespace: &'a [u8],
     // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
     item_type: PhantomData<T>,
+    serialization_type: PhantomData<Ser>,
 }
 
-impl<'a, T: BorshSerialize + BorshDeserialize> Deque<'a, T> {
+impl<'a, T, Ser: Format<T>> Deque<'a, T, Ser> {
     pub const fn new(prefix: &'a str) -> Self {
         Self {
             namespace: prefix.as_bytes(),
             item_type: PhantomData,
+            serialization_type: PhantomData,
         }
     }
 }
 
-impl<'a, T: BorshSerialize + BorshDeserialize> Deque<'a, T> {
+impl<'a, T, Ser: Format<T>> Deque<'a, T, Ser> {
     /// Adds the given value to the end of the deque
     pub fn push_back(&self, storage: &mut dyn Storage, value: &T) -> StdResult<()> {
         // save value
@@ -171,7 +175,7 @@ impl<'a, T: BorshSerialize + BorshDeserialize> Deque<'a, T> {
     /// Used internally
     fn get_unchecked(&self, storage: &dyn Storage, pos: u32) -> StdResult<Option<T>> {
         let prefixed_key = namespaces_with_key(&[self.namespace], &pos.to_be_bytes());
-        may_deserialize(&storage.get(&prefixed_key))
+        may_deserialize::<T, Ser>(&storage.get(&prefixed_key))
     }
 
     /// Removes the value at the given position
@@ -185,20 +189,118 @@ impl<'a, T: BorshSerialize + BorshDeserialize> Deque<'a, T> {
     /// Used internally when pushing
     fn set_unchecked(&self, storage: &mut dyn Storage, pos: u32, value: &T) -> StdResult<()> {
         let prefixed_key = namespaces_with_key(&[self.namespace], &pos.to_be_bytes());
-        storage.set(&prefixed_key, &Borsh::serialize(value)?);
+        storage.set(&prefixed_key, &Ser::encode(value)?);
 
         Ok(())
     }
 }
 
+impl<'a, T, Ser: Format<T>> Deque<'a, T, Ser> {
+    /// If you import the proper Deque from the remote contract, this will let you read the
+    /// length of the remote deque using a raw query
+    pub fn query_len<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<u32> {
+        let head = self.query_meta_key(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+            HEAD_KEY,
+        )?;
+        let tail = self.query_meta_key(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            TAIL_KEY,
+        )?;
+        Ok(calc_len(head, tail))
+    }
+
+    /// Returns the value at the given position in the remote deque or `None` if the index is out of bounds
+    pub fn query_get<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        pos: u32,
+    ) -> StdResult<Option<T>> {
+        Ok(self
+            .query_paging(querier, remote_contract, remote_contract_code_hash, pos, 1)?
+            .pop())
+    }
+
+    /// does paging over the remote deque with the given parameters
+    pub fn query_paging<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<T>> {
+        let head = self.query_meta_key(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+            HEAD_KEY,
+        )?;
+        let tail = self.query_meta_key(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+            TAIL_KEY,
+        )?;
+        let len = calc_len(head, tail);
+        let start = start_page.saturating_mul(size).min(len);
+        let end = start.saturating_add(size).min(len);
+        (start..end)
+            .map(|pos| {
+                let pos = head.wrapping_add(pos);
+                let prefixed_key = namespaces_with_key(&[self.namespace], &pos.to_be_bytes());
+                let result = query_raw(
+                    querier,
+                    remote_contract.clone(),
+                    remote_contract_code_hash.clone(),
+                    prefixed_key.into(),
+                )?;
+                if result.is_empty() {
+                    return Err(StdError::not_found(format!("deque position {}", pos)));
+                }
+                Ser::decode(&result)
+            })
+            .collect()
+    }
+
+    /// Helper method for the remote queries to read the head or tail of the remote deque
+    fn query_meta_key<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        key: &[u8],
+    ) -> StdResult<u32> {
+        let full_key = namespaces_with_key(&[self.namespace], key);
+        let result = query_raw(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            full_key.into(),
+        )?;
+        parse_raw_u32(&result)
+    }
+}
+
 // used internally to avoid additional storage loads
 #[inline]
 fn calc_len(head: u32, tail: u32) -> u32 {
     tail.wrapping_sub(head)
 }
 
-impl<'a, T: BorshSerialize + BorshDeserialize> Deque<'a, T> {
-    pub fn iter(&self, storage: &'a dyn Storage) -> StdResult<DequeIter<T>> {
+impl<'a, T, Ser: Format<T>> Deque<'a, T, Ser> {
+    pub fn iter(&self, storage: &'a dyn Storage) -> StdResult<DequeIter<T, Ser>> {
         Ok(DequeIter {
             deque: self,
             storage,
@@ -215,19 +317,19 @@ impl<'a, T: BorshSerialize + BorshDeserialize> Deque<'a, T> {
     }
 }
 
-pub struct DequeIter<'a, T>
+pub struct DequeIter<'a, T, Ser>
 where
-    T: BorshSerialize + BorshDeserialize,
+    Ser: Format<T>,
 {
-    deque: &'a Deque<'a, T>,
+    deque: &'a Deque<'a, T, Ser>,
     storage: &'a dyn Storage,
     start: u32,
     end: u32,
 }
 
-impl<'a, T> Iterator for DequeIter<'a, T>
+impl<'a, T, Ser> Iterator for DequeIter<'a, T, Ser>
 where
-    T: BorshSerialize + BorshDeserialize,
+    Ser: Format<T>,
 {
     type Item = StdResult<T>;
 
@@ -266,9 +368,9 @@ where
     }
 }
 
-impl<'a, T> DoubleEndedIterator for DequeIter<'a, T>
+impl<'a, T, Ser> DoubleEndedIterator for DequeIter<'a, T, Ser>
 where
-    T: BorshSerialize + BorshDeserialize,
+    Ser: Format<T>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         if self.start == self.end {
@@ -300,9 +402,9 @@ where
 mod tests {
     use super::*;
     use crate::deque::Deque;
-    use borsh_derive::{BorshDeserialize, BorshSerialize};
+    use crate::helpers::mock_remote_querier;
     use cosmwasm_std::testing::MockStorage;
-    use cosmwasm_std::{StdError, StdResult};
+    use cosmwasm_std::{Empty, StdError, StdResult};
     use serde::{Deserialize, Serialize};
 
     #[test]
@@ -505,7 +607,7 @@ mod tests {
         assert_eq!(deque.front(&store).unwrap(), Some(3));
     }
 
-    #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
     struct Data {
         pub name: String,
         pub age: i32,
@@ -658,4 +760,50 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn remote_queries() {
+        let deque: Deque<u32> = Deque::new("test");
+        let mut store = MockStorage::new();
+        for i in 3..8 {
+            deque.push_back(&mut store, &i).unwrap();
+        }
+        for i in (0..3).rev() {
+            deque.push_front(&mut store, &i).unwrap();
+        }
+
+        let remote = mock_remote_querier(store);
+        let querier = QuerierWrapper::<Empty>::new(&remote);
+        let contract = Addr::unchecked("remote");
+        let hash = "hash".to_string();
+
+        assert_eq!(
+            deque
+                .query_len(&querier, contract.clone(), hash.clone())
+                .unwrap(),
+            8
+        );
+        assert_eq!(
+            deque
+                .query_get(&querier, contract.clone(), hash.clone(), 0)
+                .unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            deque
+                .query_get(&querier, contract.clone(), hash.clone(), 6)
+                .unwrap(),
+            Some(6)
+        );
+        assert_eq!(
+            deque
+                .query_get(&querier, contract.clone(), hash.clone(), 8)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            deque.query_paging(&querier, contract, hash, 2, 3).unwrap(),
+            vec![6, 7]
+        );
+    }
 }
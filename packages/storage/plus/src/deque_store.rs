@@ -4,44 +4,63 @@
 //! This is achieved by storing each item in a separate storage entry.
 //! A special key is reserved for storing the length of the collection so far.
 //! Another special key is reserved for storing the offset of the collection.
+//!
+//! A store can also be built with [`DequeStore::new_with_bucket_size`], in which case every
+//! storage entry (bucket) holds up to `bucket_size` consecutive items. Reads and writes then touch
+//! a single bucket, and iterating or paging reads each bucket only once.
 use std::any::type_name;
+use std::borrow::Borrow;
+use std::cell::Cell;
 use std::convert::TryInto;
 use std::marker::PhantomData;
-use std::sync::Mutex;
-
-use serde::{de::DeserializeOwned, Serialize};
+use std::ops::RangeBounds;
 
-use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
-use crate::{Json, Serde};
+use crate::helpers::{parse_raw_u32, query_raw, resolve_range};
+use crate::serialization::owned_copy;
+use crate::{Format, Json};
 
 const LEN_KEY: &[u8] = b"len";
 const OFFSET_KEY: &[u8] = b"off";
 
+/// The formats a [`DequeStore`] of `T` can be stored with. Besides the items, the format encodes
+/// the buckets of a bucketed store, together with the index of their first item. Every
+/// [`Serde`](crate::Serde) format implements it for serde items.
+pub trait DequeStoreFormat<T>: Format<T> + Format<(u32, Vec<T>)> {}
+
+impl<T, Ser> DequeStoreFormat<T> for Ser where Ser: Format<T> + Format<(u32, Vec<T>)> {}
+
 pub struct DequeStore<'a, T, Ser = Json>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: DequeStoreFormat<T>,
 {
     /// prefix of the newly constructed Storage
     namespace: &'a [u8],
     /// needed if any suffixes were added to the original namespace.
     /// therefore it is not necessarily same as the namespace.
     prefix: Option<Vec<u8>>,
-    length: Mutex<Option<u32>>,
-    offset: Mutex<Option<u32>>,
+    /// number of items stored in a single storage entry
+    bucket_size: u32,
     item_type: PhantomData<T>,
     serialization_type: PhantomData<Ser>,
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
+impl<'a, T, Ser: DequeStoreFormat<T>> DequeStore<'a, T, Ser> {
     /// constructor
     pub const fn new(prefix: &'a str) -> Self {
+        Self::new_with_bucket_size(prefix, 1)
+    }
+    /// constructor for a store that packs up to `bucket_size` items into each storage entry.
+    ///
+    /// The bucket size is part of the storage layout, so it must not change once items have been
+    /// stored. A bucket size of 1 uses the same layout as `new`.
+    pub const fn new_with_bucket_size(prefix: &'a str, bucket_size: u32) -> Self {
+        assert!(bucket_size > 0, "bucket size must be at least 1");
         Self {
             namespace: prefix.as_bytes(),
             prefix: None,
-            length: Mutex::new(None),
-            offset: Mutex::new(None),
+            bucket_size,
             item_type: PhantomData,
             serialization_type: PhantomData,
         }
@@ -57,44 +76,34 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
         Self {
             namespace: self.namespace,
             prefix: Some(prefix),
-            length: Mutex::new(None),
-            offset: Mutex::new(None),
+            bucket_size: self.bucket_size,
             item_type: self.item_type,
             serialization_type: self.serialization_type,
         }
     }
+    /// Binds the store to `storage` for one execution, see [`CachedDequeStore`]
+    pub fn cached<'s>(&self, storage: &'s mut dyn Storage) -> CachedDequeStore<'a, 's, T, Ser> {
+        CachedDequeStore {
+            store: self.clone(),
+            storage,
+            len: Cell::new(None),
+            off: Cell::new(None),
+        }
+    }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
-    /// gets the length from storage, and otherwise sets it to 0
+impl<'a, T, Ser: DequeStoreFormat<T>> DequeStore<'a, T, Ser> {
+    /// gets the length from storage, and otherwise returns 0
+    ///
+    /// Like the offset, the length is read on every call rather than cached in the store, so a
+    /// `const` store stays correct when it is used against several storages. Use
+    /// [`DequeStore::cached`] to keep both in memory for one execution.
     pub fn get_len(&self, storage: &dyn Storage) -> StdResult<u32> {
-        let mut may_len = self.length.lock().unwrap();
-        if let Some(len) = *may_len {
-            Ok(len)
-        } else {
-            match self._get_u32(storage, LEN_KEY) {
-                Ok(len) => {
-                    *may_len = Some(len);
-                    Ok(len)
-                }
-                Err(e) => Err(e),
-            }
-        }
+        self._get_u32(storage, LEN_KEY)
     }
-    /// gets the offset from storage, and otherwise sets it to 0
+    /// gets the offset from storage, and otherwise returns 0
     pub fn get_off(&self, storage: &dyn Storage) -> StdResult<u32> {
-        let mut may_off = self.offset.lock().unwrap();
-        if let Some(len) = *may_off {
-            Ok(len)
-        } else {
-            match self._get_u32(storage, OFFSET_KEY) {
-                Ok(len) => {
-                    *may_off = Some(len);
-                    Ok(len)
-                }
-                Err(e) => Err(e),
-            }
-        }
+        self._get_u32(storage, OFFSET_KEY)
     }
     /// gets offset or length
     fn _get_u32(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<u32> {
@@ -124,7 +133,25 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
     }
     /// tries to get the element at pos
     fn get_at_unchecked(&self, storage: &dyn Storage, pos: u32) -> StdResult<T> {
-        self.load_impl(storage, &self._get_offset_pos(storage, pos)?.to_be_bytes())
+        let off = self.get_off(storage)?;
+        self.get_at_with_off(storage, off, pos)
+    }
+    /// tries to get the element at pos, given the offset that was already read from storage
+    pub(crate) fn get_at_with_off(
+        &self,
+        storage: &dyn Storage,
+        off: u32,
+        pos: u32,
+    ) -> StdResult<T> {
+        let offset_pos = pos.overflowing_add(off).0;
+        if self.bucket_size == 1 {
+            return self.load_impl(storage, &offset_pos.to_be_bytes());
+        }
+        let (bucket, slot) = self.bucket_pos(offset_pos);
+        let (first, items) = self.load_bucket(storage, bucket)?;
+        slot.checked_sub(first)
+            .and_then(|index| items.into_iter().nth(index))
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))
     }
     /// add the offset to the pos
     fn _get_offset_pos(&self, storage: &dyn Storage, pos: u32) -> StdResult<u32> {
@@ -133,14 +160,10 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
     }
     /// Set the length of the collection
     fn set_len(&self, storage: &mut dyn Storage, len: u32) {
-        let mut may_len = self.length.lock().unwrap();
-        *may_len = Some(len);
         self._set_u32(storage, LEN_KEY, len)
     }
     /// Set the offset of the collection
     fn set_off(&self, storage: &mut dyn Storage, off: u32) {
-        let mut may_off = self.offset.lock().unwrap();
-        *may_off = Some(off);
         self._set_u32(storage, OFFSET_KEY, off)
     }
     /// Set the length or offset of the collection
@@ -163,8 +186,22 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
     }
     /// Sets data at a given index
     fn set_at_unchecked(&self, storage: &mut dyn Storage, pos: u32, item: &T) -> StdResult<()> {
-        let off = self._get_offset_pos(storage, pos)?.to_be_bytes();
-        self.save_impl(storage, &off, item)
+        let off = self.get_off(storage)?;
+        self.set_at_with_off(storage, off, pos, item)
+    }
+    /// Sets data at a given index, given the offset that was already read from storage
+    fn set_at_with_off(
+        &self,
+        storage: &mut dyn Storage,
+        off: u32,
+        pos: u32,
+        item: &T,
+    ) -> StdResult<()> {
+        let offset_pos = pos.overflowing_add(off).0;
+        if self.bucket_size == 1 {
+            return self.save_impl(storage, &offset_pos.to_be_bytes(), item);
+        }
+        self.save_to_buckets(storage, offset_pos, vec![owned_copy::<T, Ser>(item)?])
     }
     /// Pushes an item to the back
     pub fn push_back(&self, storage: &mut dyn Storage, item: &T) -> StdResult<()> {
@@ -221,6 +258,22 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
         }
         let item = self.get_at_unchecked(storage, pos);
         let to_tail = len - pos;
+        if self.bucket_size > 1 {
+            // move the shifted items a bucket at a time
+            let (start, end, to) = if to_tail < pos {
+                (pos + 1, len, pos)
+            } else {
+                (0, pos, 1)
+            };
+            let shifted =
+                DequeStoreIter::new(self, storage, start, end).collect::<StdResult<Vec<T>>>()?;
+            self.save_to_buckets(storage, to.overflowing_add(off).0, shifted)?;
+            if to_tail >= pos {
+                self.set_off(storage, off.overflowing_add(1).0);
+            }
+            self.set_len(storage, len - 1);
+            return item;
+        }
         if to_tail < pos {
             // closer to the tail
             for i in pos..(len - 1) {
@@ -238,6 +291,111 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
         self.set_len(storage, len - 1);
         item
     }
+    /// Pushes every item of `items` to the back, writing the length only once
+    pub fn extend<I>(&self, storage: &mut dyn Storage, items: I) -> StdResult<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        let items: Vec<I::Item> = items.into_iter().collect();
+        if items.is_empty() {
+            return Ok(());
+        }
+        let len = self.get_len(storage)?;
+        let refs: Vec<&T> = items.iter().map(<I::Item as Borrow<T>>::borrow).collect();
+        self.save_run(storage, len, &refs)?;
+        self.set_len(storage, len + refs.len() as u32);
+        Ok(())
+    }
+    /// Shortens the collection to its first `len` items. Does nothing if it isn't longer than
+    /// that.
+    pub fn truncate(&self, storage: &mut dyn Storage, len: u32) -> StdResult<()> {
+        if len < self.get_len(storage)? {
+            self.set_len(storage, len);
+        }
+        Ok(())
+    }
+    /// Removes the element at the specified position and returns it, moving the last element
+    /// into its place.
+    ///
+    /// Unlike `remove`, this has a constant cost, but it doesn't preserve the order of the
+    /// collection.
+    pub fn swap_remove(&self, storage: &mut dyn Storage, pos: u32) -> StdResult<T> {
+        let len = self.get_len(storage)?;
+        if pos >= len {
+            return Err(StdError::generic_err("DequeStore access out of bounds"));
+        }
+        let item = self.get_at_unchecked(storage, pos)?;
+        let last = len - 1;
+        if pos != last {
+            let last_item = self.get_at_unchecked(storage, last)?;
+            self.set_at_unchecked(storage, pos, &last_item)?;
+        }
+        self.set_len(storage, last);
+        Ok(item)
+    }
+    /// Keeps only the elements for which `keep` returns true, preserving their order.
+    ///
+    /// Every element is read once, and the kept elements are written from the first removed
+    /// position on.
+    pub fn retain<F>(&self, storage: &mut dyn Storage, mut keep: F) -> StdResult<()>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let items = self.iter(storage)?.collect::<StdResult<Vec<T>>>()?;
+        let mut first_removed = None;
+        let mut kept = vec![];
+        for (pos, item) in (0..).zip(items) {
+            if keep(&item) {
+                if first_removed.is_some() {
+                    kept.push(item);
+                }
+            } else if first_removed.is_none() {
+                first_removed = Some(pos);
+            }
+        }
+        if let Some(first) = first_removed {
+            let len = first + kept.len() as u32;
+            self.save_owned_run(storage, first, kept)?;
+            self.set_len(storage, len);
+        }
+        Ok(())
+    }
+    /// Removes the elements in `range` and returns them.
+    ///
+    /// Like `remove`, only the elements between the range and the closest tip of the collection
+    /// are shifted in storage, so draining from the head or the tail doesn't move any element.
+    pub fn drain(
+        &self,
+        storage: &mut dyn Storage,
+        range: impl RangeBounds<u32>,
+    ) -> StdResult<Vec<T>> {
+        let len = self.get_len(storage)?;
+        let (start, end) = resolve_range(range, len)?;
+        let count = end - start;
+        if count == 0 {
+            return Ok(vec![]);
+        }
+        let drained = if start < len - end {
+            // closer to the head
+            let mut head =
+                DequeStoreIter::new(self, storage, 0, end).collect::<StdResult<Vec<T>>>()?;
+            let drained = head.split_off(start as usize);
+            self.save_owned_run(storage, count, head)?;
+            let off = self.get_off(storage)?;
+            self.set_off(storage, off.overflowing_add(count).0);
+            drained
+        } else {
+            // closer to the tail
+            let mut drained =
+                DequeStoreIter::new(self, storage, start, len).collect::<StdResult<Vec<T>>>()?;
+            let tail = drained.split_off(count as usize);
+            self.save_owned_run(storage, start, tail)?;
+            drained
+        };
+        self.set_len(storage, len - count);
+        Ok(drained)
+    }
     /// Returns a readonly iterator
     pub fn iter(&self, storage: &'a dyn Storage) -> StdResult<DequeStoreIter<T, Ser>> {
         let len = self.get_len(storage)?;
@@ -253,7 +411,7 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
     }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
+impl<'a, T, Ser: DequeStoreFormat<T>> DequeStore<'a, T, Ser> {
     fn as_slice(&self) -> &[u8] {
         if let Some(prefix) = &self.prefix {
             prefix
@@ -271,7 +429,7 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
     /// * `key` - a byte slice representing the key to access the stored item
     fn load_impl(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<T> {
         let prefixed_key = [self.as_slice(), key].concat();
-        Ser::deserialize(
+        <Ser as Format<T>>::decode(
             &storage
                 .get(&prefixed_key)
                 .ok_or_else(|| StdError::not_found(type_name::<T>()))?,
@@ -287,40 +445,491 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
     /// * `value` - a reference to the item to store
     fn save_impl(&self, storage: &mut dyn Storage, key: &[u8], value: &T) -> StdResult<()> {
         let prefixed_key = [self.as_slice(), key].concat();
-        storage.set(&prefixed_key, &Ser::serialize(value)?);
+        storage.set(&prefixed_key, &<Ser as Format<T>>::encode(value)?);
         Ok(())
     }
+
+    /// Returns the bucket holding the item at an offset position, and the item's index within
+    /// that bucket
+    fn bucket_pos(&self, offset_pos: u32) -> (u32, usize) {
+        (
+            offset_pos / self.bucket_size,
+            (offset_pos % self.bucket_size) as usize,
+        )
+    }
+
+    /// Returns the index of the first item stored in a bucket and the consecutive items stored
+    /// from there on. Items are pushed to the front of a bucket as well, so it doesn't
+    /// necessarily start at index 0. A bucket may hold stale items outside of the collection.
+    fn load_bucket(&self, storage: &dyn Storage, bucket: u32) -> StdResult<(usize, Vec<T>)> {
+        let key = [self.as_slice(), &bucket.to_be_bytes()].concat();
+        match storage.get(&key) {
+            Some(bytes) => {
+                let (first, items) = <Ser as Format<(u32, Vec<T>)>>::decode(&bytes)?;
+                Ok((first as usize, items))
+            }
+            None => Ok((0, vec![])),
+        }
+    }
+
+    /// Writes borrowed items to consecutive positions
+    fn save_run(&self, storage: &mut dyn Storage, pos: u32, items: &[&T]) -> StdResult<()> {
+        let offset_pos = self._get_offset_pos(storage, pos)?;
+        if self.bucket_size == 1 {
+            for (index, item) in (0..).zip(items) {
+                self.save_impl(storage, &offset_pos.wrapping_add(index).to_be_bytes(), item)?;
+            }
+            return Ok(());
+        }
+        let items = items
+            .iter()
+            .map(|item| owned_copy::<T, Ser>(item))
+            .collect::<StdResult<Vec<T>>>()?;
+        self.save_to_buckets(storage, offset_pos, items)
+    }
+
+    /// Writes items that were read from the store to consecutive positions, e.g. to shift them
+    fn save_owned_run(&self, storage: &mut dyn Storage, pos: u32, items: Vec<T>) -> StdResult<()> {
+        let offset_pos = self._get_offset_pos(storage, pos)?;
+        if self.bucket_size == 1 {
+            for (index, item) in (0..).zip(&items) {
+                self.save_impl(storage, &offset_pos.wrapping_add(index).to_be_bytes(), item)?;
+            }
+            return Ok(());
+        }
+        self.save_to_buckets(storage, offset_pos, items)
+    }
+
+    /// Writes items to consecutive offset positions, reading and writing every bucket they touch
+    /// only once
+    fn save_to_buckets(
+        &self,
+        storage: &mut dyn Storage,
+        offset_pos: u32,
+        items: Vec<T>,
+    ) -> StdResult<()> {
+        let mut items = (0..).zip(items).peekable();
+        while let Some(&(index, _)) = items.peek() {
+            let bucket = self.bucket_pos(offset_pos.wrapping_add(index)).0;
+            let (mut first, mut stored) = self.load_bucket(storage, bucket)?;
+            while let Some((index, item)) = items
+                .next_if(|(index, _)| self.bucket_pos(offset_pos.wrapping_add(*index)).0 == bucket)
+            {
+                let slot = self.bucket_pos(offset_pos.wrapping_add(index)).1;
+                if stored.is_empty() || slot + 1 < first || slot > first + stored.len() {
+                    // the write is not next to the stored items, so none of them are still
+                    // part of the collection
+                    first = slot;
+                    stored = vec![item];
+                } else if slot + 1 == first {
+                    first = slot;
+                    stored.insert(0, item);
+                } else if slot == first + stored.len() {
+                    stored.push(item);
+                } else {
+                    stored[slot - first] = item;
+                }
+            }
+
+            let key = [self.as_slice(), &bucket.to_be_bytes()].concat();
+            let bucket_value = (first as u32, stored);
+            storage.set(
+                &key,
+                &<Ser as Format<(u32, Vec<T>)>>::encode(&bucket_value)?,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T, Ser: DequeStoreFormat<T>> DequeStore<'a, T, Ser> {
+    /// If you import the proper DequeStore from the remote contract, this will let you read the
+    /// length of the remote collection using a raw query
+    pub fn query_len<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<u32> {
+        self._query_u32(querier, remote_contract, remote_contract_code_hash, LEN_KEY)
+    }
+    /// gets the offset of the remote collection using a raw query
+    pub fn query_off<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<u32> {
+        self._query_u32(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            OFFSET_KEY,
+        )
+    }
+    /// gets the element at pos of the remote collection if within bounds
+    pub fn query_at<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        pos: u32,
+    ) -> StdResult<T> {
+        let len = self.query_len(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+        )?;
+        if pos >= len {
+            return Err(StdError::generic_err("DequeStore access out of bounds"));
+        }
+        let off = self.query_off(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+        )?;
+        self._query_at_offset_pos(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            pos.overflowing_add(off).0,
+        )
+    }
+    /// does paging over the remote collection with the given parameters
+    pub fn query_paging<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<T>> {
+        let len = self.query_len(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+        )?;
+        let off = self.query_off(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+        )?;
+        let start = start_page.saturating_mul(size).min(len);
+        let end = start.saturating_add(size).min(len);
+        if self.bucket_size > 1 {
+            // query every bucket of the page once
+            let mut bucket: Option<(u32, usize, Vec<Option<T>>)> = None;
+            return (start..end)
+                .map(|pos| {
+                    let (index, slot) = self.bucket_pos(pos.overflowing_add(off).0);
+                    if !matches!(&bucket, Some((cached, _, _)) if *cached == index) {
+                        let (first, items) = self.query_bucket(
+                            querier,
+                            remote_contract.clone(),
+                            remote_contract_code_hash.clone(),
+                            index,
+                        )?;
+                        bucket = Some((index, first, items.into_iter().map(Some).collect()));
+                    }
+                    bucket
+                        .as_mut()
+                        .and_then(|(_, first, items)| items.get_mut(slot.checked_sub(*first)?))
+                        .and_then(Option::take)
+                        .ok_or_else(|| StdError::not_found(type_name::<T>()))
+                })
+                .collect();
+        }
+        (start..end)
+            .map(|pos| {
+                self._query_at_offset_pos(
+                    querier,
+                    remote_contract.clone(),
+                    remote_contract_code_hash.clone(),
+                    pos.overflowing_add(off).0,
+                )
+            })
+            .collect()
+    }
+    /// queries offset or length
+    fn _query_u32<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        key: &[u8],
+    ) -> StdResult<u32> {
+        let num_key = [self.as_slice(), key].concat();
+        let result = query_raw(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            num_key.into(),
+        )?;
+        parse_raw_u32(&result)
+    }
+    /// queries the element stored at an already offset position
+    fn _query_at_offset_pos<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        offset_pos: u32,
+    ) -> StdResult<T> {
+        if self.bucket_size > 1 {
+            let (bucket, slot) = self.bucket_pos(offset_pos);
+            let (first, items) =
+                self.query_bucket(querier, remote_contract, remote_contract_code_hash, bucket)?;
+            return slot
+                .checked_sub(first)
+                .and_then(|index| items.into_iter().nth(index))
+                .ok_or_else(|| StdError::not_found(type_name::<T>()));
+        }
+        let key = [self.as_slice(), &offset_pos.to_be_bytes()].concat();
+        let result = query_raw(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            key.into(),
+        )?;
+        if result.is_empty() {
+            return Err(StdError::not_found(type_name::<T>()));
+        }
+        <Ser as Format<T>>::decode(&result)
+    }
+    /// queries the first index and the items of a bucket
+    fn query_bucket<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        bucket: u32,
+    ) -> StdResult<(usize, Vec<T>)> {
+        let key = [self.as_slice(), &bucket.to_be_bytes()].concat();
+        let result = query_raw(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            key.into(),
+        )?;
+        if result.is_empty() {
+            return Ok((0, vec![]));
+        }
+        let (first, items) = <Ser as Format<(u32, Vec<T>)>>::decode(&result)?;
+        Ok((first as usize, items))
+    }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> Clone for DequeStore<'a, T, Ser> {
+impl<'a, T, Ser: DequeStoreFormat<T>> Clone for DequeStore<'a, T, Ser> {
     fn clone(&self) -> Self {
         Self {
             namespace: self.namespace,
             prefix: self.prefix.clone(),
-            length: Mutex::new(None),
-            offset: Mutex::new(None),
+            bucket_size: self.bucket_size,
             item_type: self.item_type,
             serialization_type: self.serialization_type,
         }
     }
 }
 
+/// A [`DequeStore`] bound to one storage for a single execution, built with
+/// [`DequeStore::cached`].
+///
+/// Like [`CachedAppendStore`](crate::CachedAppendStore), the length and the offset are read from
+/// storage the first time they are needed and then kept in the handle, which holds the only
+/// borrow of the storage while it is alive.
+///
+/// ```rust
+/// # use cosmwasm_std::testing::MockStorage;
+/// use secret_storage_plus::DequeStore;
+///
+/// const QUEUE: DequeStore<u64> = DequeStore::new("queue");
+///
+/// # let mut storage = MockStorage::new();
+/// let mut queue = QUEUE.cached(&mut storage);
+/// for i in 0..10 {
+///     queue.push_back(&i)?;
+/// }
+/// assert_eq!(queue.pop_front()?, 0);
+/// assert_eq!(queue.get_len()?, 9);
+/// # Ok::<(), cosmwasm_std::StdError>(())
+/// ```
+pub struct CachedDequeStore<'a, 's, T, Ser = Json>
+where
+    Ser: DequeStoreFormat<T>,
+{
+    store: DequeStore<'a, T, Ser>,
+    storage: &'s mut dyn Storage,
+    len: Cell<Option<u32>>,
+    off: Cell<Option<u32>>,
+}
+
+impl<'a, 's, T, Ser: DequeStoreFormat<T>> CachedDequeStore<'a, 's, T, Ser> {
+    /// gets the length, reading it from storage only the first time
+    pub fn get_len(&self) -> StdResult<u32> {
+        match self.len.get() {
+            Some(len) => Ok(len),
+            None => {
+                let len = self.store.get_len(self.storage)?;
+                self.len.set(Some(len));
+                Ok(len)
+            }
+        }
+    }
+    /// gets the offset, reading it from storage only the first time
+    pub fn get_off(&self) -> StdResult<u32> {
+        match self.off.get() {
+            Some(off) => Ok(off),
+            None => {
+                let off = self.store.get_off(self.storage)?;
+                self.off.set(Some(off));
+                Ok(off)
+            }
+        }
+    }
+    /// checks if the collection has any elements
+    pub fn is_empty(&self) -> StdResult<bool> {
+        Ok(self.get_len()? == 0)
+    }
+    /// gets the element at pos if within bounds
+    pub fn get_at(&self, pos: u32) -> StdResult<T> {
+        if pos >= self.get_len()? {
+            return Err(StdError::generic_err("DequeStore access out of bounds"));
+        }
+        self.store
+            .get_at_with_off(self.storage, self.get_off()?, pos)
+    }
+    /// Replaces data at a position within bounds
+    pub fn set_at(&mut self, pos: u32, item: &T) -> StdResult<()> {
+        if pos >= self.get_len()? {
+            return Err(StdError::generic_err("DequeStore access out of bounds"));
+        }
+        let off = self.get_off()?;
+        self.store.set_at_with_off(self.storage, off, pos, item)
+    }
+    /// Pushes an item to the back
+    pub fn push_back(&mut self, item: &T) -> StdResult<()> {
+        let len = self.get_len()?;
+        let off = self.get_off()?;
+        self.store.set_at_with_off(self.storage, off, len, item)?;
+        self.set_len(len + 1);
+        Ok(())
+    }
+    /// Pushes an item to the front
+    pub fn push_front(&mut self, item: &T) -> StdResult<()> {
+        let len = self.get_len()?;
+        let off = self.get_off()?.overflowing_sub(1).0;
+        self.set_off(off);
+        self.store.set_at_with_off(self.storage, off, 0, item)?;
+        self.set_len(len + 1);
+        Ok(())
+    }
+    /// Pops an item from the back
+    pub fn pop_back(&mut self) -> StdResult<T> {
+        if let Some(len) = self.get_len()?.checked_sub(1) {
+            let item = self
+                .store
+                .get_at_with_off(self.storage, self.get_off()?, len);
+            self.set_len(len);
+            item
+        } else {
+            Err(StdError::generic_err("Can not pop from empty DequeStore"))
+        }
+    }
+    /// Pops an item from the front
+    pub fn pop_front(&mut self) -> StdResult<T> {
+        if let Some(len) = self.get_len()?.checked_sub(1) {
+            let off = self.get_off()?;
+            let item = self.store.get_at_with_off(self.storage, off, 0);
+            self.set_len(len);
+            self.set_off(off.overflowing_add(1).0);
+            item
+        } else {
+            Err(StdError::generic_err("Can not pop from empty DequeStore"))
+        }
+    }
+    /// Shortens the collection to its first `len` items. Does nothing if it isn't longer than
+    /// that.
+    pub fn truncate(&mut self, len: u32) -> StdResult<()> {
+        if len < self.get_len()? {
+            self.set_len(len);
+        }
+        Ok(())
+    }
+    /// Clear the collection
+    pub fn clear(&mut self) {
+        self.set_len(0);
+        self.set_off(0);
+    }
+    /// Pushes every item of `items` to the back, see [`DequeStore::extend`]
+    pub fn extend<I>(&mut self, items: I) -> StdResult<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        self.len.set(None);
+        self.store.extend(self.storage, items)
+    }
+    /// Removes an element, see [`DequeStore::remove`]
+    pub fn remove(&mut self, pos: u32) -> StdResult<T> {
+        self.len.set(None);
+        self.off.set(None);
+        self.store.remove(self.storage, pos)
+    }
+    /// Keeps only the elements for which `keep` returns true, see [`DequeStore::retain`]
+    pub fn retain<F>(&mut self, keep: F) -> StdResult<()>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.len.set(None);
+        self.store.retain(self.storage, keep)
+    }
+    /// Removes the elements in `range` and returns them, see [`DequeStore::drain`]
+    pub fn drain(&mut self, range: impl RangeBounds<u32>) -> StdResult<Vec<T>> {
+        self.len.set(None);
+        self.off.set(None);
+        self.store.drain(self.storage, range)
+    }
+    /// Returns a readonly iterator
+    pub fn iter(&self) -> StdResult<DequeStoreIter<T, Ser>> {
+        let mut iter = DequeStoreIter::new(&self.store, self.storage, 0, self.get_len()?);
+        iter.offset = Some(self.get_off()?);
+        Ok(iter)
+    }
+    /// does paging with the given parameters
+    pub fn paging(&self, start_page: u32, size: u32) -> StdResult<Vec<T>> {
+        self.iter()?
+            .skip((start_page as usize) * (size as usize))
+            .take(size as usize)
+            .collect()
+    }
+    fn set_len(&mut self, len: u32) {
+        self.store.set_len(self.storage, len);
+        self.len.set(Some(len));
+    }
+    fn set_off(&mut self, off: u32) {
+        self.store.set_off(self.storage, off);
+        self.off.set(Some(off));
+    }
+}
+
 /// An iterator over the contents of the deque store.
 pub struct DequeStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: DequeStoreFormat<T>,
 {
     deque_store: &'a DequeStore<'a, T, Ser>,
     storage: &'a dyn Storage,
     start: u32,
     end: u32,
+    /// the last bucket read by a bucketed store, so each bucket is only loaded once
+    bucket: Option<(u32, usize, Vec<Option<T>>)>,
+    /// the offset of the store, read from storage once
+    offset: Option<u32>,
 }
 
 impl<'a, T, Ser> DequeStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: DequeStoreFormat<T>,
 {
     /// constructor
     pub fn new(
@@ -334,14 +943,37 @@ where
             storage,
             start,
             end,
+            bucket: None,
+            offset: None,
         }
     }
+
+    fn get_at(&mut self, pos: u32) -> StdResult<T> {
+        let off = match self.offset {
+            Some(off) => off,
+            None => *self.offset.insert(self.deque_store.get_off(self.storage)?),
+        };
+        if self.deque_store.bucket_size == 1 {
+            // `start..end` is within the length read when the iterator was built
+            return self.deque_store.get_at_with_off(self.storage, off, pos);
+        }
+        let (bucket, slot) = self.deque_store.bucket_pos(pos.overflowing_add(off).0);
+        if !matches!(&self.bucket, Some((cached, _, _)) if *cached == bucket) {
+            let (first, items) = self.deque_store.load_bucket(self.storage, bucket)?;
+            self.bucket = Some((bucket, first, items.into_iter().map(Some).collect()));
+        }
+        // every position is yielded once, so the item can be moved out of the cached bucket
+        self.bucket
+            .as_mut()
+            .and_then(|(_, first, items)| items.get_mut(slot.checked_sub(*first)?))
+            .and_then(Option::take)
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))
+    }
 }
 
 impl<'a, T, Ser> Iterator for DequeStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: DequeStoreFormat<T>,
 {
     type Item = StdResult<T>;
 
@@ -349,7 +981,7 @@ where
         if self.start >= self.end {
             return None;
         }
-        let item = self.deque_store.get_at(self.storage, self.start);
+        let item = self.get_at(self.start);
         self.start += 1;
         Some(item)
     }
@@ -374,15 +1006,14 @@ where
 
 impl<'a, T, Ser> DoubleEndedIterator for DequeStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: DequeStoreFormat<T>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         if self.start >= self.end {
             return None;
         }
         self.end -= 1;
-        let item = self.deque_store.get_at(self.storage, self.end);
+        let item = self.get_at(self.end);
         Some(item)
     }
 
@@ -399,18 +1030,15 @@ where
 }
 
 // This enables writing `deque_store.iter().skip(n).rev()`
-impl<'a, T, Ser> ExactSizeIterator for DequeStoreIter<'a, T, Ser>
-where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
-{
-}
+impl<'a, T, Ser> ExactSizeIterator for DequeStoreIter<'a, T, Ser> where Ser: DequeStoreFormat<T> {}
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::MockStorage;
 
-    use crate::Json;
+    use crate::helpers::{mock_remote_querier, CountingStorage};
+    use crate::{Bincode2, Json};
+    use cosmwasm_std::Empty;
 
     use super::*;
 
@@ -631,4 +1259,265 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_remote_queries() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<u32, Bincode2> = DequeStore::new("test");
+        for i in 3..8 {
+            deque_store.push_back(&mut storage, &i)?;
+        }
+        for i in (0..3).rev() {
+            deque_store.push_front(&mut storage, &i)?;
+        }
+
+        let remote = mock_remote_querier(storage);
+        let querier = QuerierWrapper::<Empty>::new(&remote);
+        let contract = Addr::unchecked("remote");
+        let hash = "hash".to_string();
+
+        assert_eq!(
+            deque_store.query_len(&querier, contract.clone(), hash.clone())?,
+            8
+        );
+        assert_eq!(
+            deque_store.query_at(&querier, contract.clone(), hash.clone(), 0)?,
+            0
+        );
+        assert_eq!(
+            deque_store.query_at(&querier, contract.clone(), hash.clone(), 5)?,
+            5
+        );
+        assert!(deque_store
+            .query_at(&querier, contract.clone(), hash.clone(), 8)
+            .is_err());
+        assert_eq!(
+            deque_store.query_paging(&querier, contract.clone(), hash.clone(), 0, 3)?,
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            deque_store.query_paging(&querier, contract, hash, 2, 3)?,
+            vec![6, 7]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_operations() -> StdResult<()> {
+        for bucket_size in [1, 3] {
+            let mut storage = MockStorage::new();
+            let deque_store: DequeStore<u32> =
+                DequeStore::new_with_bucket_size("test", bucket_size);
+            deque_store.extend(&mut storage, [2, 3, 4, 5])?;
+            deque_store.push_front(&mut storage, &1)?;
+            deque_store.push_front(&mut storage, &0)?;
+            deque_store.extend(&mut storage, &[6, 7, 8, 9])?;
+            assert_eq!(
+                deque_store.paging(&storage, 0, 10)?,
+                vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+            );
+
+            assert_eq!(deque_store.swap_remove(&mut storage, 1)?, 1);
+            assert_eq!(deque_store.swap_remove(&mut storage, 8)?, 8);
+            assert!(deque_store.swap_remove(&mut storage, 8).is_err());
+            assert_eq!(
+                deque_store.paging(&storage, 0, 10)?,
+                vec![0, 9, 2, 3, 4, 5, 6, 7]
+            );
+
+            deque_store.retain(&mut storage, |item| item % 3 != 0)?;
+            assert_eq!(deque_store.paging(&storage, 0, 10)?, vec![2, 4, 5, 7]);
+
+            deque_store.extend(&mut storage, [10, 11, 12])?;
+            // closer to the head
+            assert_eq!(deque_store.drain(&mut storage, 1..3)?, vec![4, 5]);
+            assert_eq!(deque_store.paging(&storage, 0, 10)?, vec![2, 7, 10, 11, 12]);
+            // closer to the tail
+            assert_eq!(deque_store.drain(&mut storage, 2..4)?, vec![10, 11]);
+            assert_eq!(deque_store.paging(&storage, 0, 10)?, vec![2, 7, 12]);
+            assert!(deque_store.drain(&mut storage, ..4).is_err());
+            assert_eq!(deque_store.drain(&mut storage, 1..1)?, vec![]);
+            deque_store.push_front(&mut storage, &1)?;
+            assert_eq!(
+                deque_store.iter(&storage)?.collect::<StdResult<Vec<_>>>()?,
+                vec![1, 2, 7, 12]
+            );
+
+            deque_store.truncate(&mut storage, 5)?;
+            assert_eq!(deque_store.get_len(&storage)?, 4);
+            deque_store.truncate(&mut storage, 2)?;
+            deque_store.push_back(&mut storage, &20)?;
+            assert_eq!(deque_store.paging(&storage, 0, 10)?, vec![1, 2, 20]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_reads_length_and_offset_once() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let deque_store: DequeStore<u32> = DequeStore::new("test");
+        deque_store.extend(&mut storage, [0, 1, 2, 3, 4])?;
+
+        // the length and the offset are read once, then one read per item
+        storage.reads.set(0);
+        assert_eq!(
+            deque_store
+                .iter(&storage)?
+                .collect::<StdResult<Vec<_>>>()?
+                .len(),
+            5
+        );
+        assert_eq!(storage.reads.get(), 2 + 5);
+
+        storage.reads.set(0);
+        assert_eq!(deque_store.paging(&storage, 1, 2)?, vec![2, 3]);
+        assert_eq!(storage.reads.get(), 2 + 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cached_handle() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let deque_store: DequeStore<u32> = DequeStore::new("test");
+        deque_store.extend(&mut storage, [0, 1, 2])?;
+
+        storage.reads.set(0);
+        {
+            let mut cached = deque_store.cached(&mut storage);
+            cached.push_back(&3)?;
+            cached.push_front(&4)?;
+            assert_eq!(cached.get_at(4)?, 3);
+            assert!(cached.get_at(5).is_err());
+            assert_eq!(cached.pop_back()?, 3);
+            assert_eq!(cached.pop_front()?, 4);
+            cached.push_front(&5)?;
+            assert_eq!(cached.paging(0, 10)?, vec![5, 0, 1, 2]);
+        }
+        // the length and the offset are read once, then one read per loaded item
+        assert_eq!(storage.reads.get(), 2 + 1 + 1 + 1 + 4);
+        assert_eq!(deque_store.paging(&storage, 0, 10)?, vec![5, 0, 1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_operations_write_length_once() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let deque_store: DequeStore<u32> = DequeStore::new("test");
+        deque_store.extend(&mut storage, [0, 1, 2, 3, 4])?;
+        // one write per item and one for the length
+        assert_eq!(storage.writes, 6);
+
+        // draining from the head only moves the offset
+        storage.writes = 0;
+        assert_eq!(deque_store.drain(&mut storage, ..2)?, vec![0, 1]);
+        assert_eq!(storage.writes, 2);
+
+        storage.writes = 0;
+        deque_store.retain(&mut storage, |item| *item != 3)?;
+        assert_eq!(storage.writes, 2);
+        assert_eq!(deque_store.paging(&storage, 0, 10)?, vec![2, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bucketed() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<u32> = DequeStore::new_with_bucket_size("test", 4);
+        for i in 3..10 {
+            deque_store.push_back(&mut storage, &i)?;
+        }
+        for i in (0..3).rev() {
+            deque_store.push_front(&mut storage, &i)?;
+        }
+        assert_eq!(deque_store.get_len(&storage)?, 10);
+
+        // items pushed to the front fill the last bucket from its end
+        let key = [deque_store.as_slice(), &(u32::MAX / 4).to_be_bytes()].concat();
+        assert_eq!(storage.get(&key), Some(b"[1,[0,1,2]]".to_vec()));
+        let key = [deque_store.as_slice(), &0_u32.to_be_bytes()].concat();
+        assert_eq!(storage.get(&key), Some(b"[0,[3,4,5,6]]".to_vec()));
+
+        assert_eq!(
+            deque_store.iter(&storage)?.collect::<StdResult<Vec<_>>>()?,
+            (0..10).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            deque_store
+                .iter(&storage)?
+                .rev()
+                .collect::<StdResult<Vec<_>>>()?,
+            (0..10).rev().collect::<Vec<_>>()
+        );
+        assert_eq!(deque_store.paging(&storage, 1, 4)?, vec![4, 5, 6, 7]);
+
+        deque_store.set_at(&mut storage, 2, &20)?;
+        assert_eq!(deque_store.remove(&mut storage, 1)?, 1);
+        assert_eq!(deque_store.remove(&mut storage, 7)?, 8);
+        assert_eq!(deque_store.remove(&mut storage, 3)?, 4);
+        assert_eq!(
+            deque_store.paging(&storage, 0, 10)?,
+            vec![0, 20, 3, 5, 6, 7, 9]
+        );
+
+        assert_eq!(deque_store.pop_front(&mut storage)?, 0);
+        assert_eq!(deque_store.pop_back(&mut storage)?, 9);
+        deque_store.push_front(&mut storage, &1)?;
+        assert_eq!(
+            deque_store.paging(&storage, 0, 10)?,
+            vec![1, 20, 3, 5, 6, 7]
+        );
+
+        deque_store.clear(&mut storage);
+        deque_store.push_back(&mut storage, &100)?;
+        deque_store.push_front(&mut storage, &99)?;
+        assert_eq!(deque_store.paging(&storage, 0, 10)?, vec![99, 100]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bucketed_remote_queries() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        // u32::MAX is a multiple of 3, so the last bucket only holds a single item
+        let deque_store: DequeStore<u32, Bincode2> = DequeStore::new_with_bucket_size("test", 3);
+        for i in 3..8 {
+            deque_store.push_back(&mut storage, &i)?;
+        }
+        for i in (0..3).rev() {
+            deque_store.push_front(&mut storage, &i)?;
+        }
+        assert_eq!(
+            deque_store.iter(&storage)?.collect::<StdResult<Vec<_>>>()?,
+            (0..8).collect::<Vec<_>>()
+        );
+
+        let remote = mock_remote_querier(storage);
+        let querier = QuerierWrapper::<Empty>::new(&remote);
+        let contract = Addr::unchecked("remote");
+        let hash = "hash".to_string();
+
+        assert_eq!(
+            deque_store.query_at(&querier, contract.clone(), hash.clone(), 0)?,
+            0
+        );
+        assert_eq!(
+            deque_store.query_at(&querier, contract.clone(), hash.clone(), 5)?,
+            5
+        );
+        assert_eq!(
+            deque_store.query_paging(&querier, contract.clone(), hash.clone(), 0, 3)?,
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            deque_store.query_paging(&querier, contract.clone(), hash.clone(), 1, 3)?,
+            vec![3, 4, 5]
+        );
+        assert_eq!(
+            deque_store.query_paging(&querier, contract, hash, 2, 3)?,
+            vec![6, 7]
+        );
+
+        Ok(())
+    }
 }
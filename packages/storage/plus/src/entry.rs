@@ -0,0 +1,310 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{StdResult, Storage};
+
+use crate::{Format, Json};
+
+/// A view into a single key of a `Map` or `Path`, which is either occupied or vacant.
+///
+/// The value is read once when the entry is created, and every method writes at most once, so
+/// replacing a `has` and `load` pair with an entry saves a storage read.
+pub enum Entry<'s, T, Ser = Json>
+where
+    Ser: Format<T>,
+{
+    Occupied(OccupiedEntry<'s, T, Ser>),
+    Vacant(VacantEntry<'s, T, Ser>),
+}
+
+/// An entry with a value stored at its key
+pub struct OccupiedEntry<'s, T, Ser = Json>
+where
+    Ser: Format<T>,
+{
+    store: &'s mut dyn Storage,
+    storage_key: Vec<u8>,
+    value: T,
+    serialization_type: PhantomData<*const Ser>,
+}
+
+/// An entry with nothing stored at its key
+pub struct VacantEntry<'s, T, Ser = Json>
+where
+    Ser: Format<T>,
+{
+    store: &'s mut dyn Storage,
+    storage_key: Vec<u8>,
+    // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
+    data_type: PhantomData<T>,
+    serialization_type: PhantomData<*const Ser>,
+}
+
+impl<'s, T, Ser> Entry<'s, T, Ser>
+where
+    Ser: Format<T>,
+{
+    /// Reads the value stored at `storage_key`
+    pub(crate) fn new(store: &'s mut dyn Storage, storage_key: Vec<u8>) -> StdResult<Self> {
+        Ok(match store.get(&storage_key) {
+            Some(value) => Entry::Occupied(OccupiedEntry {
+                value: Ser::decode(&value)?,
+                store,
+                storage_key,
+                serialization_type: PhantomData,
+            }),
+            None => Entry::Vacant(VacantEntry {
+                store,
+                storage_key,
+                data_type: PhantomData,
+                serialization_type: PhantomData,
+            }),
+        })
+    }
+
+    /// Returns the full storage key of the entry
+    pub fn key(&self) -> &[u8] {
+        match self {
+            Entry::Occupied(entry) => &entry.storage_key,
+            Entry::Vacant(entry) => &entry.storage_key,
+        }
+    }
+
+    /// Returns the stored value, inserting `default` first if the entry is vacant
+    pub fn or_insert(self, default: T) -> StdResult<T> {
+        match self {
+            Entry::Occupied(entry) => Ok(entry.into_value()),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Returns the stored value, inserting the result of `default` first if the entry is vacant
+    pub fn or_insert_with<F: FnOnce() -> T>(self, default: F) -> StdResult<T> {
+        match self {
+            Entry::Occupied(entry) => Ok(entry.into_value()),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Modifies and saves the stored value if the entry is occupied. Chaining `or_insert` after
+    /// it still writes at most once, as only one of them can write.
+    pub fn and_modify<F: FnOnce(&mut T)>(self, f: F) -> StdResult<Self> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(&mut entry.value);
+                entry.save()?;
+                Ok(Entry::Occupied(entry))
+            }
+            Entry::Vacant(entry) => Ok(Entry::Vacant(entry)),
+        }
+    }
+
+    /// Removes the stored value and returns it, if there is one
+    pub fn take(self) -> Option<T> {
+        match self {
+            Entry::Occupied(entry) => Some(entry.remove()),
+            Entry::Vacant(_) => None,
+        }
+    }
+
+    /// Stores `value` and returns the value it replaced, if there was one
+    pub fn replace(self, value: T) -> StdResult<Option<T>> {
+        match self {
+            Entry::Occupied(mut entry) => entry.insert(value).map(Some),
+            Entry::Vacant(entry) => entry.insert(value).map(|_| None),
+        }
+    }
+
+    /// Removes the stored value if `predicate` returns true for it, and returns the removed value
+    pub fn remove_if<F: FnOnce(&T) -> bool>(self, predicate: F) -> Option<T> {
+        match self {
+            Entry::Occupied(entry) if predicate(&entry.value) => Some(entry.remove()),
+            _ => None,
+        }
+    }
+}
+
+impl<'s, T, Ser> OccupiedEntry<'s, T, Ser>
+where
+    Ser: Format<T>,
+{
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Changes made through the reference are only stored by `insert` or `Entry::and_modify`
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Stores `value` and returns the value it replaced
+    pub fn insert(&mut self, value: T) -> StdResult<T> {
+        let old = std::mem::replace(&mut self.value, value);
+        self.save()?;
+        Ok(old)
+    }
+
+    /// Removes the stored value and returns it
+    pub fn remove(self) -> T {
+        self.store.remove(&self.storage_key);
+        self.value
+    }
+
+    fn save(&mut self) -> StdResult<()> {
+        self.store
+            .set(&self.storage_key, &Ser::encode(&self.value)?);
+        Ok(())
+    }
+}
+
+impl<'s, T, Ser> VacantEntry<'s, T, Ser>
+where
+    Ser: Format<T>,
+{
+    /// Stores `value` and returns it
+    pub fn insert(self, value: T) -> StdResult<T> {
+        self.store.set(&self.storage_key, &Ser::encode(&value)?);
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::helpers::CountingStorage;
+    use crate::{Bincode2, Map, Serde};
+    use cosmwasm_std::testing::MockStorage;
+    use rstest::*;
+
+    const BALANCES: Map<&str, u64> = Map::new("balances");
+    const B_BALANCES: Map<&str, u64, Bincode2> = Map::new("balances");
+
+    #[rstest]
+    fn or_insert_works(#[values(BALANCES, B_BALANCES)] map: Map<&str, u64, impl Serde>) {
+        let mut store = MockStorage::new();
+        assert_eq!(
+            map.entry(&mut store, "alice")
+                .unwrap()
+                .or_insert(5)
+                .unwrap(),
+            5
+        );
+        assert_eq!(
+            map.entry(&mut store, "alice")
+                .unwrap()
+                .or_insert(7)
+                .unwrap(),
+            5
+        );
+        assert_eq!(
+            map.entry(&mut store, "bob")
+                .unwrap()
+                .or_insert_with(|| 3)
+                .unwrap(),
+            3
+        );
+        assert_eq!(map.load(&store, "alice").unwrap(), 5);
+        assert_eq!(map.load(&store, "bob").unwrap(), 3);
+    }
+
+    #[rstest]
+    fn and_modify_works(#[values(BALANCES, B_BALANCES)] map: Map<&str, u64, impl Serde>) {
+        let mut store = MockStorage::new();
+        for _ in 0..3 {
+            map.entry(&mut store, "alice")
+                .unwrap()
+                .and_modify(|balance| *balance += 10)
+                .unwrap()
+                .or_insert(1)
+                .unwrap();
+        }
+        assert_eq!(map.load(&store, "alice").unwrap(), 21);
+    }
+
+    #[rstest]
+    fn take_replace_and_remove_if_work(
+        #[values(BALANCES, B_BALANCES)] map: Map<&str, u64, impl Serde>,
+    ) {
+        let mut store = MockStorage::new();
+        assert_eq!(map.entry(&mut store, "alice").unwrap().take(), None);
+        assert_eq!(
+            map.entry(&mut store, "alice").unwrap().replace(4).unwrap(),
+            None
+        );
+        assert_eq!(
+            map.entry(&mut store, "alice").unwrap().replace(8).unwrap(),
+            Some(4)
+        );
+        assert_eq!(
+            map.entry(&mut store, "alice")
+                .unwrap()
+                .remove_if(|balance| *balance < 5),
+            None
+        );
+        assert_eq!(map.load(&store, "alice").unwrap(), 8);
+        assert_eq!(
+            map.entry(&mut store, "alice")
+                .unwrap()
+                .remove_if(|balance| *balance > 5),
+            Some(8)
+        );
+        assert!(!map.has(&store, "alice"));
+
+        map.save(&mut store, "bob", &2).unwrap();
+        assert_eq!(map.entry(&mut store, "bob").unwrap().take(), Some(2));
+        assert!(!map.has(&store, "bob"));
+    }
+
+    #[test]
+    fn reads_once_and_writes_at_most_once() {
+        let mut store = CountingStorage::default();
+
+        BALANCES
+            .entry(&mut store, "alice")
+            .unwrap()
+            .and_modify(|balance| *balance += 1)
+            .unwrap()
+            .or_insert(1)
+            .unwrap();
+        assert_eq!((store.reads.get(), store.writes), (1, 1));
+
+        BALANCES
+            .entry(&mut store, "alice")
+            .unwrap()
+            .and_modify(|balance| *balance += 1)
+            .unwrap()
+            .or_insert(1)
+            .unwrap();
+        assert_eq!((store.reads.get(), store.writes), (2, 2));
+
+        BALANCES.entry(&mut store, "bob").unwrap().take();
+        assert_eq!((store.reads.get(), store.writes), (3, 2));
+
+        BALANCES
+            .key("alice")
+            .entry(&mut store)
+            .unwrap()
+            .remove_if(|_| true);
+        assert_eq!((store.reads.get(), store.writes), (4, 3));
+        assert!(!BALANCES.has(&store, "alice"));
+    }
+
+    #[test]
+    fn occupied_entry_works() {
+        let mut store = MockStorage::new();
+        BALANCES.save(&mut store, "alice", &1).unwrap();
+        match BALANCES.entry(&mut store, "alice").unwrap() {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(*entry.get(), 1);
+                *entry.get_mut() += 1;
+                let value = *entry.get();
+                assert_eq!(entry.insert(value * 10).unwrap(), 2);
+            }
+            Entry::Vacant(_) => panic!("entry should be occupied"),
+        }
+        assert_eq!(BALANCES.load(&store, "alice").unwrap(), 20);
+    }
+}
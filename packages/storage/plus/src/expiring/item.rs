@@ -0,0 +1,157 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Env, StdError, StdResult, Storage};
+
+use crate::expiring::{Expiration, ExpiringFormat};
+use crate::serialization::owned_copy;
+use crate::{Format, Json};
+
+/// Item whose value expires. An expired value loads as absent, and stays in storage until it is
+/// overwritten or removed by `sweep`.
+pub struct ExpiringItem<'a, T, Ser = Json> {
+    storage_key: &'a [u8],
+    // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
+    data_type: PhantomData<T>,
+    serialization_type: PhantomData<*const Ser>,
+}
+
+impl<'a, T, Ser> ExpiringItem<'a, T, Ser> {
+    pub const fn new(storage_key: &'a str) -> Self {
+        ExpiringItem {
+            storage_key: storage_key.as_bytes(),
+            data_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, Ser> ExpiringItem<'a, T, Ser>
+where
+    Ser: ExpiringFormat<T>,
+{
+    // this gets the path of the data to use elsewhere
+    pub fn as_slice(&self) -> &[u8] {
+        self.storage_key
+    }
+
+    /// save will serialize the model and store it together with its expiration
+    pub fn save(&self, store: &mut dyn Storage, data: &T, expires: Expiration) -> StdResult<()> {
+        let value = (expires, owned_copy::<T, Ser>(data)?);
+        store.set(
+            self.storage_key,
+            &<Ser as Format<(Expiration, T)>>::encode(&value)?,
+        );
+        Ok(())
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage) {
+        store.remove(self.storage_key);
+    }
+
+    /// load will return an error if no data is set at the given key, if it expired, or on parse
+    /// error
+    pub fn load(&self, store: &dyn Storage, env: &Env) -> StdResult<T> {
+        self.may_load(store, env)?
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))
+    }
+
+    /// may_load will parse the data stored at the key if present and not expired, returns
+    /// `Ok(None)` otherwise
+    pub fn may_load(&self, store: &dyn Storage, env: &Env) -> StdResult<Option<T>> {
+        Ok(self
+            .may_load_with_expiration(store, env)?
+            .map(|(data, _)| data))
+    }
+
+    /// Like `may_load`, but also returns when the value expires
+    pub fn may_load_with_expiration(
+        &self,
+        store: &dyn Storage,
+        env: &Env,
+    ) -> StdResult<Option<(T, Expiration)>> {
+        Ok(self
+            .load_raw(store)?
+            .filter(|(expires, _)| !expires.is_expired(&env.block))
+            .map(|(expires, data)| (data, expires)))
+    }
+
+    /// Returns true if a value is stored and not expired
+    pub fn exists(&self, store: &dyn Storage, env: &Env) -> StdResult<bool> {
+        Ok(self.may_load_with_expiration(store, env)?.is_some())
+    }
+
+    /// Removes the stored value if it expired. Returns true if it was removed.
+    pub fn sweep(&self, store: &mut dyn Storage, env: &Env) -> StdResult<bool> {
+        match self.load_raw(store)? {
+            Some((expires, _)) if expires.is_expired(&env.block) => {
+                self.remove(store);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn load_raw(&self, store: &dyn Storage) -> StdResult<Option<(Expiration, T)>> {
+        store
+            .get(self.storage_key)
+            .map(|value| <Ser as Format<(Expiration, T)>>::decode(&value))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Bincode2;
+    use cosmwasm_std::testing::{mock_env, MockStorage};
+
+    const COOLDOWN: ExpiringItem<u64> = ExpiringItem::new("cooldown");
+
+    #[test]
+    fn expires() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let mut env = mock_env();
+        assert_eq!(COOLDOWN.may_load(&storage, &env)?, None);
+
+        let expires = Expiration::AtHeight(env.block.height + 10);
+        COOLDOWN.save(&mut storage, &5, expires)?;
+        assert_eq!(COOLDOWN.load(&storage, &env)?, 5);
+        assert_eq!(
+            COOLDOWN.may_load_with_expiration(&storage, &env)?,
+            Some((5, expires))
+        );
+        assert!(!COOLDOWN.sweep(&mut storage, &env)?);
+
+        env.block.height += 10;
+        assert!(!COOLDOWN.exists(&storage, &env)?);
+        assert!(COOLDOWN.load(&storage, &env).is_err());
+        // the expired value stays in storage until it is swept
+        assert!(storage.get(COOLDOWN.as_slice()).is_some());
+        assert!(COOLDOWN.sweep(&mut storage, &env)?);
+        assert!(storage.get(COOLDOWN.as_slice()).is_none());
+
+        COOLDOWN.save(&mut storage, &6, Expiration::Never)?;
+        env.block.height = u64::MAX;
+        assert_eq!(COOLDOWN.may_load(&storage, &env)?, Some(6));
+        Ok(())
+    }
+
+    #[test]
+    fn expires_at_time() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let mut env = mock_env();
+        let item: ExpiringItem<String, Bincode2> = ExpiringItem::new("nonce");
+
+        item.save(
+            &mut storage,
+            &"abc".to_string(),
+            Expiration::AtTime(env.block.time.plus_seconds(60)),
+        )?;
+        env.block.time = env.block.time.plus_seconds(59);
+        assert_eq!(item.may_load(&storage, &env)?, Some("abc".to_string()));
+        env.block.time = env.block.time.plus_seconds(1);
+        assert_eq!(item.may_load(&storage, &env)?, None);
+        Ok(())
+    }
+}
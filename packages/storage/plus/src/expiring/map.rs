@@ -0,0 +1,298 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use cosmwasm_std::{BlockInfo, Env, StdError, StdResult, Storage, Timestamp};
+
+use crate::expiring::{Expiration, ExpiringFormat};
+use crate::helpers::namespaces_with_key;
+use crate::keys::PrimaryKey;
+use crate::serialization::owned_copy;
+use crate::{Format, Json, SortedMap, SortedMapFormat};
+
+/// Map whose values expire. Expired values load as absent, and stay in storage until they are
+/// overwritten, removed, or purged by `sweep`.
+///
+/// Every value that can expire also has an entry in an index ordered by expiration, which lets
+/// `sweep` find the expired values without going through the whole map.
+pub struct ExpiringMap<'a, K, V, Ser = Json> {
+    namespace: &'a [u8],
+    /// the expirations and joined keys of the values that can expire. The index only uses the
+    /// keys of the sorted map, its values are always `true`.
+    index: SortedMap<'a, (Expiration, Vec<u8>), bool, Ser>,
+    // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
+    key_type: PhantomData<K>,
+    data_type: PhantomData<V>,
+}
+
+impl<'a, K, V, Ser> ExpiringMap<'a, K, V, Ser> {
+    pub const fn new(namespace: &'a str, index_namespace: &'a str) -> Self {
+        ExpiringMap {
+            namespace: namespace.as_bytes(),
+            index: SortedMap::new(index_namespace),
+            key_type: PhantomData,
+            data_type: PhantomData,
+        }
+    }
+
+    pub fn namespace(&self) -> &'a [u8] {
+        self.namespace
+    }
+}
+
+impl<'a, K, V, Ser> ExpiringMap<'a, K, V, Ser>
+where
+    K: PrimaryKey<'a>,
+    Ser: ExpiringFormat<V> + SortedMapFormat<(Expiration, Vec<u8>), bool>,
+{
+    fn storage_key(&self, joined_key: &[u8]) -> Vec<u8> {
+        namespaces_with_key(&[self.namespace], joined_key)
+    }
+
+    fn load_raw(
+        &self,
+        store: &dyn Storage,
+        joined_key: &[u8],
+    ) -> StdResult<Option<(Expiration, V)>> {
+        store
+            .get(&self.storage_key(joined_key))
+            .map(|value| <Ser as Format<(Expiration, V)>>::decode(&value))
+            .transpose()
+    }
+
+    /// save will serialize the model and store it together with its expiration, replacing the
+    /// expiration of a previous value at the key
+    pub fn save(
+        &self,
+        store: &mut dyn Storage,
+        k: K,
+        data: &V,
+        expires: Expiration,
+    ) -> StdResult<()> {
+        let joined_key = k.joined_key();
+        let previous = self
+            .load_raw(store, &joined_key)?
+            .map(|(expires, _)| expires);
+        if previous != Some(expires) {
+            if let Some(previous) = previous {
+                self.index.remove(store, &(previous, joined_key.clone()))?;
+            }
+            if expires != Expiration::Never {
+                self.index
+                    .save(store, &(expires, joined_key.clone()), &true)?;
+            }
+        }
+        let value = (expires, owned_copy::<V, Ser>(data)?);
+        store.set(
+            &self.storage_key(&joined_key),
+            &<Ser as Format<(Expiration, V)>>::encode(&value)?,
+        );
+        Ok(())
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage, k: K) -> StdResult<()> {
+        let joined_key = k.joined_key();
+        if let Some((expires, _)) = self.load_raw(store, &joined_key)? {
+            self.index.remove(store, &(expires, joined_key.clone()))?;
+            store.remove(&self.storage_key(&joined_key));
+        }
+        Ok(())
+    }
+
+    /// load will return an error if no data is set at the given key, if it expired, or on parse
+    /// error
+    pub fn load(&self, store: &dyn Storage, env: &Env, k: K) -> StdResult<V> {
+        self.may_load(store, env, k)?
+            .ok_or_else(|| StdError::not_found(type_name::<V>()))
+    }
+
+    /// may_load will parse the data stored at the key if present and not expired, returns
+    /// `Ok(None)` otherwise
+    pub fn may_load(&self, store: &dyn Storage, env: &Env, k: K) -> StdResult<Option<V>> {
+        Ok(self
+            .may_load_with_expiration(store, env, k)?
+            .map(|(data, _)| data))
+    }
+
+    /// Like `may_load`, but also returns when the value expires
+    pub fn may_load_with_expiration(
+        &self,
+        store: &dyn Storage,
+        env: &Env,
+        k: K,
+    ) -> StdResult<Option<(V, Expiration)>> {
+        Ok(self
+            .load_raw(store, &k.joined_key())?
+            .filter(|(expires, _)| !expires.is_expired(&env.block))
+            .map(|(expires, data)| (data, expires)))
+    }
+
+    /// Returns true if a value is stored at the key and not expired
+    pub fn has(&self, store: &dyn Storage, env: &Env, k: K) -> StdResult<bool> {
+        Ok(self.may_load_with_expiration(store, env, k)?.is_some())
+    }
+
+    /// Removes up to `limit` expired values, the ones that expired first, and returns how many
+    /// were removed. Values expiring at a height are removed before values expiring at a time.
+    ///
+    /// Calling it with a small limit in every transaction keeps storage clean while bounding the
+    /// gas it uses.
+    pub fn sweep(&self, store: &mut dyn Storage, env: &Env, limit: u32) -> StdResult<u32> {
+        let mut expired = self.expired_keys(store, &env.block, Expiration::AtHeight(0), limit)?;
+        let remaining = limit - expired.len() as u32;
+        if remaining > 0 {
+            expired.extend(self.expired_keys(
+                store,
+                &env.block,
+                Expiration::AtTime(Timestamp::from_nanos(0)),
+                remaining,
+            )?);
+        }
+
+        for index_key in &expired {
+            self.index.remove(store, index_key)?;
+            store.remove(&self.storage_key(&index_key.1));
+        }
+        Ok(expired.len() as u32)
+    }
+
+    /// Returns the index keys of up to `limit` expired values that expire the same way as `from`,
+    /// which must be the smallest expiration of its kind
+    fn expired_keys(
+        &self,
+        store: &dyn Storage,
+        block: &BlockInfo,
+        from: Expiration,
+        limit: u32,
+    ) -> StdResult<Vec<(Expiration, Vec<u8>)>> {
+        let same_kind = |expires: &Expiration| {
+            matches!(
+                (expires, from),
+                (Expiration::AtHeight(_), Expiration::AtHeight(_))
+                    | (Expiration::AtTime(_), Expiration::AtTime(_))
+            )
+        };
+
+        let mut keys = vec![];
+        let mut iter = self.index.range(store, (from, vec![])..)?;
+        while keys.len() < limit as usize {
+            let key = match iter.next_key() {
+                Some(key) => key?,
+                None => break,
+            };
+            if !same_kind(&key.0) || !key.0.is_expired(block) {
+                break;
+            }
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Bincode2;
+    use cosmwasm_std::testing::{mock_env, MockStorage};
+
+    const NONCES: ExpiringMap<&str, u64> = ExpiringMap::new("nonces", "nonces_exp");
+
+    #[test]
+    fn expires() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let mut env = mock_env();
+        let height = env.block.height;
+
+        NONCES.save(&mut storage, "a", &1, Expiration::AtHeight(height + 1))?;
+        NONCES.save(&mut storage, "b", &2, Expiration::AtHeight(height + 2))?;
+        NONCES.save(&mut storage, "c", &3, Expiration::Never)?;
+        assert_eq!(NONCES.load(&storage, &env, "a")?, 1);
+        assert_eq!(
+            NONCES.may_load_with_expiration(&storage, &env, "b")?,
+            Some((2, Expiration::AtHeight(height + 2)))
+        );
+
+        env.block.height += 1;
+        assert_eq!(NONCES.may_load(&storage, &env, "a")?, None);
+        assert!(NONCES.load(&storage, &env, "a").is_err());
+        assert!(NONCES.has(&storage, &env, "b")?);
+
+        env.block.height = u64::MAX;
+        assert!(!NONCES.has(&storage, &env, "b")?);
+        assert_eq!(NONCES.may_load(&storage, &env, "c")?, Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn resave_and_remove() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let mut env = mock_env();
+        let height = env.block.height;
+
+        NONCES.save(&mut storage, "a", &1, Expiration::AtHeight(height + 1))?;
+        NONCES.save(&mut storage, "a", &2, Expiration::AtHeight(height + 5))?;
+        NONCES.save(&mut storage, "b", &3, Expiration::AtHeight(height + 1))?;
+        NONCES.save(&mut storage, "b", &4, Expiration::Never)?;
+        NONCES.save(&mut storage, "c", &5, Expiration::AtHeight(height + 1))?;
+        NONCES.remove(&mut storage, "c")?;
+        NONCES.remove(&mut storage, "d")?;
+        // only the latest expiration of "a" is left in the index
+        assert_eq!(NONCES.index.len(&storage)?, 1);
+
+        env.block.height += 1;
+        assert_eq!(NONCES.sweep(&mut storage, &env, 10)?, 0);
+        assert_eq!(NONCES.load(&storage, &env, "a")?, 2);
+        assert_eq!(NONCES.load(&storage, &env, "b")?, 4);
+
+        env.block.height += 4;
+        assert_eq!(NONCES.sweep(&mut storage, &env, 10)?, 1);
+        assert!(NONCES.index.is_empty(&storage)?);
+        assert!(storage.get(&NONCES.storage_key(b"a")).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn sweep_with_limit() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let mut env = mock_env();
+        let map: ExpiringMap<u32, String, Bincode2> = ExpiringMap::new("allowances", "expiry");
+        let height = env.block.height;
+        let time = env.block.time;
+
+        for i in 0..4 {
+            map.save(
+                &mut storage,
+                i,
+                &format!("h{}", i),
+                Expiration::AtHeight(height + 4 - i as u64),
+            )?;
+            map.save(
+                &mut storage,
+                10 + i,
+                &format!("t{}", i),
+                Expiration::AtTime(time.plus_seconds(4 - i as u64)),
+            )?;
+        }
+        map.save(&mut storage, 20, &"never".to_string(), Expiration::Never)?;
+
+        // nothing expired yet
+        assert_eq!(map.sweep(&mut storage, &env, 10)?, 0);
+
+        env.block.height += 2;
+        env.block.time = env.block.time.plus_seconds(3);
+        // the keys expiring at the lowest height are removed first
+        assert_eq!(map.sweep(&mut storage, &env, 1)?, 1);
+        assert!(storage.get(&map.storage_key(&3u32.joined_key())).is_none());
+        assert!(storage.get(&map.storage_key(&2u32.joined_key())).is_some());
+        // then the remaining expired heights, and the times
+        assert_eq!(map.sweep(&mut storage, &env, 2)?, 2);
+        assert!(storage.get(&map.storage_key(&13u32.joined_key())).is_none());
+        assert_eq!(map.sweep(&mut storage, &env, 10)?, 2);
+        assert_eq!(map.sweep(&mut storage, &env, 10)?, 0);
+
+        for i in [0, 1, 10, 20] {
+            assert!(map.has(&storage, &env, i)?);
+        }
+        assert_eq!(map.index.len(&storage)?, 3);
+        Ok(())
+    }
+}
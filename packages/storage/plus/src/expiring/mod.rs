@@ -0,0 +1,77 @@
+//! Expiring storage keeps an expiration next to every stored value. Expired values are treated
+//! as absent when they are loaded, and `ExpiringMap::sweep` removes them from storage a few at a
+//! time, oldest first.
+mod item;
+mod map;
+
+pub use item::ExpiringItem;
+pub use map::ExpiringMap;
+
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{BlockInfo, Timestamp};
+
+use crate::Format;
+
+/// The formats an [`ExpiringItem`] or [`ExpiringMap`] of `T` can be stored with. The values are
+/// stored after their expiration, as `(Expiration, T)`. Every [`Serde`](crate::Serde) format
+/// implements it for serde values.
+pub trait ExpiringFormat<T>: Format<T> + Format<(Expiration, T)> {}
+
+impl<T, Ser> ExpiringFormat<T> for Ser where Ser: Format<T> + Format<(Expiration, T)> {}
+
+/// When a stored value expires.
+///
+/// Expirations are ordered by kind first, heights before times, and then by their value.
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh_derive::BorshSerialize, borsh_derive::BorshDeserialize)
+)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Expiration {
+    /// expires once the block height reaches this height
+    AtHeight(u64),
+    /// expires once the block time reaches this time
+    AtTime(
+        #[cfg_attr(
+            feature = "borsh",
+            borsh(
+                serialize_with = "crate::serialization::borsh::timestamp::serialize",
+                deserialize_with = "crate::serialization::borsh::timestamp::deserialize"
+            )
+        )]
+        Timestamp,
+    ),
+    /// never expires
+    Never,
+}
+
+impl Expiration {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        match self {
+            Expiration::AtHeight(height) => block.height >= *height,
+            Expiration::AtTime(time) => block.time >= *time,
+            Expiration::Never => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::testing::mock_env;
+
+    #[test]
+    fn is_expired() {
+        let block = mock_env().block;
+        assert!(Expiration::AtHeight(block.height).is_expired(&block));
+        assert!(!Expiration::AtHeight(block.height + 1).is_expired(&block));
+        assert!(Expiration::AtTime(block.time).is_expired(&block));
+        assert!(!Expiration::AtTime(block.time.plus_seconds(1)).is_expired(&block));
+        assert!(!Expiration::Never.is_expired(&block));
+
+        assert!(Expiration::AtHeight(u64::MAX) < Expiration::AtTime(Timestamp::from_nanos(0)));
+        assert!(Expiration::AtTime(Timestamp::from_nanos(u64::MAX)) < Expiration::Never);
+    }
+}
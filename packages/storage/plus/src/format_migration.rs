@@ -0,0 +1,362 @@
+//! Helpers to move existing state to another serialization format in place, e.g. from `Json` to
+//! `Bincode2`. Every value is decoded with the old format, encoded with the new one, and checked
+//! to decode back to the same value before it is written.
+//!
+//! Collections are migrated in pages, so that a contract can bound the gas used by each `migrate`
+//! call and continue with the next page in a later transaction. A value must not be migrated
+//! twice: formats that are not self-describing may decode the new bytes as garbage instead of
+//! failing.
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::type_name;
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::de::KeyDeserialize;
+use crate::helpers::namespaces_with_key;
+use crate::keys::PrimaryKey;
+use crate::{AppendStore, AppendStoreFormat, Format, Keymap, Map, Serde};
+
+/// Counts of one migration batch
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// number of storage entries that were re-encoded
+    pub migrated: u32,
+    /// number of requested keys that had no value stored
+    pub missing: u32,
+}
+
+/// Re-encodes the item stored at `storage_key` from `Old` to `New`.
+///
+/// ```rust
+/// # use cosmwasm_std::testing::MockStorage;
+/// use secret_storage_plus::{migrate_item, Bincode2, Item, Json};
+///
+/// # let mut storage = MockStorage::new();
+/// Item::<u64, Json>::new("config").save(&mut storage, &7).unwrap();
+/// migrate_item::<u64, Json, Bincode2>(&mut storage, "config").unwrap();
+/// assert_eq!(Item::<u64, Bincode2>::new("config").load(&storage).unwrap(), 7);
+/// ```
+pub fn migrate_item<T, Old, New>(
+    storage: &mut dyn Storage,
+    storage_key: &str,
+) -> StdResult<MigrationReport>
+where
+    T: Serialize + DeserializeOwned,
+    Old: Serde,
+    New: Format<T>,
+{
+    let mut report = MigrationReport::default();
+    reencode_entry(
+        storage,
+        storage_key.as_bytes(),
+        None,
+        reencode::<T, Old, New>,
+        &mut report,
+    )?;
+    Ok(report)
+}
+
+/// Re-encodes the values of `from` at the given keys into `to`. Both maps usually share their
+/// namespace, otherwise the values are moved out of `from`.
+pub fn migrate_map<'a, K, T, Old, New>(
+    storage: &mut dyn Storage,
+    from: &Map<'a, K, T, Old>,
+    to: &Map<'a, K, T, New>,
+    keys: impl IntoIterator<Item = K>,
+) -> StdResult<MigrationReport>
+where
+    K: PrimaryKey<'a>,
+    T: Serialize + DeserializeOwned,
+    Old: Serde,
+    New: Format<T>,
+{
+    let mut report = MigrationReport::default();
+    for k in keys {
+        let to_key = to.key(k.clone());
+        reencode_entry(
+            storage,
+            &from.key(k),
+            Some(&*to_key),
+            reencode::<T, Old, New>,
+            &mut report,
+        )?;
+    }
+    Ok(report)
+}
+
+/// Re-encodes the values of `from` at the keys of one page of `index` into `to`. The keymap is
+/// only used as an index of the keys of the map, its own values are left untouched.
+pub fn migrate_map_keymap_page<'a, 'k, K, T, Old, New, KK, V, KSer>(
+    storage: &mut dyn Storage,
+    from: &Map<'a, K, T, Old>,
+    to: &Map<'a, K, T, New>,
+    index: &Keymap<'k, KK, V, KSer>,
+    start_page: u32,
+    size: u32,
+) -> StdResult<MigrationReport>
+where
+    T: Serialize + DeserializeOwned,
+    Old: Serde,
+    New: Format<T>,
+    KK: PrimaryKey<'k> + KeyDeserialize,
+    V: Serialize + DeserializeOwned,
+    KSer: Serde,
+{
+    let mut report = MigrationReport::default();
+    for key in index.paging_raw_keys(storage, start_page, size)? {
+        let from_key = namespaces_with_key(&[from.namespace()], &key);
+        let to_key = namespaces_with_key(&[to.namespace()], &key);
+        reencode_entry(
+            storage,
+            &from_key,
+            Some(to_key.as_slice()),
+            reencode::<T, Old, New>,
+            &mut report,
+        )?;
+    }
+    Ok(report)
+}
+
+/// Re-encodes one page of the storage entries of an append store. Pages count storage entries,
+/// which are whole buckets for stores created with `new_with_bucket_size`.
+///
+/// Both stores must use the same prefix and bucket size, as the items are migrated in place.
+pub fn migrate_append_store<T, Old, New>(
+    storage: &mut dyn Storage,
+    from: &AppendStore<T, Old>,
+    to: &AppendStore<T, New>,
+    start_page: u32,
+    size: u32,
+) -> StdResult<MigrationReport>
+where
+    T: Serialize + DeserializeOwned,
+    Old: Serde,
+    New: AppendStoreFormat<T>,
+{
+    if !from.same_layout(to) {
+        return Err(StdError::generic_err(
+            "AppendStore migrations must keep the prefix and bucket size",
+        ));
+    }
+    let count = from.entry_count(storage)?;
+    let start = start_page.saturating_mul(size).min(count);
+    let end = start.saturating_add(size).min(count);
+
+    let mut report = MigrationReport::default();
+    for entry in start..end {
+        let key = from.entry_key(entry);
+        if from.is_bucketed() {
+            reencode_entry(
+                storage,
+                &key,
+                None,
+                reencode::<Vec<T>, Old, New>,
+                &mut report,
+            )?;
+        } else {
+            reencode_entry(storage, &key, None, reencode::<T, Old, New>, &mut report)?;
+        }
+    }
+    Ok(report)
+}
+
+/// Re-encodes the value stored at `from_key` with `reencode`, and writes it to `to_key` if it is
+/// given, or back to `from_key` otherwise.
+fn reencode_entry(
+    storage: &mut dyn Storage,
+    from_key: &[u8],
+    to_key: Option<&[u8]>,
+    reencode: fn(&[u8]) -> StdResult<Vec<u8>>,
+    report: &mut MigrationReport,
+) -> StdResult<()> {
+    let value = match storage.get(from_key) {
+        Some(value) => value,
+        None => {
+            report.missing += 1;
+            return Ok(());
+        }
+    };
+    let reencoded = reencode(&value)?;
+    match to_key {
+        Some(to_key) if to_key != from_key => {
+            storage.remove(from_key);
+            storage.set(to_key, &reencoded);
+        }
+        _ => storage.set(from_key, &reencoded),
+    }
+    report.migrated += 1;
+    Ok(())
+}
+
+/// Converts a value from `Old` to `New`, and checks that the result decodes to the same value
+fn reencode<T, Old, New>(value: &[u8]) -> StdResult<Vec<u8>>
+where
+    T: Serialize + DeserializeOwned,
+    Old: Serde,
+    New: Format<T>,
+{
+    let decoded: T = Old::deserialize(value)?;
+    let reencoded = New::encode(&decoded)?;
+    let round_trip = New::decode(&reencoded)?;
+    check_round_trip::<T, Old>(&decoded, &round_trip)?;
+    Ok(reencoded)
+}
+
+// compare through the old format, so that T doesn't need to implement PartialEq
+fn check_round_trip<T: Serialize, Old: Serde>(decoded: &T, round_trip: &T) -> StdResult<()> {
+    if Old::serialize(round_trip)? != Old::serialize(decoded)? {
+        return Err(StdError::generic_err(format!(
+            "{} doesn't round trip through the new serialization format",
+            type_name::<T>()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Bincode2, Item, Json, Postcard};
+    use cosmwasm_std::testing::MockStorage;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Data {
+        name: String,
+        age: i32,
+    }
+
+    fn data(i: u32) -> Data {
+        Data {
+            name: format!("user{}", i),
+            age: i as i32,
+        }
+    }
+
+    #[test]
+    fn migrates_items() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        Item::<Data, Json>::new("config").save(&mut storage, &data(1))?;
+
+        let report = migrate_item::<Data, Json, Bincode2>(&mut storage, "config")?;
+        assert_eq!(
+            report,
+            MigrationReport {
+                migrated: 1,
+                missing: 0
+            }
+        );
+        assert_eq!(
+            Item::<Data, Bincode2>::new("config").load(&storage)?,
+            data(1)
+        );
+        assert!(Item::<Data, Json>::new("config").load(&storage).is_err());
+
+        let report = migrate_item::<Data, Json, Bincode2>(&mut storage, "other")?;
+        assert_eq!(report.missing, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_values_of_other_types() {
+        let mut storage = MockStorage::new();
+        Item::<String, Json>::new("config")
+            .save(&mut storage, &"admin".to_string())
+            .unwrap();
+        assert!(migrate_item::<Data, Json, Bincode2>(&mut storage, "config").is_err());
+        // the value is left untouched
+        assert_eq!(
+            Item::<String, Json>::new("config").load(&storage).unwrap(),
+            "admin"
+        );
+    }
+
+    #[test]
+    fn migrates_maps_by_keymap_pages() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let old: Map<(&str, u32), Data, Json> = Map::new("people");
+        let new: Map<(&str, u32), Data, Postcard> = Map::new("people");
+        let index: Keymap<(String, u32), bool> = Keymap::new("people_index");
+
+        let names = ["alice", "bob", "carol"];
+        for (i, name) in names.iter().enumerate() {
+            old.save(&mut storage, (*name, i as u32), &data(i as u32))?;
+            index.insert(&mut storage, (name.to_string(), i as u32), &true)?;
+        }
+
+        let report = migrate_map_keymap_page(&mut storage, &old, &new, &index, 0, 2)?;
+        assert_eq!(report.migrated, 2);
+        assert_eq!(new.load(&storage, ("bob", 1))?, data(1));
+        assert_eq!(old.load(&storage, ("carol", 2))?, data(2));
+
+        let report = migrate_map_keymap_page(&mut storage, &old, &new, &index, 1, 2)?;
+        assert_eq!(report.migrated, 1);
+        for (i, name) in names.iter().enumerate() {
+            assert_eq!(new.load(&storage, (*name, i as u32))?, data(i as u32));
+        }
+        // the index keeps its values
+        assert_eq!(index.get(&storage, ("alice".to_string(), 0))?, Some(true));
+        Ok(())
+    }
+
+    #[test]
+    fn moves_maps_to_another_namespace() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let old: Map<u32, Data, Json> = Map::new("people");
+        let new: Map<u32, Data, Bincode2> = Map::new("people_v2");
+        old.save(&mut storage, 1, &data(1))?;
+        old.save(&mut storage, 2, &data(2))?;
+
+        let report = migrate_map(&mut storage, &old, &new, [1, 2, 3])?;
+        assert_eq!(
+            report,
+            MigrationReport {
+                migrated: 2,
+                missing: 1
+            }
+        );
+        assert_eq!(new.load(&storage, 2)?, data(2));
+        assert!(!old.has(&storage, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn migrates_append_stores() -> StdResult<()> {
+        for bucket_size in [1, 4] {
+            let mut storage = MockStorage::new();
+            let old: AppendStore<Data, Json> =
+                AppendStore::new_with_bucket_size("people", bucket_size);
+            let new: AppendStore<Data, Bincode2> =
+                AppendStore::new_with_bucket_size("people", bucket_size);
+            for i in 0..10 {
+                old.push(&mut storage, &data(i))?;
+            }
+
+            let entries = if bucket_size == 1 { 10 } else { 3 };
+            let mut page = 0;
+            let mut migrated = 0;
+            loop {
+                let report = migrate_append_store(&mut storage, &old, &new, page, 2)?;
+                if report.migrated == 0 {
+                    break;
+                }
+                migrated += report.migrated;
+                page += 1;
+            }
+            assert_eq!(migrated, entries);
+            assert_eq!(new.get_len(&storage)?, 10);
+            assert_eq!(
+                new.iter(&storage)?.collect::<StdResult<Vec<_>>>()?,
+                (0..10).map(data).collect::<Vec<_>>()
+            );
+        }
+
+        let mut storage = MockStorage::new();
+        let old: AppendStore<Data, Json> = AppendStore::new("people");
+        let new: AppendStore<Data, Bincode2> = AppendStore::new_with_bucket_size("people", 4);
+        old.push(&mut storage, &data(1))?;
+        assert!(migrate_append_store(&mut storage, &old, &new, 0, 10).is_err());
+        Ok(())
+    }
+}
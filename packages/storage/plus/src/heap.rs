@@ -0,0 +1,257 @@
+//! A binary heap kept in storage, to retrieve items smallest first.
+//!
+//! The heap is laid out like an [`AppendStore`], with one storage entry per node, so pushing and
+//! popping only touch the O(log n) nodes on the path between the root and a leaf.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use cosmwasm_std::{StdResult, Storage};
+
+use crate::{AppendStore, AppendStoreFormat, Json};
+
+/// A min-heap: `pop` and `peek` return the smallest item. Wrap items in [`Reverse`] to retrieve
+/// the largest item first.
+pub struct StorageHeap<'a, T, Ser = Json>
+where
+    T: Ord,
+    Ser: AppendStoreFormat<T>,
+{
+    store: AppendStore<'a, T, Ser>,
+}
+
+impl<'a, T: Ord, Ser: AppendStoreFormat<T>> StorageHeap<'a, T, Ser> {
+    pub const fn new(prefix: &'a str) -> Self {
+        Self {
+            store: AppendStore::new(prefix),
+        }
+    }
+
+    /// This is used to produce a new heap under the same namespace, e.g. one heap per user
+    pub fn add_suffix(&self, suffix: &str) -> Self {
+        Self {
+            store: self.store.add_suffix(suffix),
+        }
+    }
+}
+
+impl<'a, T: Ord, Ser: AppendStoreFormat<T>> StorageHeap<'a, T, Ser> {
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        self.store.get_len(storage)
+    }
+
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        self.store.is_empty(storage)
+    }
+
+    /// Returns the smallest item without removing it
+    pub fn peek(&self, storage: &dyn Storage) -> StdResult<Option<T>> {
+        if self.is_empty(storage)? {
+            return Ok(None);
+        }
+        self.store.get_at_unchecked(storage, 0).map(Some)
+    }
+
+    /// Adds an item, moving it up from the bottom of the heap until its parent is not larger
+    pub fn push(&self, storage: &mut dyn Storage, item: &T) -> StdResult<()> {
+        let len = self.len(storage)?;
+        let mut pos = len;
+        while pos > 0 {
+            let parent_pos = (pos - 1) / 2;
+            let parent = self.store.get_at_unchecked(storage, parent_pos)?;
+            if parent <= *item {
+                break;
+            }
+            self.store.set_at_unchecked(storage, pos, &parent)?;
+            pos = parent_pos;
+        }
+        self.store.set_at_unchecked(storage, pos, item)?;
+        self.store.set_len(storage, len + 1);
+        Ok(())
+    }
+
+    /// Removes and returns the smallest item. The last item takes its place and is moved down
+    /// until none of its children are smaller.
+    pub fn pop(&self, storage: &mut dyn Storage) -> StdResult<Option<T>> {
+        let len = self.len(storage)?;
+        let last_pos = match len.checked_sub(1) {
+            Some(last_pos) => last_pos,
+            None => return Ok(None),
+        };
+        let top = self.store.get_at_unchecked(storage, 0)?;
+        if last_pos > 0 {
+            let last = self.store.get_at_unchecked(storage, last_pos)?;
+            let mut pos = 0;
+            while let Some((child_pos, child)) = self.smaller_child(storage, pos, last_pos)? {
+                if last <= child {
+                    break;
+                }
+                self.store.set_at_unchecked(storage, pos, &child)?;
+                pos = child_pos;
+            }
+            self.store.set_at_unchecked(storage, pos, &last)?;
+        }
+        self.store.set_len(storage, last_pos);
+        Ok(Some(top))
+    }
+
+    /// Returns a page of the items in ascending order, without modifying the heap.
+    ///
+    /// Only the nodes that can hold one of the first `(start_page + 1) * size` items are read, so
+    /// the first pages are cheap even for a large heap.
+    pub fn into_sorted_paging(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<T>> {
+        let len = self.len(storage)?;
+        let skip = start_page.saturating_mul(size);
+        let end = skip.saturating_add(size).min(len);
+        if skip >= end {
+            return Ok(vec![]);
+        }
+
+        // the smallest items not yet returned are always among the children of returned nodes
+        let mut frontier = BinaryHeap::new();
+        frontier.push(Reverse((self.store.get_at_unchecked(storage, 0)?, 0)));
+        let mut items = vec![];
+        for index in 0..end {
+            let Reverse((item, pos)) = match frontier.pop() {
+                Some(next) => next,
+                None => break,
+            };
+            for child_pos in [2 * pos + 1, 2 * pos + 2] {
+                if child_pos < len {
+                    let child = self.store.get_at_unchecked(storage, child_pos)?;
+                    frontier.push(Reverse((child, child_pos)));
+                }
+            }
+            if index >= skip {
+                items.push(item);
+            }
+        }
+        Ok(items)
+    }
+
+    /// Removes all items
+    pub fn clear(&self, storage: &mut dyn Storage) {
+        self.store.clear(storage);
+    }
+
+    /// Returns the position and the item of the smaller child of the node at pos, if it has any
+    /// children within the first `len` nodes
+    fn smaller_child(
+        &self,
+        storage: &dyn Storage,
+        pos: u32,
+        len: u32,
+    ) -> StdResult<Option<(u32, T)>> {
+        let left = 2 * pos + 1;
+        let right = left + 1;
+        if left >= len {
+            return Ok(None);
+        }
+        let left_item = self.store.get_at_unchecked(storage, left)?;
+        if right >= len {
+            return Ok(Some((left, left_item)));
+        }
+        let right_item = self.store.get_at_unchecked(storage, right)?;
+        if right_item < left_item {
+            Ok(Some((right, right_item)))
+        } else {
+            Ok(Some((left, left_item)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::CountingStorage;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn pops_smallest_first() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let heap: StorageHeap<u32> = StorageHeap::new("heap");
+        assert_eq!(heap.peek(&storage)?, None);
+        assert_eq!(heap.pop(&mut storage)?, None);
+
+        for item in [5, 3, 8, 1, 9, 2, 7, 3] {
+            heap.push(&mut storage, &item)?;
+        }
+        assert_eq!(heap.len(&storage)?, 8);
+        assert_eq!(heap.peek(&storage)?, Some(1));
+
+        let mut popped = vec![];
+        while let Some(item) = heap.pop(&mut storage)? {
+            popped.push(item);
+        }
+        assert_eq!(popped, vec![1, 2, 3, 3, 5, 7, 8, 9]);
+        assert!(heap.is_empty(&storage)?);
+
+        let suffixed = heap.add_suffix("user");
+        suffixed.push(&mut storage, &4)?;
+        assert!(heap.is_empty(&storage)?);
+        assert_eq!(suffixed.peek(&storage)?, Some(4));
+
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_pops_largest_first() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let heap: StorageHeap<Reverse<(u64, String)>> = StorageHeap::new("heap");
+        heap.push(&mut storage, &Reverse((2, "b".to_string())))?;
+        heap.push(&mut storage, &Reverse((3, "c".to_string())))?;
+        heap.push(&mut storage, &Reverse((1, "a".to_string())))?;
+
+        assert_eq!(heap.pop(&mut storage)?, Some(Reverse((3, "c".to_string()))));
+        assert_eq!(heap.pop(&mut storage)?, Some(Reverse((2, "b".to_string()))));
+        Ok(())
+    }
+
+    #[test]
+    fn sorted_paging() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let heap: StorageHeap<u32> = StorageHeap::new("heap");
+        for item in (0..20).rev() {
+            heap.push(&mut storage, &item)?;
+        }
+
+        assert_eq!(
+            heap.into_sorted_paging(&storage, 0, 5)?,
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(heap.into_sorted_paging(&storage, 3, 6)?, vec![18, 19]);
+        assert_eq!(heap.into_sorted_paging(&storage, 4, 6)?, vec![]);
+        // paging doesn't modify the heap
+        assert_eq!(heap.len(&storage)?, 20);
+        assert_eq!(heap.peek(&storage)?, Some(0));
+        Ok(())
+    }
+
+    #[test]
+    fn operations_touch_one_path() -> StdResult<()> {
+        let mut storage = CountingStorage::default();
+        let heap: StorageHeap<u32> = StorageHeap::new("heap");
+        // 1023 nodes make a full tree of depth 10
+        for item in 0..1023 {
+            heap.push(&mut storage, &item)?;
+        }
+
+        storage.reads.set(0);
+        storage.writes = 0;
+        heap.push(&mut storage, &0)?;
+        // the length and every ancestor are read, the new item moves up to the root
+        assert!(storage.reads.get() <= 11);
+        assert!(storage.writes <= 12);
+
+        storage.reads.set(0);
+        storage.writes = 0;
+        assert_eq!(heap.pop(&mut storage)?, Some(0));
+        assert!(storage.reads.get() <= 3 + 2 * 10);
+        assert!(storage.writes <= 11);
+        Ok(())
+    }
+}
@@ -4,10 +4,11 @@
 //! Everything in this file is only responsible for building such keys
 //! and is in no way specific to any kind of storage.
 
-use serde::de::DeserializeOwned;
 use std::any::type_name;
+use std::convert::TryInto;
+use std::ops::{Bound, RangeBounds};
 
-use crate::{keys::Key, Serde};
+use crate::{keys::Key, Format};
 
 use cosmwasm_std::{
     to_vec, Addr, Binary, ContractResult, CustomQuery, QuerierWrapper, QueryRequest, StdError,
@@ -18,21 +19,17 @@ use cosmwasm_std::{
 ///
 /// value is an odd type, but this is meant to be easy to use with output from storage.get (Option<Vec<u8>>)
 /// and value.map(|s| s.as_slice()) seems trickier than &value
-pub(crate) fn may_deserialize<T: DeserializeOwned, Ser: Serde>(
-    value: &Option<Vec<u8>>,
-) -> StdResult<Option<T>> {
+pub(crate) fn may_deserialize<T, Ser: Format<T>>(value: &Option<Vec<u8>>) -> StdResult<Option<T>> {
     match value {
-        Some(vec) => Ok(Some(Ser::deserialize(vec)?)),
+        Some(vec) => Ok(Some(Ser::decode(vec)?)),
         None => Ok(None),
     }
 }
 
 /// must_deserialize parses json bytes from storage (Option), returning NotFound error if no data present
-pub(crate) fn must_deserialize<T: DeserializeOwned, Ser: Serde>(
-    value: &Option<Vec<u8>>,
-) -> StdResult<T> {
+pub(crate) fn must_deserialize<T, Ser: Format<T>>(value: &Option<Vec<u8>>) -> StdResult<T> {
     match value {
-        Some(vec) => Ok(Ser::deserialize(vec)?),
+        Some(vec) => Ok(Ser::decode(vec)?),
         None => Err(StdError::not_found(type_name::<T>())),
     }
 }
@@ -91,6 +88,22 @@ pub(crate) fn encode_length(namespace: &[u8]) -> [u8; 2] {
     [length_bytes[2], length_bytes[3]]
 }
 
+/// Splits the first length-prefixed namespace off a key built by `namespaces_with_key`.
+/// Returns None if the key doesn't start with a non-empty length-prefixed namespace.
+pub(crate) fn split_namespace(key: &[u8]) -> Option<(&[u8], &[u8])> {
+    match key {
+        [high, low, rest @ ..] => {
+            let len = u16::from_be_bytes([*high, *low]) as usize;
+            if len == 0 || len > rest.len() {
+                None
+            } else {
+                Some(rest.split_at(len))
+            }
+        }
+        _ => None,
+    }
+}
+
 /// Use this in Map/SnapshotMap/etc when you want to provide a QueryRaw helper.
 /// This is similar to querier.query(WasmQuery::Raw{}), except it does NOT parse the
 /// result, but return a possibly empty Binary to be handled by the calling code.
@@ -124,6 +137,94 @@ pub(crate) fn query_raw<Q: CustomQuery>(
     }
 }
 
+/// Parses a big endian u32 (e.g. the length of a collection) returned by `query_raw`.
+/// An empty result means the value was never written, which is handled as 0.
+pub(crate) fn parse_raw_u32(raw: &[u8]) -> StdResult<u32> {
+    if raw.is_empty() {
+        return Ok(0);
+    }
+    let bytes = raw
+        .try_into()
+        .map_err(|err| StdError::parse_err("u32", err))?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+/// Resolves a range of positions against the length of a collection, as `Vec::drain` does
+pub(crate) fn resolve_range(range: impl RangeBounds<u32>, len: u32) -> StdResult<(u32, u32)> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.saturating_add(1),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    if start > end || end > len {
+        return Err(StdError::generic_err(format!(
+            "range {}..{} out of bounds for length {}",
+            start, end, len
+        )));
+    }
+    Ok((start, end))
+}
+
+/// Builds a querier that answers every `WasmQuery::Smart` with the raw value stored under the
+/// query message in `storage`, mimicking a remote contract that exposes its state.
+#[cfg(test)]
+pub(crate) fn mock_remote_querier(
+    storage: cosmwasm_std::testing::MockStorage,
+) -> cosmwasm_std::testing::MockQuerier {
+    use cosmwasm_std::Storage;
+
+    let mut querier = cosmwasm_std::testing::MockQuerier::new(&[]);
+    querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { msg, .. } => SystemResult::Ok(ContractResult::Ok(Binary::from(
+            storage.get(msg.as_slice()).unwrap_or_default(),
+        ))),
+        _ => panic!("unexpected query {:?}", query),
+    });
+    querier
+}
+
+/// Storage that counts the reads and writes that reach it
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct CountingStorage {
+    pub inner: cosmwasm_std::testing::MockStorage,
+    pub reads: std::cell::Cell<u32>,
+    pub writes: u32,
+}
+
+#[cfg(test)]
+impl cosmwasm_std::Storage for CountingStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.reads.set(self.reads.get() + 1);
+        self.inner.get(key)
+    }
+
+    #[cfg(feature = "iterator")]
+    fn range<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = cosmwasm_std::Record> + 'a> {
+        self.inner.range(start, end, order)
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes += 1;
+        self.inner.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes += 1;
+        self.inner.remove(key)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::Json;
@@ -138,6 +239,22 @@ mod test {
         pub age: i32,
     }
 
+    #[test]
+    fn split_namespace_works() {
+        let key = namespaces_with_key(&[b"foo".as_slice(), b"bar"], b"baz");
+        let (namespace, rest) = split_namespace(&key).unwrap();
+        assert_eq!(namespace, b"foo");
+        assert_eq!(
+            split_namespace(rest).unwrap(),
+            (b"bar".as_slice(), b"baz".as_slice())
+        );
+
+        // plain keys are not length-prefixed
+        assert_eq!(split_namespace(b"config"), None);
+        assert_eq!(split_namespace(b"\x00\x00foo"), None);
+        assert_eq!(split_namespace(b"\x00"), None);
+    }
+
     #[test]
     fn encode_length_works() {
         assert_eq!(encode_length(b""), *b"\x00\x00");
@@ -197,4 +314,15 @@ mod test {
             e => panic!("Unexpected error {}", e),
         }
     }
+
+    #[test]
+    fn resolve_range_works() {
+        assert_eq!(resolve_range(.., 5).unwrap(), (0, 5));
+        assert_eq!(resolve_range(1..3, 5).unwrap(), (1, 3));
+        assert_eq!(resolve_range(1..=3, 5).unwrap(), (1, 4));
+        assert_eq!(resolve_range(2.., 5).unwrap(), (2, 5));
+        assert_eq!(resolve_range(5..5, 5).unwrap(), (5, 5));
+        assert!(resolve_range(..6, 5).is_err());
+        assert!(resolve_range((Bound::Included(3), Bound::Excluded(2)), 5).is_err());
+    }
 }
@@ -0,0 +1,364 @@
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::indexes::Index;
+use crate::keys::PrimaryKey;
+use crate::map::Map;
+#[cfg(feature = "iterator")]
+use crate::{de::KeyDeserialize, prefix::Prefix, Bound};
+use crate::{Format, Json, Path};
+#[cfg(feature = "iterator")]
+use cosmwasm_std::Order;
+
+pub trait IndexList<T> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<T>> + '_>;
+}
+
+/// `IndexedMap` works like a `Map` but has secondary indexes that are kept in sync on every
+/// `save`, `replace`, `update` and `remove`.
+pub struct IndexedMap<'a, K, T, I, Ser = Json>
+where
+    K: PrimaryKey<'a>,
+    T: Clone,
+    I: IndexList<T>,
+    Ser: Format<T>,
+{
+    primary: Map<'a, K, T, Ser>,
+    /// This is meant to be read directly to get the proper types, like:
+    /// map.idx.owner.paging(...)
+    pub idx: I,
+}
+
+impl<'a, K, T, I, Ser> IndexedMap<'a, K, T, I, Ser>
+where
+    K: PrimaryKey<'a>,
+    T: Clone,
+    I: IndexList<T>,
+    Ser: Format<T>,
+{
+    pub fn new(pk_namespace: &'a str, indexes: I) -> Self {
+        IndexedMap {
+            primary: Map::new(pk_namespace),
+            idx: indexes,
+        }
+    }
+
+    pub fn key(&self, k: K) -> Path<T, Ser> {
+        self.primary.key(k)
+    }
+}
+
+impl<'a, K, T, I, Ser> IndexedMap<'a, K, T, I, Ser>
+where
+    K: PrimaryKey<'a>,
+    T: Clone,
+    I: IndexList<T>,
+    Ser: Format<T>,
+{
+    /// save will serialize the model and store, returns an error on serialization issues.
+    /// this must load the old value to update the indexes properly
+    /// if you loaded the old value earlier in the same function, use replace to avoid needless db reads
+    pub fn save(&self, store: &mut dyn Storage, key: K, data: &T) -> StdResult<()> {
+        let old_data = self.may_load(store, key.clone())?;
+        self.replace(store, key, Some(data), old_data.as_ref())
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage, key: K) -> StdResult<()> {
+        let old_data = self.may_load(store, key.clone())?;
+        self.replace(store, key, None, old_data.as_ref())
+    }
+
+    /// replace writes data to key. old_data must be the current stored value (from a previous load)
+    /// and is used to properly update the index. This is used by save, replace, and update
+    /// and can be called directly if you want to optimize
+    pub fn replace(
+        &self,
+        store: &mut dyn Storage,
+        key: K,
+        data: Option<&T>,
+        old_data: Option<&T>,
+    ) -> StdResult<()> {
+        // this is the key *relative* to the primary map namespace
+        let pk = key.joined_key();
+        if let Some(old) = old_data {
+            for index in self.idx.get_indexes() {
+                index.remove(store, &pk, old)?;
+            }
+        }
+        if let Some(updated) = data {
+            for index in self.idx.get_indexes() {
+                index.save(store, &pk, updated)?;
+            }
+            self.primary.save(store, key, updated)?;
+        } else {
+            self.primary.remove(store, key);
+        }
+        Ok(())
+    }
+
+    /// Loads the data, perform the specified action, and store the result
+    /// in the database. This is shorthand for some common sequences, which may be useful.
+    ///
+    /// If the data exists, `action(Some(value))` is called. Otherwise `action(None)` is called.
+    pub fn update<A, E>(&self, store: &mut dyn Storage, key: K, action: A) -> Result<T, E>
+    where
+        A: FnOnce(Option<T>) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        let input = self.may_load(store, key.clone())?;
+        let old_val = input.clone();
+        let output = action(input)?;
+        self.replace(store, key, Some(&output), old_val.as_ref())?;
+        Ok(output)
+    }
+
+    // Everything else, that doesn't touch indexers, is just pass-through from self.primary,
+    // thus can be used from while iterating over indexes
+
+    /// load will return an error if no data is set at the given key, or on parse error
+    pub fn load(&self, store: &dyn Storage, key: K) -> StdResult<T> {
+        self.primary.load(store, key)
+    }
+
+    /// may_load will parse the data stored at the key if present, returns Ok(None) if no data there.
+    /// returns an error on issues parsing
+    pub fn may_load(&self, store: &dyn Storage, key: K) -> StdResult<Option<T>> {
+        self.primary.may_load(store, key)
+    }
+
+    /// Returns true if storage contains this key, without parsing or interpreting the contents.
+    pub fn has(&self, store: &dyn Storage, k: K) -> bool {
+        self.primary.has(store, k)
+    }
+}
+
+#[cfg(feature = "iterator")]
+impl<'a, K, T, I, Ser> IndexedMap<'a, K, T, I, Ser>
+where
+    K: PrimaryKey<'a>,
+    T: Clone,
+    I: IndexList<T>,
+    Ser: Format<T>,
+{
+    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix, Ser> {
+        self.primary.sub_prefix(p)
+    }
+
+    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix, Ser> {
+        self.primary.prefix(p)
+    }
+}
+
+#[cfg(feature = "iterator")]
+impl<'a, K, T, I, Ser> IndexedMap<'a, K, T, I, Ser>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    T: Clone,
+    I: IndexList<T>,
+    Ser: Format<T>,
+{
+    pub fn range<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = StdResult<(K::Output, T)>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.primary.range(store, min, max, order)
+    }
+
+    pub fn keys<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = StdResult<K::Output>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.primary.keys(store, min, max, order)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::*;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{Bincode2, MultiIndex, Serde, UniqueIndex};
+    use cosmwasm_std::testing::MockStorage;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Data {
+        pub name: String,
+        pub last_name: String,
+        pub age: u32,
+    }
+
+    struct DataIndexes<'a, Ser: Serde> {
+        // Last type parameters are for signaling pk deserialization
+        pub name: MultiIndex<'a, String, Data, String, Ser>,
+        pub age: UniqueIndex<'a, u32, Data, String, Ser>,
+    }
+
+    impl<'a, Ser: Serde> IndexList<Data> for DataIndexes<'a, Ser> {
+        fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Data>> + '_> {
+            let v: Vec<&dyn Index<Data>> = vec![&self.name, &self.age];
+            Box::new(v.into_iter())
+        }
+    }
+
+    type DataMap<'a, Ser> = IndexedMap<'a, &'a str, Data, DataIndexes<'a, Ser>, Ser>;
+
+    fn build_map<'a, Ser: Serde>() -> DataMap<'a, Ser> {
+        let indexes = DataIndexes {
+            name: MultiIndex::new(|d| d.name.clone(), "data", "data__name"),
+            age: UniqueIndex::new(|d| d.age, "data__age"),
+        };
+        IndexedMap::new("data", indexes)
+    }
+
+    fn data(name: &str, last_name: &str, age: u32) -> Data {
+        Data {
+            name: name.to_string(),
+            last_name: last_name.to_string(),
+            age,
+        }
+    }
+
+    fn save_data(store: &mut dyn Storage, map: &DataMap<impl Serde>) {
+        map.save(store, "1", &data("Maria", "Doe", 42)).unwrap();
+        map.save(store, "2", &data("Maria", "Williams", 23))
+            .unwrap();
+        map.save(store, "3", &data("John", "Wayne", 32)).unwrap();
+        map.save(store, "4", &data("Maria", "Rodriguez", 12))
+            .unwrap();
+    }
+
+    #[rstest]
+    #[case(build_map::<Json>())]
+    #[case(build_map::<Bincode2>())]
+    fn store_and_load_by_index(#[case] map: DataMap<impl Serde>) {
+        let mut store = MockStorage::new();
+        save_data(&mut store, &map);
+
+        assert_eq!(map.load(&store, "3").unwrap(), data("John", "Wayne", 32));
+
+        // multi index lookup
+        assert_eq!(map.idx.name.count(&store, "Maria".to_string()).unwrap(), 3);
+        assert_eq!(
+            map.idx
+                .name
+                .paging_keys(&store, "Maria".to_string(), 0, 10)
+                .unwrap(),
+            vec!["1".to_string(), "2".to_string(), "4".to_string()]
+        );
+        assert_eq!(
+            map.idx
+                .name
+                .paging(&store, "Maria".to_string(), 1, 2)
+                .unwrap(),
+            vec![("4".to_string(), data("Maria", "Rodriguez", 12))]
+        );
+        assert!(map
+            .idx
+            .name
+            .paging(&store, "Mary".to_string(), 0, 10)
+            .unwrap()
+            .is_empty());
+
+        // unique index lookup
+        assert_eq!(
+            map.idx.age.load_item(&store, 32).unwrap(),
+            Some(("3".to_string(), data("John", "Wayne", 32)))
+        );
+        assert_eq!(map.idx.age.item(&store, 33).unwrap(), None);
+        assert_eq!(map.idx.age.len(&store).unwrap(), 4);
+        assert_eq!(
+            map.idx.age.paging(&store, 1, 3).unwrap(),
+            vec![("4".to_string(), data("Maria", "Rodriguez", 12))]
+        );
+    }
+
+    #[rstest]
+    #[case(build_map::<Json>())]
+    #[case(build_map::<Bincode2>())]
+    fn indexes_follow_updates(#[case] map: DataMap<impl Serde>) {
+        let mut store = MockStorage::new();
+        save_data(&mut store, &map);
+
+        // changing the indexed fields moves the entry between index values
+        map.save(&mut store, "2", &data("John", "Williams", 24))
+            .unwrap();
+        assert_eq!(map.idx.name.count(&store, "Maria".to_string()).unwrap(), 2);
+        assert_eq!(
+            map.idx
+                .name
+                .paging_keys(&store, "John".to_string(), 0, 10)
+                .unwrap(),
+            vec!["3".to_string(), "2".to_string()]
+        );
+        assert_eq!(map.idx.age.item(&store, 23).unwrap(), None);
+        assert!(map.idx.age.item(&store, 24).unwrap().is_some());
+
+        // update goes through the same path
+        map.update(&mut store, "2", |d| -> StdResult<_> {
+            let mut d = d.unwrap();
+            d.age = 25;
+            Ok(d)
+        })
+        .unwrap();
+        assert_eq!(map.idx.age.item(&store, 24).unwrap(), None);
+        assert_eq!(
+            map.idx.age.load_item(&store, 25).unwrap(),
+            Some(("2".to_string(), data("John", "Williams", 25)))
+        );
+
+        // removing clears every index
+        map.remove(&mut store, "3").unwrap();
+        assert!(!map.has(&store, "3"));
+        assert_eq!(
+            map.idx
+                .name
+                .paging_keys(&store, "John".to_string(), 0, 10)
+                .unwrap(),
+            vec!["2".to_string()]
+        );
+        assert_eq!(map.idx.age.item(&store, 32).unwrap(), None);
+        assert_eq!(map.idx.age.len(&store).unwrap(), 3);
+
+        // removing a missing key is a no-op
+        map.remove(&mut store, "3").unwrap();
+    }
+
+    #[rstest]
+    #[case(build_map::<Json>())]
+    #[case(build_map::<Bincode2>())]
+    fn unique_index_enforced(#[case] map: DataMap<impl Serde>) {
+        let mut store = MockStorage::new();
+        save_data(&mut store, &map);
+
+        // same age as "1"
+        let err = map
+            .save(&mut store, "5", &data("Mary", "Poppins", 42))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Violates unique constraint on index")
+        );
+
+        // overwriting the owner of the unique value is fine
+        map.save(&mut store, "1", &data("Maria", "Smith", 42))
+            .unwrap();
+        assert_eq!(
+            map.idx.age.load_item(&store, 42).unwrap(),
+            Some(("1".to_string(), data("Maria", "Smith", 42)))
+        );
+    }
+}
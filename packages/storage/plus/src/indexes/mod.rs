@@ -0,0 +1,21 @@
+//! Secondary indexes for `IndexedMap`.
+//!
+//! The indexes don't rely on storage iteration: every entry is kept in a `Keymap` or `Keyset`, so
+//! an index can be looked up by its index key and paged through on Secret Network.
+mod multi;
+mod unique;
+
+pub use multi::MultiIndex;
+pub use unique::UniqueIndex;
+
+use cosmwasm_std::{StdResult, Storage};
+
+// Note: we cannot store traits with generic functions inside `Box<dyn Index>`,
+// so I pull S: Storage to a top-level
+pub trait Index<T>
+where
+    T: Clone,
+{
+    fn save(&self, store: &mut dyn Storage, pk: &[u8], data: &T) -> StdResult<()>;
+    fn remove(&self, store: &mut dyn Storage, pk: &[u8], old_data: &T) -> StdResult<()>;
+}
@@ -0,0 +1,148 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::de::KeyDeserialize;
+use crate::helpers::namespaces_with_key;
+use crate::keymap::Keyset;
+use crate::{Format, Index, Json, PrimaryKey};
+
+/// MultiIndex stores one `Keyset` of primary keys per index value, under
+/// (namespace, index_name, idx_value).
+/// Allows many values per index, and references pk.
+/// The associated primary key value is stored in the main (pk_namespace) map,
+/// which stores (namespace, pk_namespace, pk) -> value.
+///
+/// The PK type defines the type of Primary Key deserialization.
+/// This type must match the encompassing `IndexedMap` primary key type,
+/// or its owned variant.
+pub struct MultiIndex<'a, IK, T, PK = (), Ser = Json> {
+    index: fn(&T) -> IK,
+    idx_namespace: &'a [u8],
+    pk_namespace: &'a [u8],
+    phantom: PhantomData<PK>,
+    serialization_type: PhantomData<*const Ser>,
+}
+
+impl<'a, IK, T, PK, Ser> MultiIndex<'a, IK, T, PK, Ser> {
+    /// Create a new MultiIndex
+    ///
+    /// idx_fn - lambda creating index key from value
+    /// pk_namespace - prefix for the primary key
+    /// idx_namespace - prefix for the index value
+    ///
+    /// ## Example:
+    ///
+    /// ```rust
+    /// use secret_storage_plus::MultiIndex;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize, Clone)]
+    /// struct Data {
+    ///     pub name: String,
+    ///     pub age: u32,
+    /// }
+    ///
+    /// let index: MultiIndex<_, _, String> = MultiIndex::new(
+    ///     |d: &Data| d.age,
+    ///     "age",
+    ///     "age__owner",
+    /// );
+    /// ```
+    pub const fn new(idx_fn: fn(&T) -> IK, pk_namespace: &'a str, idx_namespace: &'a str) -> Self {
+        MultiIndex {
+            index: idx_fn,
+            idx_namespace: idx_namespace.as_bytes(),
+            pk_namespace: pk_namespace.as_bytes(),
+            phantom: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+}
+
+impl<'a, IK, T, PK, Ser> MultiIndex<'a, IK, T, PK, Ser>
+where
+    T: Clone,
+    IK: PrimaryKey<'a>,
+{
+    // the index value is length prefixed, so the keysets of two values never overlap
+    fn pks(&self, idx: &IK) -> Keyset<'a, Vec<u8>> {
+        Keyset::prefixed(namespaces_with_key(
+            &[self.idx_namespace, &idx.joined_key()],
+            b"",
+        ))
+    }
+
+    /// returns the number of primary keys stored under this index value
+    pub fn count(&self, store: &dyn Storage, idx: IK) -> StdResult<u32> {
+        self.pks(&idx).get_len(store)
+    }
+
+    /// returns true if the primary key is stored under this index value
+    pub fn contains(&self, store: &dyn Storage, idx: IK, pk: &[u8]) -> StdResult<bool> {
+        self.pks(&idx).contains(store, pk.to_vec())
+    }
+}
+
+impl<'a, IK, T, PK, Ser> Index<T> for MultiIndex<'a, IK, T, PK, Ser>
+where
+    T: Clone,
+    IK: PrimaryKey<'a>,
+{
+    fn save(&self, store: &mut dyn Storage, pk: &[u8], data: &T) -> StdResult<()> {
+        let idx = (self.index)(data);
+        self.pks(&idx).insert(store, pk.to_vec())?;
+        Ok(())
+    }
+
+    fn remove(&self, store: &mut dyn Storage, pk: &[u8], old_data: &T) -> StdResult<()> {
+        let idx = (self.index)(old_data);
+        self.pks(&idx).remove(store, pk.to_vec())?;
+        Ok(())
+    }
+}
+
+impl<'a, IK, T, PK, Ser> MultiIndex<'a, IK, T, PK, Ser>
+where
+    T: Clone,
+    IK: PrimaryKey<'a>,
+    PK: KeyDeserialize,
+    Ser: Format<T>,
+{
+    /// does paging over the primary keys stored under this index value
+    pub fn paging_keys(
+        &self,
+        store: &dyn Storage,
+        idx: IK,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<PK::Output>> {
+        self.pks(&idx)
+            .paging(store, start_page, size)?
+            .into_iter()
+            .map(PK::from_vec)
+            .collect()
+    }
+
+    /// does paging over the entries stored under this index value, loading every value from the
+    /// primary map
+    pub fn paging(
+        &self,
+        store: &dyn Storage,
+        idx: IK,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<(PK::Output, T)>> {
+        self.pks(&idx)
+            .paging(store, start_page, size)?
+            .into_iter()
+            .map(|pk| {
+                let full_key = namespaces_with_key(&[self.pk_namespace], &pk);
+                let v = store
+                    .get(&full_key)
+                    .ok_or_else(|| StdError::generic_err("pk not found"))?;
+                Ok((PK::from_vec(pk)?, Ser::decode(&v)?))
+            })
+            .collect()
+    }
+}
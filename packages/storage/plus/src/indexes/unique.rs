@@ -0,0 +1,157 @@
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Binary, StdError, StdResult, Storage};
+
+use crate::de::KeyDeserialize;
+use crate::keymap::Keymap;
+use crate::{Format, Index, Json, PrimaryKey};
+
+/// UniqueRef stores Binary(Vec[u8]) representation of private key and index value
+///
+/// It is stored with the format of the index, so it shows up in the `Format` bounds of
+/// `UniqueIndex` and has to be `pub`. It isn't exported from the crate.
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh_derive::BorshSerialize, borsh_derive::BorshDeserialize)
+)]
+#[derive(Deserialize, Serialize)]
+pub struct UniqueRef<T> {
+    // note, we collapse the pk - combining everything under the namespace - even if it is composite
+    #[cfg_attr(
+        feature = "borsh",
+        borsh(
+            serialize_with = "crate::serialization::borsh::binary::serialize",
+            deserialize_with = "crate::serialization::borsh::binary::deserialize"
+        )
+    )]
+    pk: Binary,
+    value: T,
+}
+
+/// UniqueIndex stores (namespace, index_name, idx_value) -> {key, value} in a `Keymap`
+/// Allows one value per index (i.e. unique) and copies pk and data
+/// The optional PK type defines the type of Primary Key deserialization.
+pub struct UniqueIndex<'a, IK, T, PK = (), Ser = Json> {
+    index: fn(&T) -> IK,
+    idx_map: Keymap<'a, Vec<u8>, UniqueRef<T>, Ser>,
+    phantom: PhantomData<PK>,
+}
+
+impl<'a, IK, T, PK, Ser> UniqueIndex<'a, IK, T, PK, Ser> {
+    /// Create a new UniqueIndex
+    ///
+    /// idx_fn - lambda creating index key from index value
+    /// idx_namespace - prefix for the index value
+    ///
+    /// ## Example:
+    ///
+    /// ```rust
+    /// use secret_storage_plus::UniqueIndex;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize, Clone)]
+    /// struct Data {
+    ///     pub name: String,
+    ///     pub age: u32,
+    /// }
+    ///
+    /// UniqueIndex::<_, _, ()>::new(|d: &Data| d.age, "data__age");
+    /// ```
+    pub const fn new(idx_fn: fn(&T) -> IK, idx_namespace: &'a str) -> Self {
+        UniqueIndex {
+            index: idx_fn,
+            idx_map: Keymap::new(idx_namespace),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, IK, T, PK, Ser> Index<T> for UniqueIndex<'a, IK, T, PK, Ser>
+where
+    T: Clone,
+    IK: PrimaryKey<'a>,
+    Ser: Format<UniqueRef<T>>,
+{
+    fn save(&self, store: &mut dyn Storage, pk: &[u8], data: &T) -> StdResult<()> {
+        let idx = (self.index)(data).joined_key();
+        // error if this is already set
+        if self.idx_map.contains(store, idx.clone())? {
+            return Err(StdError::generic_err("Violates unique constraint on index"));
+        }
+        self.idx_map.insert(
+            store,
+            idx,
+            &UniqueRef {
+                pk: pk.into(),
+                value: data.clone(),
+            },
+        )
+    }
+
+    fn remove(&self, store: &mut dyn Storage, _pk: &[u8], old_data: &T) -> StdResult<()> {
+        let idx = (self.index)(old_data).joined_key();
+        self.idx_map.remove(store, idx)?;
+        Ok(())
+    }
+}
+
+impl<'a, IK, T, PK, Ser> UniqueIndex<'a, IK, T, PK, Ser>
+where
+    T: Clone,
+    IK: PrimaryKey<'a>,
+    Ser: Format<UniqueRef<T>>,
+{
+    pub fn index_key(&self, k: IK) -> Vec<u8> {
+        k.joined_key()
+    }
+
+    /// returns the raw primary key and the value stored under this index key, if any
+    pub fn item(&self, store: &dyn Storage, idx: IK) -> StdResult<Option<(Vec<u8>, T)>> {
+        let data = self
+            .idx_map
+            .get(store, idx.joined_key())?
+            .map(|i| (i.pk.into(), i.value));
+        Ok(data)
+    }
+
+    /// returns the number of entries in the index
+    pub fn len(&self, store: &dyn Storage) -> StdResult<u32> {
+        self.idx_map.get_len(store)
+    }
+
+    pub fn is_empty(&self, store: &dyn Storage) -> StdResult<bool> {
+        self.idx_map.is_empty(store)
+    }
+}
+
+impl<'a, IK, T, PK, Ser> UniqueIndex<'a, IK, T, PK, Ser>
+where
+    T: Clone,
+    IK: PrimaryKey<'a>,
+    PK: KeyDeserialize,
+    Ser: Format<UniqueRef<T>>,
+{
+    /// returns the primary key and value stored under this index key, if any
+    pub fn load_item(&self, store: &dyn Storage, idx: IK) -> StdResult<Option<(PK::Output, T)>> {
+        self.item(store, idx)?
+            .map(|(pk, value)| Ok((PK::from_vec(pk)?, value)))
+            .transpose()
+    }
+
+    /// does paging over the index entries. The order is the one of the underlying `Keymap`, so
+    /// it is the insertion order until something gets removed.
+    pub fn paging(
+        &self,
+        store: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<(PK::Output, T)>> {
+        self.idx_map
+            .paging(store, start_page, size)?
+            .into_iter()
+            .map(|(_, i)| Ok((PK::from_vec(i.pk.into())?, i.value)))
+            .collect()
+    }
+}
@@ -1,5 +1,7 @@
 use std::mem;
 
+use cosmwasm_std::{Timestamp, Uint128, Uint256, Uint64};
+
 /// Our int keys are simply the big-endian representation bytes for unsigned ints,
 /// but "sign-flipped" (xored msb) big-endian bytes for signed ints.
 ///
@@ -51,6 +53,171 @@ macro_rules! cw_int_keys {
 
 cw_int_keys!(for i8, u8, i16, u16, i32, u32, i64, u64, i128, u128);
 
+macro_rules! cw_std_uint_keys {
+    (for $($t:ty, $int:ty),+) => {
+        $(impl IntKey for $t {
+            type Buf = [u8; mem::size_of::<$int>()];
+
+            #[inline]
+            fn to_cw_bytes(&self) -> Self::Buf {
+                <$int>::from(*self).to_be_bytes()
+            }
+
+            #[inline]
+            fn from_cw_bytes(bytes: Self::Buf) -> Self {
+                <$int>::from_be_bytes(bytes).into()
+            }
+        })*
+    }
+}
+
+cw_std_uint_keys!(for Uint64, u64, Uint128, u128);
+
+impl IntKey for Uint256 {
+    type Buf = [u8; 32];
+
+    #[inline]
+    fn to_cw_bytes(&self) -> Self::Buf {
+        self.to_be_bytes()
+    }
+
+    #[inline]
+    fn from_cw_bytes(bytes: Self::Buf) -> Self {
+        Self::from_be_bytes(bytes)
+    }
+}
+
+/// Timestamps are keyed by their nanoseconds, so they sort chronologically
+impl IntKey for Timestamp {
+    type Buf = [u8; 8];
+
+    #[inline]
+    fn to_cw_bytes(&self) -> Self::Buf {
+        self.nanos().to_be_bytes()
+    }
+
+    #[inline]
+    fn from_cw_bytes(bytes: Self::Buf) -> Self {
+        Timestamp::from_nanos(u64::from_be_bytes(bytes))
+    }
+}
+
+#[cfg(feature = "ethnum")]
+impl IntKey for ethnum::U256 {
+    type Buf = [u8; 32];
+
+    #[inline]
+    fn to_cw_bytes(&self) -> Self::Buf {
+        self.to_be_bytes()
+    }
+
+    #[inline]
+    fn from_cw_bytes(bytes: Self::Buf) -> Self {
+        Self::from_be_bytes(bytes)
+    }
+}
+
+#[cfg(feature = "ethnum")]
+impl IntKey for ethnum::I256 {
+    type Buf = [u8; 32];
+
+    #[inline]
+    fn to_cw_bytes(&self) -> Self::Buf {
+        let mut bytes = self.to_be_bytes();
+        bytes[0] ^= 0x80;
+        bytes
+    }
+
+    #[inline]
+    fn from_cw_bytes(mut bytes: Self::Buf) -> Self {
+        bytes[0] ^= 0x80;
+        Self::from_be_bytes(bytes)
+    }
+}
+
+/// Declares a newtype over an integer that can be used wherever the integer can: as a `Map` key
+/// or key prefix, and as the value of a `Counter`. The keys are the same bytes as the integer's.
+///
+/// ```rust
+/// use cosmwasm_std::testing::MockStorage;
+/// use secret_storage_plus::{int_key_newtype, Counter, Map};
+///
+/// int_key_newtype! {
+///     /// ID of a token
+///     pub struct TokenId(pub u64);
+/// }
+///
+/// const TOKEN_IDS: Counter<TokenId> = Counter::new("token_ids");
+/// const OWNERS: Map<TokenId, String> = Map::new("owners");
+///
+/// let mut storage = MockStorage::new();
+/// let id = TOKEN_IDS.next(&mut storage).unwrap();
+/// OWNERS.save(&mut storage, id, &"alice".to_string()).unwrap();
+/// assert_eq!(id, TokenId(0));
+/// ```
+#[macro_export]
+macro_rules! int_key_newtype {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident($inner_vis:vis $inner:ty);) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        $vis struct $name($inner_vis $inner);
+
+        impl $crate::IntKey for $name {
+            type Buf = <$inner as $crate::IntKey>::Buf;
+
+            #[inline]
+            fn to_cw_bytes(&self) -> Self::Buf {
+                $crate::IntKey::to_cw_bytes(&self.0)
+            }
+
+            #[inline]
+            fn from_cw_bytes(bytes: Self::Buf) -> Self {
+                $name(<$inner as $crate::IntKey>::from_cw_bytes(bytes))
+            }
+        }
+
+        impl $crate::CounterInt for $name {
+            const ZERO: Self = $name(<$inner as $crate::CounterInt>::ZERO);
+
+            #[inline]
+            fn checked_increment(self) -> Option<Self> {
+                $crate::CounterInt::checked_increment(self.0).map($name)
+            }
+
+            #[inline]
+            fn checked_decrement(self) -> Option<Self> {
+                $crate::CounterInt::checked_decrement(self.0).map($name)
+            }
+        }
+
+        impl<'a> $crate::PrimaryKey<'a> for $name {
+            type Prefix = ();
+            type SubPrefix = ();
+            type Suffix = Self;
+            type SuperSuffix = Self;
+
+            fn key(&self) -> Vec<$crate::Key> {
+                $crate::PrimaryKey::key(&self.0)
+            }
+        }
+
+        impl<'a> $crate::Prefixer<'a> for $name {
+            fn prefix(&self) -> Vec<$crate::Key> {
+                $crate::Prefixer::prefix(&self.0)
+            }
+        }
+
+        impl $crate::KeyDeserialize for $name {
+            type Output = Self;
+
+            #[inline(always)]
+            fn from_vec(value: Vec<u8>) -> ::cosmwasm_std::StdResult<Self::Output> {
+                <$inner as $crate::KeyDeserialize>::from_vec(value).map($name)
+            }
+        }
+    };
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -127,4 +294,44 @@ mod test {
         assert!((-321i32).to_cw_bytes() < 0i32.to_cw_bytes());
         assert!(0i32.to_cw_bytes() < 652i32.to_cw_bytes());
     }
+
+    #[test]
+    fn cosmwasm_int_keys_work() {
+        assert_eq!(Uint64::new(0x4243).to_cw_bytes(), 0x4243u64.to_be_bytes());
+        assert_eq!(Uint128::new(0x4243).to_cw_bytes(), 0x4243u128.to_be_bytes());
+        assert_eq!(
+            Uint256::from(0x4243u128).to_cw_bytes()[16..],
+            0x4243u128.to_be_bytes()
+        );
+        assert_eq!(
+            Timestamp::from_seconds(2).to_cw_bytes(),
+            2_000_000_000u64.to_be_bytes()
+        );
+
+        assert!(Uint128::new(9).to_cw_bytes() < Uint128::new(256).to_cw_bytes());
+        assert!(Uint256::from(9u8).to_cw_bytes() < Uint256::MAX.to_cw_bytes());
+        assert!(
+            Timestamp::from_nanos(999).to_cw_bytes() < Timestamp::from_seconds(1).to_cw_bytes()
+        );
+        assert_eq!(
+            Uint256::from_cw_bytes(Uint256::from(1234u32).to_cw_bytes()),
+            Uint256::from(1234u32)
+        );
+    }
+
+    #[cfg(feature = "ethnum")]
+    #[test]
+    fn ethnum_int_keys_work() {
+        use ethnum::{I256, U256};
+
+        assert_eq!(U256::new(0x42).to_cw_bytes()[31], 0x42);
+        assert!(U256::new(9).to_cw_bytes() < U256::MAX.to_cw_bytes());
+        assert!(I256::MIN.to_cw_bytes() < I256::new(-1).to_cw_bytes());
+        assert!(I256::new(-1).to_cw_bytes() < I256::ZERO.to_cw_bytes());
+        assert!(I256::ZERO.to_cw_bytes() < I256::MAX.to_cw_bytes());
+        assert_eq!(
+            I256::from_cw_bytes(I256::new(-1234).to_cw_bytes()),
+            I256::new(-1234)
+        );
+    }
 }
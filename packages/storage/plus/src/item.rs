@@ -1,21 +1,17 @@
-use serde::de::DeserializeOwned;
-use serde::Serialize;
+use std::any::type_name;
 use std::marker::PhantomData;
 
-use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage, WasmQuery};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
 use crate::{
-    helpers::{may_deserialize, must_deserialize},
-    Json, Serde,
+    helpers::{may_deserialize, must_deserialize, query_raw},
+    Format, Json,
 };
 
 /// Item stores one typed item at the given key.
 /// This is an analog of Singleton.
 /// It functions the same way as Path does but doesn't use a Vec and thus has a const fn constructor.
-pub struct Item<'a, T, Ser = Json>
-where
-    Ser: Serde,
-{
+pub struct Item<'a, T, Ser = Json> {
     // this is full key - no need to length-prefix it, we only store one item
     storage_key: &'a [u8],
     // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
@@ -23,10 +19,7 @@ where
     serialization_type: PhantomData<*const Ser>,
 }
 
-impl<'a, T, Ser> Item<'a, T, Ser>
-where
-    Ser: Serde,
-{
+impl<'a, T, Ser> Item<'a, T, Ser> {
     pub const fn new(storage_key: &'a str) -> Self {
         Item {
             storage_key: storage_key.as_bytes(),
@@ -38,8 +31,7 @@ where
 
 impl<'a, T, Ser> Item<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Format<T>,
 {
     // this gets the path of the data to use elsewhere
     pub fn as_slice(&self) -> &[u8] {
@@ -48,7 +40,7 @@ where
 
     /// save will serialize the model and store, returns an error on serialization issues
     pub fn save(&self, store: &mut dyn Storage, data: &T) -> StdResult<()> {
-        store.set(self.storage_key, &Ser::serialize(data)?);
+        store.set(self.storage_key, &Ser::encode(data)?);
         Ok(())
     }
 
@@ -86,7 +78,7 @@ where
     }
 
     /// If you import the proper Item from the remote contract, this will let you read the data
-    /// from a remote contract in a type-safe way using WasmQuery::RawQuery.
+    /// from a remote contract in a type-safe way using a raw query, decoding it with `Ser`.
     ///
     /// Note that we expect an Item to be set, and error if there is no data there
     pub fn query<Q: CustomQuery>(
@@ -95,21 +87,27 @@ where
         remote_contract: Addr,
         remote_contract_code_hash: String,
     ) -> StdResult<T> {
-        let request = WasmQuery::Smart {
-            contract_addr: remote_contract.into(),
-            code_hash: remote_contract_code_hash,
-            msg: self.storage_key.into(),
-        };
-        querier.query(&request.into())
+        let result = query_raw(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            self.storage_key.into(),
+        )?;
+        if result.is_empty() {
+            Err(StdError::not_found(type_name::<T>()))
+        } else {
+            Ser::decode(&result)
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::Bincode2;
+    use crate::helpers::mock_remote_querier;
+    use crate::{Bincode2, Cbor, Compressed, Padded, Postcard, Serde};
 
     use super::*;
-    use cosmwasm_std::{testing::MockStorage, to_vec};
+    use cosmwasm_std::{testing::MockStorage, to_vec, Empty};
     use rstest::*;
     use rstest_reuse::{self, *};
     use serde::{Deserialize, Serialize};
@@ -125,11 +123,20 @@ mod test {
     // note const constructor rather than 2 funcs with Singleton
     const CONFIG: Item<Config> = Item::new("config");
     const B_CONFIG: Item<Config, Bincode2> = Item::new("config");
+    const P_CONFIG: Item<Config, Postcard> = Item::new("config");
+    const C_CONFIG: Item<Config, Cbor> = Item::new("config");
+    // compresses everything, to exercise both paths of `Compressed`
+    const Z_CONFIG: Item<Config, Compressed<Json, 0>> = Item::new("config");
+    const PAD_CONFIG: Item<Config, Padded<Json, 64>> = Item::new("config");
 
     #[template]
     #[rstest]
     #[case(CONFIG)]
     #[case(B_CONFIG)]
+    #[case(P_CONFIG)]
+    #[case(C_CONFIG)]
+    #[case(Z_CONFIG)]
+    #[case(PAD_CONFIG)]
     fn serialization(#[case] config: Item<Config, impl Serde>) {}
 
     #[apply(serialization)]
@@ -148,6 +155,31 @@ mod test {
         assert_eq!(cfg, config.load(&store).unwrap());
     }
 
+    #[apply(serialization)]
+    fn remote_query(#[case] config: Item<Config, impl Serde>) {
+        let mut store = MockStorage::new();
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        config.save(&mut store, &cfg).unwrap();
+
+        let remote = mock_remote_querier(store);
+        let querier = QuerierWrapper::<Empty>::new(&remote);
+        let loaded = config
+            .query(&querier, Addr::unchecked("remote"), "hash".to_string())
+            .unwrap();
+        assert_eq!(cfg, loaded);
+
+        // an unset item is an error
+        let remote = mock_remote_querier(MockStorage::new());
+        let querier = QuerierWrapper::<Empty>::new(&remote);
+        let err = config
+            .query(&querier, Addr::unchecked("remote"), "hash".to_string())
+            .unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }));
+    }
+
     #[apply(serialization)]
     fn remove_works(#[case] config: Item<Config, impl Serde>) {
         let mut store = MockStorage::new();
@@ -0,0 +1,57 @@
+#![cfg(feature = "iterator")]
+
+use cosmwasm_std::{Record, StdResult};
+
+use crate::de::KeyDeserialize;
+use crate::Format;
+
+pub(crate) fn deserialize_v<T, Ser: Format<T>>(kv: Record) -> StdResult<Record<T>> {
+    let (k, v) = kv;
+    let t = Ser::decode(&v)?;
+    Ok((k, t))
+}
+
+pub(crate) fn deserialize_kv<K: KeyDeserialize, T, Ser: Format<T>>(
+    kv: Record,
+) -> StdResult<(K::Output, T)> {
+    let (k, v) = kv;
+    let kt = K::from_vec(k)?;
+    let vt = Ser::decode(&v)?;
+    Ok((kt, vt))
+}
+
+// TODO: add a check here that it is the real prefix?
+#[inline]
+pub(crate) fn trim(namespace: &[u8], key: &[u8]) -> Vec<u8> {
+    key[namespace.len()..].to_vec()
+}
+
+#[inline]
+pub(crate) fn concat(namespace: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut k = namespace.to_vec();
+    k.extend_from_slice(key);
+    k
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Bincode2, Json};
+
+    #[test]
+    fn deserialize_kv_uses_serde() {
+        let json = (b"john".to_vec(), b"42".to_vec());
+        let (k, v) = deserialize_kv::<String, u64, Json>(json).unwrap();
+        assert_eq!(("john".to_string(), 42), (k, v));
+
+        let bincode = (b"john".to_vec(), bincode2::serialize(&42u64).unwrap());
+        let (k, v) = deserialize_v::<u64, Bincode2>(bincode).unwrap();
+        assert_eq!((b"john".to_vec(), 42), (k, v));
+    }
+
+    #[test]
+    fn trim_and_concat() {
+        assert_eq!(concat(b"foo", b"bar"), b"foobar".to_vec());
+        assert_eq!(trim(b"foo", b"foobar"), b"bar".to_vec());
+    }
+}
@@ -0,0 +1,310 @@
+use std::convert::TryInto;
+use std::fmt;
+
+use cosmwasm_std::Uint256;
+
+use crate::endian::Endian;
+use crate::helpers::split_namespace;
+
+// metadata keys of the collections that use their prefix as is
+const LEN_KEY: &[u8] = b"len";
+const OFFSET_KEY: &[u8] = b"off";
+// metadata keys of the borsh `Deque`, stored under a length-prefixed namespace
+const HEAD_KEY: &[u8] = b"h";
+const TAIL_KEY: &[u8] = b"t";
+
+/// A part of a decoded storage key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeySegment {
+    /// a prefix registered with `KeyDecoder::with_namespaces`
+    Namespace(Vec<u8>),
+    /// a length-prefixed namespace or key component
+    Prefixed(Vec<u8>),
+    /// the length of an `AppendStore` or `DequeStore`
+    Len,
+    /// the offset of a `DequeStore`
+    Offset,
+    /// the head position of a borsh `Deque`
+    Head,
+    /// the tail position of a borsh `Deque`
+    Tail,
+    /// whatever is left of the key
+    Raw(Vec<u8>),
+}
+
+impl fmt::Display for KeySegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeySegment::Namespace(bytes) | KeySegment::Prefixed(bytes) | KeySegment::Raw(bytes) => {
+                f.write_str(&render_bytes(bytes))
+            }
+            KeySegment::Len => f.write_str("<len>"),
+            KeySegment::Offset => f.write_str("<offset>"),
+            KeySegment::Head => f.write_str("<head>"),
+            KeySegment::Tail => f.write_str("<tail>"),
+        }
+    }
+}
+
+/// A storage key split back into its segments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedKey {
+    pub segments: Vec<KeySegment>,
+}
+
+impl fmt::Display for DecodedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" / ")?;
+            }
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits raw storage keys back into namespaces and key components, to inspect the state of a
+/// contract while debugging.
+///
+/// Length-prefixed namespaces, as built by `Map`, `IndexedMap`, `Keymap` and the borsh `Deque`, are
+/// found on their own. Collections that use their prefix as is, like `AppendStore` and
+/// `DequeStore`, must be registered with `with_namespaces`, otherwise their keys are left undecoded.
+///
+/// Decoding is a best effort: a key component that happens to start with a small big-endian
+/// number can't be told apart from a length prefix, and a borsh `Deque` meta key looks the same
+/// as a `Map<&str, _>` entry under the key `"h"` or `"t"`.
+///
+/// Key types aren't known either, so integer components are rendered as unsigned numbers by
+/// their size. Signed keys have their sign bit flipped to keep them ordered, which means they
+/// show the flipped value: `-1i32` renders as `2147483647` and `0i32` as `2147483648`.
+///
+/// ```rust
+/// use secret_storage_plus::{KeyDecoder, Map};
+///
+/// const BALANCES: Map<(&str, u32), u64> = Map::new("balances");
+///
+/// let key = BALANCES.key(("alice", 7));
+/// let decoded = KeyDecoder::new().decode(&key);
+/// assert_eq!(decoded.to_string(), "balances / alice / 7");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct KeyDecoder {
+    namespaces: Vec<Vec<u8>>,
+}
+
+impl KeyDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the prefixes of collections that don't length-prefix their namespace
+    pub fn with_namespaces(mut self, namespaces: &[&str]) -> Self {
+        self.namespaces.extend(
+            namespaces
+                .iter()
+                .map(|namespace| namespace.as_bytes().to_vec()),
+        );
+        self
+    }
+
+    pub fn decode(&self, key: &[u8]) -> DecodedKey {
+        let mut segments = vec![];
+        let registered = self
+            .namespaces
+            .iter()
+            .filter(|namespace| key.starts_with(namespace))
+            .max_by_key(|namespace| namespace.len());
+
+        if let Some(namespace) = registered {
+            segments.push(KeySegment::Namespace(namespace.clone()));
+            match &key[namespace.len()..] {
+                [] => {}
+                LEN_KEY => segments.push(KeySegment::Len),
+                OFFSET_KEY => segments.push(KeySegment::Offset),
+                rest => segments.push(KeySegment::Raw(rest.to_vec())),
+            }
+            return DecodedKey { segments };
+        }
+
+        let mut rest = key;
+        while let Some((part, tail)) = split_namespace(rest) {
+            segments.push(KeySegment::Prefixed(part.to_vec()));
+            rest = tail;
+        }
+        match rest {
+            [] => {}
+            HEAD_KEY if segments.len() == 1 => segments.push(KeySegment::Head),
+            TAIL_KEY if segments.len() == 1 => segments.push(KeySegment::Tail),
+            _ => segments.push(KeySegment::Raw(rest.to_vec())),
+        }
+        DecodedKey { segments }
+    }
+
+    /// Renders the whole storage as a tree, one line per key segment, with the values as leaves
+    #[cfg(feature = "iterator")]
+    pub fn dump(&self, storage: &dyn cosmwasm_std::Storage) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+        let mut previous: Vec<KeySegment> = vec![];
+        for (key, value) in storage.range(None, None, cosmwasm_std::Order::Ascending) {
+            let segments = self.decode(&key).segments;
+            let common = previous
+                .iter()
+                .zip(&segments)
+                .take_while(|(a, b)| a == b)
+                .count();
+            // a key equal to the previous one's path still needs a line for its value
+            let start = common.min(segments.len().saturating_sub(1));
+            for (depth, segment) in segments.iter().enumerate().skip(start) {
+                write!(out, "{:indent$}{}", "", segment, indent = depth * 2).unwrap();
+                if depth + 1 == segments.len() {
+                    write!(out, " = {}", render_value(&value)).unwrap();
+                }
+                out.push('\n');
+            }
+            previous = segments;
+        }
+        out
+    }
+}
+
+/// Renders printable UTF-8 as text, sizes of integer keys as unsigned big-endian numbers and
+/// anything else as hex
+fn render_bytes(bytes: &[u8]) -> String {
+    if let Some(text) = printable(bytes) {
+        return text.to_string();
+    }
+    match bytes.len() {
+        1 => bytes[0].to_string(),
+        2 => <u16 as Endian>::from_be_bytes(bytes.try_into().unwrap()).to_string(),
+        4 => <u32 as Endian>::from_be_bytes(bytes.try_into().unwrap()).to_string(),
+        8 => <u64 as Endian>::from_be_bytes(bytes.try_into().unwrap()).to_string(),
+        16 => <u128 as Endian>::from_be_bytes(bytes.try_into().unwrap()).to_string(),
+        32 => Uint256::from_be_bytes(bytes.try_into().unwrap()).to_string(),
+        _ => hex(bytes),
+    }
+}
+
+fn render_value(bytes: &[u8]) -> String {
+    printable(bytes).map_or_else(|| hex(bytes), str::to_string)
+}
+
+fn printable(bytes: &[u8]) -> Option<&str> {
+    std::str::from_utf8(bytes)
+        .ok()
+        .filter(|text| !text.is_empty() && !text.chars().any(char::is_control))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::from("0x");
+    for byte in bytes {
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::helpers::{namespaces_with_key, nested_namespaces_with_key};
+    use crate::{IntKey, Key, Map};
+
+    const BALANCES: Map<(&str, u32), u64> = Map::new("balances");
+
+    #[test]
+    fn decodes_length_prefixed_keys() {
+        let decoder = KeyDecoder::new();
+        assert_eq!(
+            decoder.decode(&BALANCES.key(("alice", 7))).segments,
+            vec![
+                KeySegment::Prefixed(b"balances".to_vec()),
+                KeySegment::Prefixed(b"alice".to_vec()),
+                KeySegment::Raw(7u32.to_be_bytes().to_vec()),
+            ]
+        );
+
+        let nested = nested_namespaces_with_key(&[b"top".as_slice()], &[Key::Ref(b"sub")], b"key");
+        assert_eq!(decoder.decode(&nested).to_string(), "top / sub / key");
+
+        assert_eq!(
+            decoder.decode(b"config").segments,
+            vec![KeySegment::Raw(b"config".to_vec())]
+        );
+    }
+
+    #[test]
+    fn decodes_meta_keys() {
+        let decoder = KeyDecoder::new().with_namespaces(&["history", "queue"]);
+        assert_eq!(
+            decoder.decode(b"historylen").segments,
+            vec![KeySegment::Namespace(b"history".to_vec()), KeySegment::Len]
+        );
+        assert_eq!(decoder.decode(b"queueoff").to_string(), "queue / <offset>");
+        assert_eq!(
+            decoder
+                .decode(&[b"history".as_slice(), &3u32.to_be_bytes()].concat())
+                .to_string(),
+            "history / 3"
+        );
+
+        let head = namespaces_with_key(&[b"deque".as_slice()], HEAD_KEY);
+        assert_eq!(decoder.decode(&head).to_string(), "deque / <head>");
+        let tail = namespaces_with_key(&[b"deque".as_slice()], TAIL_KEY);
+        assert_eq!(decoder.decode(&tail).to_string(), "deque / <tail>");
+    }
+
+    #[test]
+    fn renders_bytes() {
+        assert_eq!(render_bytes(b"alice"), "alice");
+        assert_eq!(render_bytes(&[1]), "1");
+        assert_eq!(render_bytes(&258u16.to_be_bytes()), "258");
+        assert_eq!(render_bytes(&u64::MAX.to_be_bytes()), u64::MAX.to_string());
+        assert_eq!(
+            render_bytes(&Uint256::MAX.to_be_bytes()),
+            Uint256::MAX.to_string()
+        );
+        assert_eq!(render_bytes(&[0, 1, 2]), "0x000102");
+        // signed keys show their sign-flipped value
+        assert_eq!(render_bytes(&(-1i32).to_cw_bytes()), "2147483647");
+        assert_eq!(render_bytes(&0i32.to_cw_bytes()), "2147483648");
+    }
+
+    #[cfg(feature = "iterator")]
+    #[test]
+    fn dumps_storage_as_tree() -> cosmwasm_std::StdResult<()> {
+        use crate::{AppendStore, DequeStore, Item};
+        use cosmwasm_std::testing::MockStorage;
+
+        const CONFIG: Item<u64> = Item::new("config");
+        const HISTORY: AppendStore<u64> = AppendStore::new("history");
+        const QUEUE: DequeStore<u64> = DequeStore::new("queue");
+
+        let mut storage = MockStorage::new();
+        CONFIG.save(&mut storage, &1)?;
+        BALANCES.save(&mut storage, ("alice", 1), &10)?;
+        BALANCES.save(&mut storage, ("alice", 2), &20)?;
+        BALANCES.save(&mut storage, ("bob", 1), &30)?;
+        HISTORY.push(&mut storage, &5)?;
+        QUEUE.push_back(&mut storage, &6)?;
+
+        let decoder = KeyDecoder::new().with_namespaces(&["config", "history", "queue"]);
+        let dump = decoder.dump(&storage);
+        let expected = "\
+balances
+  alice
+    1 = 10
+    2 = 20
+  bob
+    1 = 30
+config = 1
+history
+  0 = 5
+  <len> = 0x00000001
+queue
+";
+        assert!(dump.starts_with(expected), "{}", dump);
+        Ok(())
+    }
+}
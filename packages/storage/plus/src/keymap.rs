@@ -0,0 +1,737 @@
+//! A "keymap" is a map that can be listed without storage iteration, which Secret contracts
+//! usually don't have access to.
+//!
+//! Next to the values, the keymap keeps an index of its keys: an append store like list that maps
+//! every position to a key, and a reverse entry that maps every key to its position. Inserting
+//! appends to the index, and removing moves the last key into the freed position (swap-remove), so
+//! both have a constant cost. The order of the keys is therefore the insertion order, until
+//! something gets removed.
+//!
+//! A `Keyset` is the same structure without any values.
+use std::convert::TryInto;
+use std::marker::PhantomData;
+
+use crate::de::KeyDeserialize;
+use crate::helpers::namespaces_with_key;
+use crate::keys::PrimaryKey;
+use crate::{Format, Json};
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+const LEN_KEY: &[u8] = b"len";
+const INDEX_NAMESPACE: &[u8] = b"idx";
+const POS_NAMESPACE: &[u8] = b"pos";
+const VALUE_NAMESPACE: &[u8] = b"val";
+
+/// The key index shared by `Keymap` and `Keyset`.
+struct KeyIndex<'a> {
+    /// prefix of the newly constructed Storage
+    namespace: &'a [u8],
+    /// needed if any suffixes were added to the original namespace.
+    /// therefore it is not necessarily same as the namespace.
+    prefix: Option<Vec<u8>>,
+}
+
+impl<'a> KeyIndex<'a> {
+    const fn new(namespace: &'a [u8]) -> Self {
+        Self {
+            namespace,
+            prefix: None,
+        }
+    }
+
+    fn prefixed(prefix: Vec<u8>) -> Self {
+        Self {
+            namespace: &[],
+            prefix: Some(prefix),
+        }
+    }
+
+    fn add_suffix(&self, suffix: &str) -> Self {
+        let prefix = if let Some(prefix) = &self.prefix {
+            [prefix.clone(), suffix.as_bytes().to_vec()].concat()
+        } else {
+            [self.namespace.to_vec(), suffix.as_bytes().to_vec()].concat()
+        };
+        Self {
+            namespace: self.namespace,
+            prefix: Some(prefix),
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        if let Some(prefix) = &self.prefix {
+            prefix
+        } else {
+            self.namespace
+        }
+    }
+
+    /// The prefix and the sub-namespace are both length-prefixed, like the namespaces of a `Map`,
+    /// so keymaps whose prefixes start the same can't write to each other's keys.
+    fn key(&self, namespace: &[u8], key: &[u8]) -> Vec<u8> {
+        namespaces_with_key(&[self.as_slice(), namespace], key)
+    }
+
+    fn get_len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        match storage.get(&self.key(LEN_KEY, &[])) {
+            Some(len_vec) => parse_u32(&len_vec),
+            None => Ok(0),
+        }
+    }
+
+    fn set_len(&self, storage: &mut dyn Storage, len: u32) {
+        storage.set(&self.key(LEN_KEY, &[]), &len.to_be_bytes());
+    }
+
+    fn position(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<Option<u32>> {
+        storage
+            .get(&self.key(POS_NAMESPACE, key))
+            .map(|pos| parse_u32(&pos))
+            .transpose()
+    }
+
+    fn key_at(&self, storage: &dyn Storage, pos: u32) -> StdResult<Vec<u8>> {
+        storage
+            .get(&self.key(INDEX_NAMESPACE, &pos.to_be_bytes()))
+            .ok_or_else(|| StdError::generic_err("Keymap index is corrupted"))
+    }
+
+    fn set_key_at(&self, storage: &mut dyn Storage, pos: u32, key: &[u8]) {
+        storage.set(&self.key(INDEX_NAMESPACE, &pos.to_be_bytes()), key);
+        storage.set(&self.key(POS_NAMESPACE, key), &pos.to_be_bytes());
+    }
+
+    /// Appends the key to the index, returns false if it was already there
+    fn insert(&self, storage: &mut dyn Storage, key: &[u8]) -> StdResult<bool> {
+        if self.position(storage, key)?.is_some() {
+            return Ok(false);
+        }
+        let len = self.get_len(storage)?;
+        self.set_key_at(storage, len, key);
+        self.set_len(storage, len + 1);
+        Ok(true)
+    }
+
+    /// Swap-removes the key from the index, returns false if it wasn't there
+    fn remove(&self, storage: &mut dyn Storage, key: &[u8]) -> StdResult<bool> {
+        let pos = match self.position(storage, key)? {
+            Some(pos) => pos,
+            None => return Ok(false),
+        };
+        let last = self.get_len(storage)? - 1;
+        if pos != last {
+            let moved = self.key_at(storage, last)?;
+            self.set_key_at(storage, pos, &moved);
+        }
+        storage.remove(&self.key(INDEX_NAMESPACE, &last.to_be_bytes()));
+        storage.remove(&self.key(POS_NAMESPACE, key));
+        self.set_len(storage, last);
+        Ok(true)
+    }
+
+    fn page_bounds(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<(u32, u32)> {
+        let len = self.get_len(storage)?;
+        let start = start_page.saturating_mul(size).min(len);
+        let end = start.saturating_add(size).min(len);
+        Ok((start, end))
+    }
+}
+
+fn parse_u32(bytes: &[u8]) -> StdResult<u32> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|err| StdError::parse_err("u32", err))?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+pub struct Keymap<'a, K, V, Ser = Json> {
+    index: KeyIndex<'a>,
+    key_type: PhantomData<K>,
+    value_type: PhantomData<V>,
+    serialization_type: PhantomData<Ser>,
+}
+
+impl<'a, K, V, Ser> Keymap<'a, K, V, Ser> {
+    /// constructor
+    pub const fn new(prefix: &'a str) -> Self {
+        Self {
+            index: KeyIndex::new(prefix.as_bytes()),
+            key_type: PhantomData,
+            value_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+    /// This is used to produce a new Keymap. This can be used when you want to associate a Keymap to each user
+    /// and you still get to define the Keymap as a static constant
+    pub fn add_suffix(&self, suffix: &str) -> Self {
+        Self {
+            index: self.index.add_suffix(suffix),
+            key_type: self.key_type,
+            value_type: self.value_type,
+            serialization_type: self.serialization_type,
+        }
+    }
+}
+
+impl<'a, K, V, Ser> Keymap<'a, K, V, Ser>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Format<V>,
+{
+    /// gets the number of entries from storage, and otherwise sets it to 0
+    pub fn get_len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        self.index.get_len(storage)
+    }
+    /// checks if the collection has any elements
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.get_len(storage)? == 0)
+    }
+    /// returns true if a value is stored at the key
+    pub fn contains(&self, storage: &dyn Storage, key: K) -> StdResult<bool> {
+        Ok(self.index.position(storage, &key.joined_key())?.is_some())
+    }
+    /// gets the value stored at the key, returns Ok(None) if there is none
+    pub fn get(&self, storage: &dyn Storage, key: K) -> StdResult<Option<V>> {
+        self.load_impl(storage, &key.joined_key())
+    }
+    /// Inserts or replaces the value at the key. New keys are appended to the end of the index,
+    /// existing keys keep their position.
+    pub fn insert(&self, storage: &mut dyn Storage, key: K, value: &V) -> StdResult<()> {
+        let key = key.joined_key();
+        self.index.insert(storage, &key)?;
+        storage.set(&self.index.key(VALUE_NAMESPACE, &key), &Ser::encode(value)?);
+        Ok(())
+    }
+    /// Removes the value at the key and returns it. The last key of the index takes its
+    /// position, so removing has a constant cost.
+    pub fn remove(&self, storage: &mut dyn Storage, key: K) -> StdResult<Option<V>> {
+        let key = key.joined_key();
+        let value = self.load_impl(storage, &key)?;
+        if self.index.remove(storage, &key)? {
+            storage.remove(&self.index.key(VALUE_NAMESPACE, &key));
+        }
+        Ok(value)
+    }
+    /// gets the entry at pos of the index if within bounds
+    pub fn get_at(&self, storage: &dyn Storage, pos: u32) -> StdResult<(K::Output, V)> {
+        if pos >= self.get_len(storage)? {
+            return Err(StdError::generic_err("Keymap access out of bounds"));
+        }
+        self.get_at_unchecked(storage, pos)
+    }
+    fn get_at_unchecked(&self, storage: &dyn Storage, pos: u32) -> StdResult<(K::Output, V)> {
+        let key = self.index.key_at(storage, pos)?;
+        let value = self
+            .load_impl(storage, &key)?
+            .ok_or_else(|| StdError::generic_err("Keymap index is corrupted"))?;
+        Ok((K::from_vec(key)?, value))
+    }
+    /// Returns a readonly iterator over the entries, in index order
+    pub fn iter<'b>(
+        &'b self,
+        storage: &'b dyn Storage,
+    ) -> StdResult<KeymapIter<'a, 'b, K, V, Ser>> {
+        let len = self.get_len(storage)?;
+        Ok(KeymapIter::new(self, storage, 0, len))
+    }
+    /// does paging with the given parameters
+    pub fn paging(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<(K::Output, V)>> {
+        let (start, end) = self.index.page_bounds(storage, start_page, size)?;
+        (start..end)
+            .map(|pos| self.get_at_unchecked(storage, pos))
+            .collect()
+    }
+    /// does paging over the keys only
+    pub fn paging_keys(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<K::Output>> {
+        let (start, end) = self.index.page_bounds(storage, start_page, size)?;
+        (start..end)
+            .map(|pos| K::from_vec(self.index.key_at(storage, pos)?))
+            .collect()
+    }
+    /// does paging over the joined keys, without deserializing them
+    pub fn paging_raw_keys(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<Vec<u8>>> {
+        let (start, end) = self.index.page_bounds(storage, start_page, size)?;
+        (start..end)
+            .map(|pos| self.index.key_at(storage, pos))
+            .collect()
+    }
+
+    fn load_impl(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<Option<V>> {
+        storage
+            .get(&self.index.key(VALUE_NAMESPACE, key))
+            .map(|value| Ser::decode(&value))
+            .transpose()
+    }
+}
+
+pub struct Keyset<'a, K> {
+    index: KeyIndex<'a>,
+    key_type: PhantomData<K>,
+}
+
+impl<'a, K> Keyset<'a, K> {
+    /// constructor
+    pub const fn new(prefix: &'a str) -> Self {
+        Self {
+            index: KeyIndex::new(prefix.as_bytes()),
+            key_type: PhantomData,
+        }
+    }
+    /// This is used to produce a new Keyset. This can be used when you want to associate a Keyset to each user
+    /// and you still get to define the Keyset as a static constant
+    pub fn add_suffix(&self, suffix: &str) -> Self {
+        Self {
+            index: self.index.add_suffix(suffix),
+            key_type: self.key_type,
+        }
+    }
+    /// Creates a keyset under an already built storage prefix, used by the secondary indexes
+    pub(crate) fn prefixed(prefix: Vec<u8>) -> Self {
+        Self {
+            index: KeyIndex::prefixed(prefix),
+            key_type: PhantomData,
+        }
+    }
+}
+
+impl<'a, K> Keyset<'a, K>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+{
+    /// gets the number of keys from storage, and otherwise sets it to 0
+    pub fn get_len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        self.index.get_len(storage)
+    }
+    /// checks if the collection has any elements
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.get_len(storage)? == 0)
+    }
+    /// returns true if the key is in the set
+    pub fn contains(&self, storage: &dyn Storage, key: K) -> StdResult<bool> {
+        Ok(self.index.position(storage, &key.joined_key())?.is_some())
+    }
+    /// Adds the key to the set, returns false if it was already there
+    pub fn insert(&self, storage: &mut dyn Storage, key: K) -> StdResult<bool> {
+        self.index.insert(storage, &key.joined_key())
+    }
+    /// Removes the key from the set, returns false if it wasn't there
+    pub fn remove(&self, storage: &mut dyn Storage, key: K) -> StdResult<bool> {
+        self.index.remove(storage, &key.joined_key())
+    }
+    /// gets the key at pos of the index if within bounds
+    pub fn get_at(&self, storage: &dyn Storage, pos: u32) -> StdResult<K::Output> {
+        if pos >= self.get_len(storage)? {
+            return Err(StdError::generic_err("Keyset access out of bounds"));
+        }
+        self.get_at_unchecked(storage, pos)
+    }
+    fn get_at_unchecked(&self, storage: &dyn Storage, pos: u32) -> StdResult<K::Output> {
+        K::from_vec(self.index.key_at(storage, pos)?)
+    }
+    /// Returns a readonly iterator over the keys, in index order
+    pub fn iter<'b>(&'b self, storage: &'b dyn Storage) -> StdResult<KeysetIter<'a, 'b, K>> {
+        let len = self.get_len(storage)?;
+        Ok(KeysetIter::new(self, storage, 0, len))
+    }
+    /// does paging with the given parameters
+    pub fn paging(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<K::Output>> {
+        let (start, end) = self.index.page_bounds(storage, start_page, size)?;
+        (start..end)
+            .map(|pos| self.get_at_unchecked(storage, pos))
+            .collect()
+    }
+}
+
+/// An iterator over the entries of a keymap, in index order.
+pub struct KeymapIter<'a, 'b, K, V, Ser>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Format<V>,
+{
+    keymap: &'b Keymap<'a, K, V, Ser>,
+    storage: &'b dyn Storage,
+    start: u32,
+    end: u32,
+}
+
+impl<'a, 'b, K, V, Ser> KeymapIter<'a, 'b, K, V, Ser>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Format<V>,
+{
+    /// constructor
+    pub fn new(
+        keymap: &'b Keymap<'a, K, V, Ser>,
+        storage: &'b dyn Storage,
+        start: u32,
+        end: u32,
+    ) -> Self {
+        Self {
+            keymap,
+            storage,
+            start,
+            end,
+        }
+    }
+}
+
+impl<'a, 'b, K, V, Ser> Iterator for KeymapIter<'a, 'b, K, V, Ser>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Format<V>,
+{
+    type Item = StdResult<(K::Output, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        let item = self.keymap.get_at_unchecked(self.storage, self.start);
+        self.start += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.end - self.start) as usize;
+        (len, Some(len))
+    }
+
+    // skipping is cheap because every position can be loaded directly, see `AppendStoreIter`
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.start = self.start.saturating_add(n as u32);
+        self.next()
+    }
+}
+
+impl<'a, 'b, K, V, Ser> DoubleEndedIterator for KeymapIter<'a, 'b, K, V, Ser>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Format<V>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        let item = self.keymap.get_at_unchecked(self.storage, self.end);
+        Some(item)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.end = self.end.saturating_sub(n as u32);
+        self.next_back()
+    }
+}
+
+impl<'a, 'b, K, V, Ser> ExactSizeIterator for KeymapIter<'a, 'b, K, V, Ser>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Format<V>,
+{
+}
+
+/// An iterator over the keys of a keyset, in index order.
+pub struct KeysetIter<'a, 'b, K>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+{
+    keyset: &'b Keyset<'a, K>,
+    storage: &'b dyn Storage,
+    start: u32,
+    end: u32,
+}
+
+impl<'a, 'b, K> KeysetIter<'a, 'b, K>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+{
+    /// constructor
+    pub fn new(keyset: &'b Keyset<'a, K>, storage: &'b dyn Storage, start: u32, end: u32) -> Self {
+        Self {
+            keyset,
+            storage,
+            start,
+            end,
+        }
+    }
+}
+
+impl<'a, 'b, K> Iterator for KeysetIter<'a, 'b, K>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+{
+    type Item = StdResult<K::Output>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        let item = self.keyset.get_at_unchecked(self.storage, self.start);
+        self.start += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.end - self.start) as usize;
+        (len, Some(len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.start = self.start.saturating_add(n as u32);
+        self.next()
+    }
+}
+
+impl<'a, 'b, K> DoubleEndedIterator for KeysetIter<'a, 'b, K>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        let item = self.keyset.get_at_unchecked(self.storage, self.end);
+        Some(item)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.end = self.end.saturating_sub(n as u32);
+        self.next_back()
+    }
+}
+
+impl<'a, 'b, K> ExactSizeIterator for KeysetIter<'a, 'b, K> where K: PrimaryKey<'a> + KeyDeserialize {}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockStorage;
+    use rstest::*;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{Bincode2, Serde};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Data {
+        pub name: String,
+        pub age: i32,
+    }
+
+    const PEOPLE: Keymap<String, Data> = Keymap::new("people");
+    const B_PEOPLE: Keymap<String, Data, Bincode2> = Keymap::new("people");
+
+    fn data(name: &str, age: i32) -> Data {
+        Data {
+            name: name.to_string(),
+            age,
+        }
+    }
+
+    #[rstest]
+    fn insert_get_remove(
+        #[values(PEOPLE, B_PEOPLE)] people: Keymap<String, Data, impl Serde>,
+    ) -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        assert!(people.is_empty(&storage)?);
+
+        people.insert(&mut storage, "john".to_string(), &data("John", 32))?;
+        people.insert(&mut storage, "jim".to_string(), &data("Jim", 44))?;
+        assert_eq!(people.get_len(&storage)?, 2);
+        assert!(people.contains(&storage, "john".to_string())?);
+        assert_eq!(
+            people.get(&storage, "jim".to_string())?,
+            Some(data("Jim", 44))
+        );
+
+        // replacing keeps the length and the position
+        people.insert(&mut storage, "john".to_string(), &data("John", 33))?;
+        assert_eq!(people.get_len(&storage)?, 2);
+        assert_eq!(
+            people.get_at(&storage, 0)?,
+            ("john".to_string(), data("John", 33))
+        );
+
+        assert_eq!(
+            people.remove(&mut storage, "john".to_string())?,
+            Some(data("John", 33))
+        );
+        assert_eq!(people.remove(&mut storage, "john".to_string())?, None);
+        assert_eq!(people.get(&storage, "john".to_string())?, None);
+        assert_eq!(people.get_len(&storage)?, 1);
+        assert!(people.get_at(&storage, 1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn swap_remove_keeps_index_dense() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let keymap: Keymap<u32, u32> = Keymap::new("test");
+        for i in 0..5 {
+            keymap.insert(&mut storage, i, &(i * 10))?;
+        }
+
+        // the last key takes the position of the removed one
+        assert_eq!(keymap.remove(&mut storage, 1)?, Some(10));
+        assert_eq!(keymap.paging_keys(&storage, 0, 10)?, vec![0, 4, 2, 3]);
+
+        // removing the last key doesn't move anything
+        assert_eq!(keymap.remove(&mut storage, 3)?, Some(30));
+        let entries: StdResult<Vec<_>> = keymap.iter(&storage)?.collect();
+        assert_eq!(entries?, vec![(0, 0), (4, 40), (2, 20)]);
+
+        // and the moved key can still be removed
+        assert_eq!(keymap.remove(&mut storage, 4)?, Some(40));
+        keymap.insert(&mut storage, 7, &70)?;
+        let entries: StdResult<Vec<_>> = keymap.iter(&storage)?.collect();
+        assert_eq!(entries?, vec![(0, 0), (2, 20), (7, 70)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn iterator() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let keymap: Keymap<&str, i32> = Keymap::new("test");
+        keymap.insert(&mut storage, "a", &1234)?;
+        keymap.insert(&mut storage, "b", &2143)?;
+        keymap.insert(&mut storage, "c", &3412)?;
+        keymap.insert(&mut storage, "d", &4321)?;
+
+        let mut iter = keymap.iter(&storage)?;
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(Ok(("a".to_string(), 1234))));
+        assert_eq!(iter.next_back(), Some(Ok(("d".to_string(), 4321))));
+        assert_eq!(iter.next(), Some(Ok(("b".to_string(), 2143))));
+        assert_eq!(iter.next(), Some(Ok(("c".to_string(), 3412))));
+        assert_eq!(iter.next(), None);
+
+        // make sure our implementation of `nth` and `nth_back` doesn't break anything
+        let mut iter = keymap.iter(&storage)?.skip(2);
+        assert_eq!(iter.next(), Some(Ok(("c".to_string(), 3412))));
+        let mut iter = keymap.iter(&storage)?.rev().skip(2);
+        assert_eq!(iter.next(), Some(Ok(("b".to_string(), 2143))));
+
+        // make sure our implementation of `ExactSizeIterator` works well
+        let mut iter = keymap.iter(&storage)?.skip(2).rev();
+        assert_eq!(iter.next(), Some(Ok(("d".to_string(), 4321))));
+        assert_eq!(iter.next(), Some(Ok(("c".to_string(), 3412))));
+        assert_eq!(iter.next(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn paging() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let keymap: Keymap<u32, u32> = Keymap::new("test");
+
+        let page_size: u32 = 5;
+        let total_items: u32 = 23;
+
+        for i in 0..total_items {
+            keymap.insert(&mut storage, i, &(i * 2))?;
+        }
+
+        for start_page in 0..(total_items / page_size) {
+            let values = keymap.paging(&storage, start_page, page_size)?;
+            assert_eq!(values.len(), page_size as usize);
+            for (index, (key, value)) in values.into_iter().enumerate() {
+                assert_eq!(key, page_size * start_page + index as u32);
+                assert_eq!(value, key * 2);
+            }
+        }
+
+        // the last page is partial and anything after it is empty
+        assert_eq!(
+            keymap.paging_keys(&storage, 4, page_size)?,
+            vec![20, 21, 22]
+        );
+        assert!(keymap.paging(&storage, 5, page_size)?.is_empty());
+        assert!(keymap.paging(&storage, u32::MAX, page_size)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn suffixes_are_separate() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let original: Keymap<u32, u32> = Keymap::new("test");
+        let suffixed = original.add_suffix("user");
+
+        suffixed.insert(&mut storage, 1, &1)?;
+        assert_eq!(original.get_len(&storage)?, 0);
+        assert_eq!(original.get(&storage, 1)?, None);
+        assert_eq!(suffixed.get_len(&storage)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn prefixes_do_not_collide() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        // unprefixed, the value of "en" in "a" would be stored at the length key of "ava"
+        let short: Keymap<String, u32> = Keymap::new("a");
+        let long: Keymap<String, u32> = Keymap::new("ava");
+
+        short.insert(&mut storage, "en".to_string(), &7)?;
+        assert_eq!(long.get_len(&storage)?, 0);
+        long.insert(&mut storage, "x".to_string(), &1)?;
+        assert_eq!(short.get(&storage, "en".to_string())?, Some(7));
+        assert_eq!(short.get_len(&storage)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn keyset() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let keyset: Keyset<(&str, u32)> = Keyset::new("set");
+
+        assert!(keyset.insert(&mut storage, ("a", 1))?);
+        assert!(keyset.insert(&mut storage, ("a", 2))?);
+        assert!(keyset.insert(&mut storage, ("b", 1))?);
+        assert!(!keyset.insert(&mut storage, ("a", 1))?);
+        assert_eq!(keyset.get_len(&storage)?, 3);
+        assert!(keyset.contains(&storage, ("a", 2))?);
+
+        assert!(keyset.remove(&mut storage, ("a", 1))?);
+        assert!(!keyset.remove(&mut storage, ("a", 1))?);
+        assert!(!keyset.contains(&storage, ("a", 1))?);
+
+        let keys: StdResult<Vec<_>> = keyset.iter(&storage)?.rev().collect();
+        assert_eq!(keys?, vec![("a".to_string(), 2), ("b".to_string(), 1)]);
+        assert_eq!(keyset.paging(&storage, 1, 1)?, vec![("a".to_string(), 2)]);
+        assert_eq!(keyset.get_at(&storage, 0)?, ("b".to_string(), 1));
+        assert!(keyset.get_at(&storage, 2).is_err());
+
+        Ok(())
+    }
+}
@@ -1,10 +1,13 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Timestamp, Uint128, Uint256, Uint64};
 
 use crate::de::KeyDeserialize;
 use crate::helpers::namespaces_with_key;
 use crate::int_key::IntKey;
 
+/// A component of a storage key. More integer widths may be added, so matches on it outside of
+/// this crate need a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Key<'a> {
     Ref(&'a [u8]),
     Val8([u8; 1]),
@@ -12,6 +15,7 @@ pub enum Key<'a> {
     Val32([u8; 4]),
     Val64([u8; 8]),
     Val128([u8; 16]),
+    Val256([u8; 32]),
 }
 
 impl<'a> AsRef<[u8]> for Key<'a> {
@@ -23,6 +27,7 @@ impl<'a> AsRef<[u8]> for Key<'a> {
             Key::Val32(v) => v,
             Key::Val64(v) => v,
             Key::Val128(v) => v,
+            Key::Val256(v) => v,
         }
     }
 }
@@ -155,6 +160,29 @@ impl<
     }
 }
 
+// use generics for combining there - so we can use &[u8], Vec<u8>, or IntKey
+impl<
+        'a,
+        T: PrimaryKey<'a> + Prefixer<'a>,
+        U: PrimaryKey<'a> + Prefixer<'a>,
+        V: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
+        W: PrimaryKey<'a> + KeyDeserialize,
+    > PrimaryKey<'a> for (T, U, V, W)
+{
+    type Prefix = (T, U, V);
+    type SubPrefix = (T, U);
+    type Suffix = W;
+    type SuperSuffix = (V, W);
+
+    fn key(&self) -> Vec<Key> {
+        let mut keys = self.0.key();
+        keys.extend(self.1.key());
+        keys.extend(self.2.key());
+        keys.extend(self.3.key());
+        keys
+    }
+}
+
 pub trait Prefixer<'a> {
     /// returns 0 or more namespaces that should be length-prefixed and concatenated for range searches
     fn prefix(&self) -> Vec<Key>;
@@ -194,6 +222,18 @@ impl<'a, T: Prefixer<'a>, U: Prefixer<'a>, V: Prefixer<'a>> Prefixer<'a> for (T,
     }
 }
 
+impl<'a, T: Prefixer<'a>, U: Prefixer<'a>, V: Prefixer<'a>, W: Prefixer<'a>> Prefixer<'a>
+    for (T, U, V, W)
+{
+    fn prefix(&self) -> Vec<Key> {
+        let mut res = self.0.prefix();
+        res.extend(self.1.prefix().into_iter());
+        res.extend(self.2.prefix().into_iter());
+        res.extend(self.3.prefix().into_iter());
+        res
+    }
+}
+
 // Provide a string version of this to raw encode strings
 impl<'a> Prefixer<'a> for &'a str {
     fn prefix(&self) -> Vec<Key> {
@@ -302,6 +342,52 @@ macro_rules! integer_prefix {
 
 integer_prefix!(for i8, Val8, u8, Val8, i16, Val16, u16, Val16, i32, Val32, u32, Val32, i64, Val64, u64, Val64, i128, Val128, u128, Val128);
 
+integer_key!(for Uint64, Val64, Uint128, Val128, Uint256, Val256, Timestamp, Val64);
+
+integer_prefix!(for Uint64, Val64, Uint128, Val128, Uint256, Val256, Timestamp, Val64);
+
+#[cfg(feature = "ethnum")]
+integer_key!(for ethnum::U256, Val256, ethnum::I256, Val256);
+
+#[cfg(feature = "ethnum")]
+integer_prefix!(for ethnum::U256, Val256, ethnum::I256, Val256);
+
+/// `false` sorts before `true`
+impl<'a> PrimaryKey<'a> for bool {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key> {
+        vec![Key::Val8([*self as u8])]
+    }
+}
+
+impl<'a> Prefixer<'a> for bool {
+    fn prefix(&self) -> Vec<Key> {
+        vec![Key::Val8([*self as u8])]
+    }
+}
+
+/// chars are keyed by their code point, so they sort like strings of one char
+impl<'a> PrimaryKey<'a> for char {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key> {
+        vec![Key::Val32(u32::from(*self).to_be_bytes())]
+    }
+}
+
+impl<'a> Prefixer<'a> for char {
+    fn prefix(&self) -> Vec<Key> {
+        vec![Key::Val32(u32::from(*self).to_be_bytes())]
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -509,4 +595,58 @@ mod test {
             vec![one.as_slice(), two.as_slice(), three.as_slice()]
         );
     }
+
+    #[test]
+    fn cosmwasm_type_keys_work() {
+        let k = Uint64::new(12345);
+        assert_eq!(k.key(), vec![12345u64.to_be_bytes().as_slice()]);
+
+        let k = Uint128::new(12345);
+        assert_eq!(k.key(), vec![12345u128.to_be_bytes().as_slice()]);
+
+        let k = Uint256::from(12345u32);
+        let path = k.key();
+        assert_eq!(1, path.len());
+        assert_eq!(32, path[0].as_ref().len());
+        assert_eq!(k.to_be_bytes(), path[0].as_ref());
+
+        let k = Timestamp::from_nanos(12345);
+        assert_eq!(k.key(), vec![12345u64.to_be_bytes().as_slice()]);
+        assert_eq!(k.prefix(), vec![12345u64.to_be_bytes().as_slice()]);
+    }
+
+    #[test]
+    fn bool_and_char_keys_work() {
+        assert_eq!(false.key(), vec![[0u8].as_slice()]);
+        assert_eq!(true.key(), vec![[1u8].as_slice()]);
+        assert!(false.joined_key() < true.joined_key());
+
+        assert_eq!('a'.key(), vec![[0u8, 0, 0, 0x61].as_slice()]);
+        assert!('a'.joined_key() < 'b'.joined_key());
+        assert!('z'.joined_key() < '\u{e9}'.joined_key());
+    }
+
+    #[test]
+    fn quadruple_keys_work() {
+        let k: (&str, u32, bool, Uint128) = ("alice", 7, true, Uint128::new(100));
+        let path = k.key();
+        assert_eq!(4, path.len());
+        assert_eq!(
+            path,
+            vec![
+                b"alice".as_slice(),
+                [0, 0, 0, 7].as_slice(),
+                [1].as_slice(),
+                100u128.to_be_bytes().as_slice()
+            ]
+        );
+
+        let dir = (k.0, k.1, k.2, k.3).prefix();
+        assert_eq!(4, dir.len());
+        let sub: (&str, u32) = (k.0, k.1);
+        assert_eq!(
+            sub.prefix(),
+            vec![b"alice".as_slice(), [0, 0, 0, 7].as_slice()]
+        );
+    }
 }
@@ -1,30 +1,75 @@
 mod append_store;
+mod bound;
+mod cached_storage;
+mod counter;
 mod de;
+mod deque;
 mod deque_store;
 mod endian;
+mod entry;
+mod expiring;
+mod format_migration;
+mod heap;
 mod helpers;
+mod indexed_map;
+mod indexes;
 mod int_key;
 mod item;
+mod iter_helpers;
+mod key_decoder;
+mod keymap;
 mod keys;
 mod map;
+mod merkle_append_store;
+mod metered_storage;
 mod path;
+mod prefix;
 mod serialization;
+mod snapshot;
+mod sorted_map;
 mod traits;
+mod versioned;
 
-pub use append_store::AppendStore;
+pub use append_store::{AppendStore, AppendStoreFormat, CachedAppendStore};
+#[cfg(feature = "iterator")]
+pub use bound::{Bound, RawBound};
+pub use cached_storage::CachedStorage;
+pub use counter::{Counter, CounterInt, Sequence};
 pub use de::KeyDeserialize;
-pub use deque_store::DequeStore;
+pub use deque::{Deque, DequeIter};
+pub use deque_store::{CachedDequeStore, DequeStore, DequeStoreFormat};
 pub use endian::Endian;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use expiring::{Expiration, ExpiringFormat, ExpiringItem, ExpiringMap};
+pub use format_migration::{
+    migrate_append_store, migrate_item, migrate_map, migrate_map_keymap_page, MigrationReport,
+};
+pub use heap::StorageHeap;
+pub use indexed_map::{IndexList, IndexedMap};
+pub use indexes::{Index, MultiIndex, UniqueIndex};
 pub use int_key::IntKey;
 pub use item::Item;
+pub use key_decoder::{DecodedKey, KeyDecoder, KeySegment};
+pub use keymap::{Keymap, KeymapIter, Keyset, KeysetIter};
 pub use keys::{Key, Prefixer, PrimaryKey};
 pub use map::Map;
+pub use merkle_append_store::{verify_merkle_proof, MerkleAppendStore};
+pub use metered_storage::{AccessStats, MeteredStorage, StorageReport};
 pub use path::Path;
-pub use serialization::{Bincode2, Json, Serde};
+#[cfg(feature = "iterator")]
+pub use prefix::{range_with_prefix, Prefix};
+#[cfg(feature = "borsh")]
+pub use serialization::Borsh;
+pub use serialization::{
+    Bincode2, Cbor, Compressed, Format, Json, Padded, Postcard, Serde, SpaceDelimited,
+};
+pub use snapshot::{ChangeSet, SnapshotFormat, SnapshotItem, SnapshotMap, Strategy};
+pub use sorted_map::{SortedMap, SortedMapFormat, SortedMapIter};
 pub use traits::{
     GenericItemStorage, GenericMapStorage, ItemStorage, MapStorage, NaiveItemStorage,
     NaiveMapStorage,
 };
+pub use versioned::{migrate, Migration, VersionedItem, VersionedMap};
 
 #[cfg(test)]
 pub use rstest_reuse;
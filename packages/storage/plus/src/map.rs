@@ -1,12 +1,16 @@
-use serde::de::DeserializeOwned;
-use serde::Serialize;
 use std::marker::PhantomData;
 
+use crate::entry::Entry;
 use crate::helpers::query_raw;
 use crate::keys::{Key, PrimaryKey};
 use crate::path::Path;
-use crate::{Json, Serde};
-use cosmwasm_std::{from_slice, Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
+use crate::{Format, Json};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
+
+#[cfg(feature = "iterator")]
+use crate::{de::KeyDeserialize, keys::Prefixer, prefix::Prefix, Bound};
+#[cfg(feature = "iterator")]
+use cosmwasm_std::{Order, Record};
 
 #[derive(Debug, Clone)]
 pub struct Map<'a, K, T, Ser = Json> {
@@ -17,10 +21,7 @@ pub struct Map<'a, K, T, Ser = Json> {
     serialization_type: PhantomData<*const Ser>,
 }
 
-impl<'a, K, T, Ser> Map<'a, K, T, Ser>
-where
-    Ser: Serde,
-{
+impl<'a, K, T, Ser> Map<'a, K, T, Ser> {
     pub const fn new(namespace: &'a str) -> Self {
         Map {
             namespace: namespace.as_bytes(),
@@ -33,13 +34,23 @@ where
     pub fn namespace(&self) -> &'a [u8] {
         self.namespace
     }
+
+    /// Returns a map over the same keys that uses another serialization format, e.g. to migrate
+    /// its values in place
+    pub const fn with_format<S>(&self) -> Map<'a, K, T, S> {
+        Map {
+            namespace: self.namespace,
+            data_type: PhantomData,
+            key_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
 }
 
 impl<'a, K, T, Ser> Map<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
     K: PrimaryKey<'a>,
-    Ser: Serde,
+    Ser: Format<T>,
 {
     pub fn key(&self, k: K) -> Path<T, Ser> {
         Path::new(
@@ -85,8 +96,14 @@ where
         self.key(k).update(store, action)
     }
 
+    /// Reads the value at the key once, and returns an `Entry` to inspect and change it with
+    /// at most one more write.
+    pub fn entry<'s>(&self, store: &'s mut dyn Storage, k: K) -> StdResult<Entry<'s, T, Ser>> {
+        self.key(k).entry(store)
+    }
+
     /// If you import the proper Map from the remote contract, this will let you read the data
-    /// from a remote contract in a type-safe way using WasmQuery::RawQuery
+    /// from a remote contract in a type-safe way using a raw query, decoding it with `Ser`.
     pub fn query<Q: CustomQuery>(
         &self,
         querier: &QuerierWrapper<Q>,
@@ -99,11 +116,116 @@ where
         if result.is_empty() {
             Ok(None)
         } else {
-            from_slice(&result).map(Some)
+            Ser::decode(&result).map(Some)
+        }
+    }
+}
+
+#[cfg(feature = "iterator")]
+impl<'a, K, T, Ser> Map<'a, K, T, Ser>
+where
+    K: PrimaryKey<'a>,
+    Ser: Format<T>,
+{
+    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix, Ser> {
+        Prefix::new(self.namespace, &p.prefix())
+    }
+
+    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix, Ser> {
+        Prefix::new(self.namespace, &p.prefix())
+    }
+
+    fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K, Ser> {
+        Prefix::new(self.namespace, &[])
+    }
+
+    pub fn range_raw<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = StdResult<Record<T>>> + 'c>
+    where
+        T: 'c,
+    {
+        self.no_prefix_raw().range_raw(store, min, max, order)
+    }
+
+    pub fn keys_raw<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'c>
+    where
+        T: 'c,
+    {
+        self.no_prefix_raw().keys_raw(store, min, max, order)
+    }
+
+    /// Returns true if no key is stored under this map's namespace.
+    pub fn is_empty(&self, store: &dyn Storage) -> bool {
+        self.keys_raw(store, None, None, Order::Ascending)
+            .next()
+            .is_none()
+    }
+
+    /// Removes every entry of the map.
+    pub fn clear(&self, store: &mut dyn Storage) {
+        let prefix = self.no_prefix_raw();
+        let keys: Vec<_> = prefix
+            .keys_raw(store, None, None, Order::Ascending)
+            .collect();
+        for key in keys {
+            store.remove(&[&*prefix, &key].concat());
         }
     }
 }
 
+#[cfg(feature = "iterator")]
+impl<'a, K, T, Ser> Map<'a, K, T, Ser>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Format<T>,
+{
+    fn no_prefix(&self) -> Prefix<K, T, K, Ser> {
+        Prefix::new(self.namespace, &[])
+    }
+
+    /// Iterates over the whole map in key order, deserializing both the keys and the values.
+    /// Composite keys are returned as tuples, see `prefix` to only scan the entries below one
+    /// prefix.
+    pub fn range<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = StdResult<(K::Output, T)>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.no_prefix().range(store, min, max, order)
+    }
+
+    pub fn keys<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = StdResult<K::Output>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.no_prefix().keys(store, min, max, order)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -112,8 +234,12 @@ mod test {
     use serde::{Deserialize, Serialize};
     use std::ops::Deref;
 
-    use crate::{int_key::IntKey, Bincode2};
+    use crate::helpers::mock_remote_querier;
+    use crate::{int_key::IntKey, Bincode2, Serde};
     use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Empty;
+    #[cfg(feature = "iterator")]
+    use cosmwasm_std::Order;
 
     #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
     struct Data {
@@ -207,6 +333,28 @@ mod test {
         assert_eq!(None, john.may_load(&store).unwrap());
     }
 
+    #[rstest]
+    fn remote_query(#[values(PEOPLE, B_PEOPLE)] people: Map<Key, Data, impl Serde>) {
+        let mut store = MockStorage::new();
+        let data = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+        people.save(&mut store, b"john", &data).unwrap();
+
+        let remote = mock_remote_querier(store);
+        let querier = QuerierWrapper::<Empty>::new(&remote);
+        let contract = Addr::unchecked("remote");
+        let loaded = people
+            .query(&querier, contract.clone(), "hash".to_string(), b"john")
+            .unwrap();
+        assert_eq!(Some(data), loaded);
+        let missing = people
+            .query(&querier, contract, "hash".to_string(), b"jack")
+            .unwrap();
+        assert_eq!(None, missing);
+    }
+
     #[rstest]
     fn existence(#[values(PEOPLE, B_PEOPLE)] people: Map<Key, Data, impl Serde>) {
         let mut store = MockStorage::new();
@@ -408,4 +556,153 @@ mod test {
 
         Ok(())
     }
+
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_simple_key(
+        #[values(PEOPLE, B_PEOPLE)] people: Map<Key, Data, impl Serde>,
+    ) -> StdResult<()> {
+        let mut store = MockStorage::new();
+
+        let john = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+        let jim = Data {
+            name: "Jim".to_string(),
+            age: 44,
+        };
+        people.save(&mut store, b"john", &john)?;
+        people.save(&mut store, b"jim", &jim)?;
+        assert!(!people.is_empty(&store));
+
+        // keys and values are returned in key order
+        let all: StdResult<Vec<_>> = people.range(&store, None, None, Order::Ascending).collect();
+        assert_eq!(
+            all?,
+            vec![
+                (b"jim".to_vec(), jim.clone()),
+                (b"john".to_vec(), john.clone())
+            ]
+        );
+
+        let keys: StdResult<Vec<_>> = people.keys(&store, None, None, Order::Descending).collect();
+        assert_eq!(keys?, vec![b"john".to_vec(), b"jim".to_vec()]);
+
+        // bounds are applied on the full key
+        let all: StdResult<Vec<_>> = people
+            .range(
+                &store,
+                Some(Bound::inclusive(b"j" as &[u8])),
+                Some(Bound::exclusive(b"john" as &[u8])),
+                Order::Ascending,
+            )
+            .collect();
+        assert_eq!(all?, vec![(b"jim".to_vec(), jim)]);
+
+        let all: StdResult<Vec<_>> = people
+            .range(
+                &store,
+                Some(Bound::exclusive(b"jim" as &[u8])),
+                None,
+                Order::Ascending,
+            )
+            .collect();
+        assert_eq!(all?, vec![(b"john".to_vec(), john)]);
+
+        people.clear(&mut store);
+        assert!(people.is_empty(&store));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_composite_key(
+        #[values(ALLOWANCE, B_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde>,
+    ) -> StdResult<()> {
+        let mut store = MockStorage::new();
+
+        allowance.save(&mut store, (b"owner", b"spender"), &1000)?;
+        allowance.save(&mut store, (b"owner", b"spender2"), &3000)?;
+        allowance.save(&mut store, (b"owner2", b"spender"), &5000)?;
+
+        // the full key is deserialized into a tuple
+        let all: StdResult<Vec<_>> = allowance
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        assert_eq!(
+            all?,
+            vec![
+                ((b"owner".to_vec(), b"spender".to_vec()), 1000),
+                ((b"owner".to_vec(), b"spender2".to_vec()), 3000),
+                ((b"owner2".to_vec(), b"spender".to_vec()), 5000),
+            ]
+        );
+
+        // a prefix only returns the remaining part of the key
+        let owner: StdResult<Vec<_>> = allowance
+            .prefix(b"owner")
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        assert_eq!(
+            owner?,
+            vec![(b"spender".to_vec(), 1000), (b"spender2".to_vec(), 3000)]
+        );
+
+        let owner: StdResult<Vec<_>> = allowance
+            .prefix(b"owner")
+            .keys(
+                &store,
+                Some(Bound::exclusive(b"spender" as &[u8])),
+                None,
+                Order::Ascending,
+            )
+            .collect();
+        assert_eq!(owner?, vec![b"spender2".to_vec()]);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_triple_key(
+        #[values(TRIPLE, B_TRIPLE)] triple: Map<TKey, u64, impl Serde>,
+    ) -> StdResult<()> {
+        let mut store = MockStorage::new();
+
+        triple.save(&mut store, (b"owner", 9, "recipient"), &1000)?;
+        triple.save(&mut store, (b"owner", 9, "recipient2"), &3000)?;
+        triple.save(&mut store, (b"owner", 10, "recipient3"), &3000)?;
+        triple.save(&mut store, (b"owner2", 9, "recipient"), &5000)?;
+
+        // sub_prefix fixes the first element only
+        let owner: StdResult<Vec<_>> = triple
+            .sub_prefix(b"owner")
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        assert_eq!(
+            owner?,
+            vec![
+                ((9, "recipient".to_string()), 1000),
+                ((9, "recipient2".to_string()), 3000),
+                ((10, "recipient3".to_string()), 3000),
+            ]
+        );
+
+        // prefix fixes the first two
+        let owner: StdResult<Vec<_>> = triple
+            .prefix((b"owner", 9))
+            .range(&store, None, None, Order::Descending)
+            .collect();
+        assert_eq!(
+            owner?,
+            vec![
+                ("recipient2".to_string(), 3000),
+                ("recipient".to_string(), 1000),
+            ]
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,298 @@
+//! An append-only store that commits to its items with a Merkle tree, so single items can be
+//! proven to someone who only knows the root.
+//!
+//! The items are the leaves of a binary sha256 tree, padded with empty subtrees to the next
+//! power of two. Leaves are hashed as `sha256(0x00 || item)`, with the item serialized by the
+//! store's format, and inner nodes as `sha256(0x01 || left || right)`. Empty subtrees hash to
+//! 32 zero bytes. Every node is stored once its subtree is complete, so a push writes the
+//! frontier nodes it completes, and the root and proofs are rebuilt from O(log n) stored nodes.
+use sha2::{Digest, Sha256};
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::append_store::AppendStoreIter;
+use crate::{AppendStore, AppendStoreFormat, Format, Json};
+
+const NODE_NAMESPACE: &[u8] = b"merkle";
+const EMPTY_HASH: [u8; 32] = [0; 32];
+
+pub struct MerkleAppendStore<'a, T, Ser = Json>
+where
+    Ser: AppendStoreFormat<T>,
+{
+    store: AppendStore<'a, T, Ser>,
+}
+
+impl<'a, T, Ser: AppendStoreFormat<T>> MerkleAppendStore<'a, T, Ser> {
+    pub const fn new(prefix: &'a str) -> Self {
+        Self {
+            store: AppendStore::new(prefix),
+        }
+    }
+
+    /// This is used to produce a new store under the same namespace, e.g. one history per user
+    pub fn add_suffix(&self, suffix: &str) -> Self {
+        Self {
+            store: self.store.add_suffix(suffix),
+        }
+    }
+}
+
+impl<'a, T, Ser: AppendStoreFormat<T>> MerkleAppendStore<'a, T, Ser> {
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        self.store.get_len(storage)
+    }
+
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        self.store.is_empty(storage)
+    }
+
+    /// gets the element at pos if within bounds
+    pub fn get_at(&self, storage: &dyn Storage, pos: u32) -> StdResult<T> {
+        self.store.get_at(storage, pos)
+    }
+
+    /// Returns a readonly iterator
+    pub fn iter(&self, storage: &'a dyn Storage) -> StdResult<AppendStoreIter<T, Ser>> {
+        self.store.iter(storage)
+    }
+
+    /// does paging with the given parameters
+    pub fn paging(&self, storage: &dyn Storage, start_page: u32, size: u32) -> StdResult<Vec<T>> {
+        self.store.paging(storage, start_page, size)
+    }
+
+    /// Appends an item and stores the nodes whose subtrees it completes
+    pub fn push(&self, storage: &mut dyn Storage, item: &T) -> StdResult<()> {
+        let pos = self.len(storage)?;
+        self.store.push(storage, item)?;
+
+        let mut hash = leaf_hash::<T, Ser>(item)?;
+        let mut level = 0;
+        let mut index = pos;
+        self.save_node(storage, level, index, &hash);
+        // a right child completes its parent
+        while index % 2 == 1 {
+            let left = self.load_node(storage, level, index - 1)?;
+            hash = node_hash(&left, &hash);
+            level += 1;
+            index /= 2;
+            self.save_node(storage, level, index, &hash);
+        }
+        Ok(())
+    }
+
+    /// Returns the root of the tree over all items. The root of an empty store is 32 zero bytes.
+    pub fn root(&self, storage: &dyn Storage) -> StdResult<[u8; 32]> {
+        let len = self.len(storage)?;
+        self.subtree_hash(storage, depth(len), 0, len)
+    }
+
+    /// Returns the hashes of the siblings on the path from the item at `pos` to the root, the
+    /// sibling of the leaf first. The proof is checked against `root` with [`verify_merkle_proof`].
+    pub fn proof(&self, storage: &dyn Storage, pos: u32) -> StdResult<Vec<[u8; 32]>> {
+        let len = self.len(storage)?;
+        if pos >= len {
+            return Err(StdError::generic_err(
+                "MerkleAppendStore access out of bounds",
+            ));
+        }
+        (0..depth(len))
+            .map(|level| self.subtree_hash(storage, level, (pos >> level) ^ 1, len))
+            .collect()
+    }
+
+    /// Returns the hash of the node at `level` and `index` of a tree over the first `len` items.
+    /// Only a node that is partially filled is rebuilt from its children, and there is at most
+    /// one of those on each level.
+    fn subtree_hash(
+        &self,
+        storage: &dyn Storage,
+        level: u32,
+        index: u32,
+        len: u32,
+    ) -> StdResult<[u8; 32]> {
+        let start = u64::from(index) << level;
+        let end = (u64::from(index) + 1) << level;
+        if end <= u64::from(len) {
+            self.load_node(storage, level, index)
+        } else if start >= u64::from(len) {
+            Ok(EMPTY_HASH)
+        } else {
+            let left = self.subtree_hash(storage, level - 1, index * 2, len)?;
+            let right = self.subtree_hash(storage, level - 1, index * 2 + 1, len)?;
+            Ok(node_hash(&left, &right))
+        }
+    }
+
+    fn node_key(&self, level: u32, index: u32) -> Vec<u8> {
+        [
+            self.store.as_slice(),
+            NODE_NAMESPACE,
+            &[level as u8],
+            &index.to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    fn load_node(&self, storage: &dyn Storage, level: u32, index: u32) -> StdResult<[u8; 32]> {
+        storage
+            .get(&self.node_key(level, index))
+            .ok_or_else(|| StdError::not_found("merkle node"))?
+            .as_slice()
+            .try_into()
+            .map_err(|err| StdError::parse_err("[u8; 32]", err))
+    }
+
+    fn save_node(&self, storage: &mut dyn Storage, level: u32, index: u32, hash: &[u8; 32]) {
+        storage.set(&self.node_key(level, index), hash);
+    }
+}
+
+/// Returns true if `proof` proves that `item` is stored at `pos` in a [`MerkleAppendStore`]
+/// with the given root. `Ser` must be the format of the store.
+///
+/// This only needs the root, so it can be used by other contracts or off-chain.
+pub fn verify_merkle_proof<T, Ser: Format<T>>(
+    root: &[u8; 32],
+    pos: u32,
+    item: &T,
+    proof: &[[u8; 32]],
+) -> StdResult<bool> {
+    // positions are u32, so a tree has at most 32 levels above its leaves
+    if proof.len() > 32 || (proof.len() < 32 && pos >> proof.len() != 0) {
+        return Ok(false);
+    }
+    let mut hash = leaf_hash::<T, Ser>(item)?;
+    for (level, sibling) in proof.iter().enumerate() {
+        hash = if (pos >> level) & 1 == 0 {
+            node_hash(&hash, sibling)
+        } else {
+            node_hash(sibling, &hash)
+        };
+    }
+    Ok(hash == *root)
+}
+
+/// The number of levels above the leaves of a tree over `len` items
+fn depth(len: u32) -> u32 {
+    match len {
+        0 => 0,
+        len => u32::BITS - (len - 1).leading_zeros(),
+    }
+}
+
+fn leaf_hash<T, Ser: Format<T>>(item: &T) -> StdResult<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(Ser::encode(item)?);
+    Ok(hasher.finalize().into())
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bincode2;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn root_matches_padded_tree() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let store: MerkleAppendStore<String> = MerkleAppendStore::new("history");
+        assert_eq!(store.root(&storage)?, EMPTY_HASH);
+
+        let items = ["a", "b", "c"].map(String::from);
+        let leaves = items
+            .iter()
+            .map(leaf_hash::<String, Json>)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        store.push(&mut storage, &items[0])?;
+        assert_eq!(store.root(&storage)?, leaves[0]);
+        store.push(&mut storage, &items[1])?;
+        let left = node_hash(&leaves[0], &leaves[1]);
+        assert_eq!(store.root(&storage)?, left);
+        store.push(&mut storage, &items[2])?;
+        let right = node_hash(&leaves[2], &EMPTY_HASH);
+        assert_eq!(store.root(&storage)?, node_hash(&left, &right));
+        assert_eq!(store.proof(&storage, 2)?, vec![EMPTY_HASH, left]);
+        assert_eq!(store.get_at(&storage, 1)?, "b");
+        assert!(store.proof(&storage, 3).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn proofs_verify() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let store: MerkleAppendStore<u64, Bincode2> = MerkleAppendStore::new("rewards");
+
+        for len in 1..=17u32 {
+            store.push(&mut storage, &(u64::from(len) * 10))?;
+            let root = store.root(&storage)?;
+            for pos in 0..len {
+                let item = store.get_at(&storage, pos)?;
+                let proof = store.proof(&storage, pos)?;
+                assert_eq!(proof.len() as u32, depth(len));
+                assert!(verify_merkle_proof::<_, Bincode2>(
+                    &root, pos, &item, &proof
+                )?);
+                // a different item, position or format doesn't verify
+                assert!(!verify_merkle_proof::<_, Bincode2>(
+                    &root,
+                    pos,
+                    &(item + 1),
+                    &proof
+                )?);
+                assert!(!verify_merkle_proof::<_, Bincode2>(
+                    &root,
+                    pos ^ 1,
+                    &item,
+                    &proof
+                )?);
+                assert!(!verify_merkle_proof::<_, Json>(&root, pos, &item, &proof)?);
+            }
+        }
+
+        // proofs of an older root keep verifying against that root
+        let root = store.root(&storage)?;
+        let proof = store.proof(&storage, 3)?;
+        store.push(&mut storage, &1000)?;
+        assert_ne!(store.root(&storage)?, root);
+        assert!(verify_merkle_proof::<_, Bincode2>(
+            &root, 3, &40u64, &proof
+        )?);
+        // a position that doesn't fit in a tree of the proof's depth
+        assert!(!verify_merkle_proof::<_, Bincode2>(
+            &root, 32, &40u64, &proof
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn suffixes_are_separate_trees() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let store: MerkleAppendStore<u32> = MerkleAppendStore::new("history");
+        let alice = store.add_suffix("alice");
+        let bob = store.add_suffix("bob");
+
+        alice.push(&mut storage, &1)?;
+        alice.push(&mut storage, &2)?;
+        bob.push(&mut storage, &1)?;
+        assert_eq!(bob.len(&storage)?, 1);
+        assert_eq!(bob.root(&storage)?, leaf_hash::<u32, Json>(&1)?);
+        assert_ne!(alice.root(&storage)?, bob.root(&storage)?);
+        assert_eq!(
+            alice.iter(&storage)?.collect::<StdResult<Vec<_>>>()?,
+            vec![1, 2]
+        );
+        Ok(())
+    }
+}
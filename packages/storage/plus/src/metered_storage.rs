@@ -0,0 +1,295 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use cosmwasm_std::Storage;
+
+use crate::helpers::split_namespace;
+
+/// Storage accesses of one collection
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccessStats {
+    /// number of reads, including the records returned by range queries
+    pub reads: u32,
+    pub writes: u32,
+    pub removes: u32,
+    /// total length of the values read
+    pub bytes_read: u64,
+    /// total length of the values written
+    pub bytes_written: u64,
+}
+
+impl AccessStats {
+    fn add(&mut self, other: &AccessStats) {
+        self.reads += other.reads;
+        self.writes += other.writes;
+        self.removes += other.removes;
+        self.bytes_read += other.bytes_read;
+        self.bytes_written += other.bytes_written;
+    }
+}
+
+/// Storage accesses broken down per collection
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageReport {
+    /// accesses per namespace, rendered as UTF-8
+    pub collections: BTreeMap<String, AccessStats>,
+    pub total: AccessStats,
+}
+
+impl fmt::Display for StorageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>8} {:>8} {:>8} {:>12} {:>12}",
+            "collection", "reads", "writes", "removes", "bytes read", "bytes written"
+        )?;
+        let rows = self
+            .collections
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+            .chain(std::iter::once(("total", &self.total)));
+        for (name, stats) in rows {
+            writeln!(
+                f,
+                "{:<24} {:>8} {:>8} {:>8} {:>12} {:>12}",
+                name,
+                stats.reads,
+                stats.writes,
+                stats.removes,
+                stats.bytes_read,
+                stats.bytes_written
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Storage that counts the accesses that go through it, to find which collections dominate the
+/// gas used by a contract.
+///
+/// Accesses are attributed to the namespace of the collection that made them. Keys built with
+/// length-prefixed namespaces, as used by `Map`, `Keymap` and the indexed collections, are
+/// attributed to their first namespace. Collections that use their prefix as is, like `Item`,
+/// `AppendStore` and `DequeStore`, must be registered with `with_namespaces`, otherwise every one
+/// of their keys is reported on its own.
+///
+/// ```rust
+/// # use cosmwasm_std::testing::MockStorage;
+/// use secret_storage_plus::{AppendStore, Map, MeteredStorage};
+///
+/// const BALANCES: Map<&str, u64> = Map::new("balances");
+/// const HISTORY: AppendStore<u64> = AppendStore::new("history");
+///
+/// # let mut storage = MockStorage::new();
+/// let mut metered = MeteredStorage::new(&mut storage).with_namespaces(&["history"]);
+/// BALANCES.save(&mut metered, "alice", &10)?;
+/// HISTORY.push(&mut metered, &10)?;
+///
+/// let report = metered.report();
+/// assert_eq!(report.collections["balances"].writes, 1);
+/// assert_eq!(report.collections["history"].writes, 2);
+/// # Ok::<(), cosmwasm_std::StdError>(())
+/// ```
+pub struct MeteredStorage<'a> {
+    inner: &'a mut dyn Storage,
+    namespaces: Vec<Vec<u8>>,
+    stats: RefCell<BTreeMap<Vec<u8>, AccessStats>>,
+}
+
+impl<'a> MeteredStorage<'a> {
+    pub fn new(inner: &'a mut dyn Storage) -> Self {
+        Self {
+            inner,
+            namespaces: vec![],
+            stats: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Registers the prefixes of collections that don't length-prefix their namespace
+    pub fn with_namespaces(mut self, namespaces: &[&str]) -> Self {
+        self.namespaces.extend(
+            namespaces
+                .iter()
+                .map(|namespace| namespace.as_bytes().to_vec()),
+        );
+        self
+    }
+
+    /// Returns the accesses made so far, per collection
+    pub fn report(&self) -> StorageReport {
+        let mut report = StorageReport::default();
+        for (namespace, stats) in self.stats.borrow().iter() {
+            report
+                .collections
+                .insert(String::from_utf8_lossy(namespace).into_owned(), *stats);
+            report.total.add(stats);
+        }
+        report
+    }
+
+    /// Forgets the accesses made so far
+    pub fn reset(&self) {
+        self.stats.borrow_mut().clear();
+    }
+
+    /// Returns the namespace of the collection the key belongs to
+    fn namespace<'k>(&self, key: &'k [u8]) -> &'k [u8] {
+        let registered = self
+            .namespaces
+            .iter()
+            .filter(|namespace| key.starts_with(namespace))
+            .map(Vec::len)
+            .max();
+        match registered {
+            Some(len) => &key[..len],
+            None => split_namespace(key).map_or(key, |(namespace, _)| namespace),
+        }
+    }
+
+    fn record(&self, key: &[u8], update: impl FnOnce(&mut AccessStats)) {
+        let namespace = self.namespace(key);
+        let mut stats = self.stats.borrow_mut();
+        match stats.get_mut(namespace) {
+            Some(entry) => update(entry),
+            None => update(stats.entry(namespace.to_vec()).or_default()),
+        }
+    }
+}
+
+impl<'a> Storage for MeteredStorage<'a> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let value = self.inner.get(key);
+        self.record(key, |stats| {
+            stats.reads += 1;
+            stats.bytes_read += value.as_ref().map_or(0, Vec::len) as u64;
+        });
+        value
+    }
+
+    #[cfg(feature = "iterator")]
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = cosmwasm_std::Record> + 'b> {
+        Box::new(
+            self.inner
+                .range(start, end, order)
+                .inspect(move |(key, value)| {
+                    self.record(key, |stats| {
+                        stats.reads += 1;
+                        stats.bytes_read += value.len() as u64;
+                    })
+                }),
+        )
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.record(key, |stats| {
+            stats.writes += 1;
+            stats.bytes_written += value.len() as u64;
+        });
+        self.inner.set(key, value);
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.record(key, |stats| stats.removes += 1);
+        self.inner.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AppendStore, Item, Keymap, Map};
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::StdResult;
+
+    const CONFIG: Item<u64> = Item::new("config");
+    const BALANCES: Map<(&str, u32), u64> = Map::new("balances");
+    const HISTORY: AppendStore<u64> = AppendStore::new("history");
+    const NAMES: Keymap<u32, String> = Keymap::new("names");
+
+    #[test]
+    fn attributes_accesses_to_collections() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let mut metered = MeteredStorage::new(&mut storage).with_namespaces(&["history"]);
+
+        CONFIG.save(&mut metered, &1)?;
+        CONFIG.load(&metered)?;
+        BALANCES.save(&mut metered, ("alice", 1), &10)?;
+        BALANCES.save(&mut metered, ("bob", 2), &20)?;
+        BALANCES.remove(&mut metered, ("bob", 2));
+        assert_eq!(BALANCES.may_load(&metered, ("bob", 2))?, None);
+        HISTORY.push(&mut metered, &5)?;
+        NAMES.insert(&mut metered, 1, &"alice".to_string())?;
+
+        let report = metered.report();
+        assert_eq!(
+            report.collections["config"],
+            AccessStats {
+                reads: 1,
+                writes: 1,
+                removes: 0,
+                bytes_read: 1,
+                bytes_written: 1,
+            }
+        );
+        assert_eq!(
+            report.collections["balances"],
+            AccessStats {
+                reads: 1,
+                writes: 2,
+                removes: 1,
+                bytes_read: 0,
+                bytes_written: 4,
+            }
+        );
+        // the length and the item
+        assert_eq!(report.collections["history"].writes, 2);
+        assert!(report.collections["names"].writes > 0);
+        assert_eq!(report.collections.len(), 4);
+        assert_eq!(
+            report.total.writes,
+            report.collections.values().map(|stats| stats.writes).sum()
+        );
+        assert!(report.to_string().contains("balances"));
+
+        metered.reset();
+        assert_eq!(metered.report(), StorageReport::default());
+        Ok(())
+    }
+
+    #[test]
+    fn unregistered_prefixes_are_reported_per_key() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let mut metered = MeteredStorage::new(&mut storage);
+        HISTORY.push(&mut metered, &5)?;
+
+        let report = metered.report();
+        assert_eq!(report.collections.len(), 2);
+        assert!(report.collections.contains_key("historylen"));
+        Ok(())
+    }
+
+    #[cfg(feature = "iterator")]
+    #[test]
+    fn counts_range_records() -> StdResult<()> {
+        use cosmwasm_std::Order;
+
+        let mut storage = MockStorage::new();
+        let mut metered = MeteredStorage::new(&mut storage);
+        BALANCES.save(&mut metered, ("alice", 1), &10)?;
+        BALANCES.save(&mut metered, ("bob", 2), &20)?;
+        metered.reset();
+
+        let all = BALANCES
+            .range(&metered, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        assert_eq!(all.len(), 2);
+        assert_eq!(metered.report().collections["balances"].reads, 2);
+        Ok(())
+    }
+}
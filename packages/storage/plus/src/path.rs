@@ -1,18 +1,16 @@
-use serde::de::DeserializeOwned;
-use serde::Serialize;
 use std::marker::PhantomData;
 
+use crate::entry::Entry;
 use crate::helpers::{may_deserialize, must_deserialize, nested_namespaces_with_key};
 use crate::keys::Key;
-use crate::{Json, Serde};
+use crate::{Format, Json};
 use cosmwasm_std::{StdError, StdResult, Storage};
 use std::ops::Deref;
 
 #[derive(Debug, Clone)]
 pub struct Path<T, Ser = Json>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Format<T>,
 {
     /// all namespaces prefixes and concatenated with the key
     pub(crate) storage_key: Vec<u8>,
@@ -23,8 +21,7 @@ where
 
 impl<T, Ser> Deref for Path<T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Format<T>,
 {
     type Target = [u8];
 
@@ -35,8 +32,7 @@ where
 
 impl<T, Ser> Path<T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Format<T>,
 {
     pub fn new(namespace: &[u8], keys: &[&[u8]]) -> Self {
         let l = keys.len();
@@ -58,7 +54,7 @@ where
 
     /// save will serialize the model and store, returns an error on serialization issues
     pub fn save(&self, store: &mut dyn Storage, data: &T) -> StdResult<()> {
-        store.set(&self.storage_key, &Ser::serialize(data)?);
+        store.set(&self.storage_key, &Ser::encode(data)?);
         Ok(())
     }
 
@@ -99,4 +95,10 @@ where
         self.save(store, &output)?;
         Ok(output)
     }
+
+    /// Reads the value at the key once, and returns an `Entry` to inspect and change it with
+    /// at most one more write.
+    pub fn entry<'s>(&self, store: &'s mut dyn Storage) -> StdResult<Entry<'s, T, Ser>> {
+        Entry::new(store, self.storage_key.clone())
+    }
 }
@@ -0,0 +1,218 @@
+use std::any::type_name;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use cosmwasm_std::{StdError, StdResult};
+
+use crate::Format;
+
+/// Use borsh for serialization.
+///
+/// Borsh doesn't go through serde, so it is not a [`Serde`](crate::Serde) format, but it
+/// implements [`Format`] for every borsh type and can be used with the collections all the same.
+#[derive(Copy, Clone, Debug)]
+pub struct Borsh;
+
+impl Borsh {
+    pub fn serialize<T: BorshSerialize>(obj: &T) -> StdResult<Vec<u8>> {
+        borsh::to_vec(obj).map_err(|err| StdError::serialize_err(type_name::<T>(), err))
+    }
+
+    pub fn deserialize<T: BorshDeserialize>(data: &[u8]) -> StdResult<T> {
+        T::try_from_slice(data).map_err(|err| StdError::parse_err(type_name::<T>(), err))
+    }
+}
+
+impl<T: BorshSerialize + BorshDeserialize> Format<T> for Borsh {
+    fn encode(obj: &T) -> StdResult<Vec<u8>> {
+        Borsh::serialize(obj)
+    }
+
+    fn decode(data: &[u8]) -> StdResult<T> {
+        Borsh::deserialize(data)
+    }
+}
+
+// `#[borsh(serialize_with = ..., deserialize_with = ...)]` functions for the cosmwasm types that
+// the collections store next to the values, which don't implement the borsh traits themselves
+
+pub(crate) mod binary {
+    use borsh::io::{Read, Result, Write};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use cosmwasm_std::Binary;
+
+    /// Same encoding as the bytes as a `Vec<u8>`
+    pub fn serialize<W: Write>(binary: &Binary, writer: &mut W) -> Result<()> {
+        binary.as_slice().serialize(writer)
+    }
+
+    pub fn deserialize<R: Read>(reader: &mut R) -> Result<Binary> {
+        Vec::<u8>::deserialize_reader(reader).map(Binary::from)
+    }
+}
+
+pub(crate) mod timestamp {
+    use borsh::io::{Read, Result, Write};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use cosmwasm_std::Timestamp;
+
+    /// Same encoding as the nanoseconds as a `u64`
+    pub fn serialize<W: Write>(timestamp: &Timestamp, writer: &mut W) -> Result<()> {
+        timestamp.nanos().serialize(writer)
+    }
+
+    pub fn deserialize<R: Read>(reader: &mut R) -> Result<Timestamp> {
+        u64::deserialize_reader(reader).map(Timestamp::from_nanos)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        AppendStore, DequeStore, ExpiringItem, ExpiringMap, Index, IndexList, IndexedMap, Item,
+        Keymap, Map, MultiIndex, SnapshotItem, SnapshotMap, SortedMap, Strategy, UniqueIndex,
+        VersionedItem,
+    };
+    use borsh_derive::{BorshDeserialize, BorshSerialize};
+    use cosmwasm_std::testing::{mock_env, MockStorage};
+
+    // no serde derives, so this can only be stored with borsh
+    #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+    struct Data {
+        name: String,
+        age: u32,
+    }
+
+    fn data(age: u32) -> Data {
+        Data {
+            name: "john".to_string(),
+            age,
+        }
+    }
+
+    #[test]
+    fn collections_store_borsh_types() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+
+        let item: Item<Data, Borsh> = Item::new("item");
+        item.save(&mut storage, &data(1))?;
+        assert_eq!(item.load(&storage)?, data(1));
+        assert_eq!(storage.get(b"item"), Some(borsh::to_vec(&data(1)).unwrap()));
+
+        let map: Map<&str, Data, Borsh> = Map::new("map");
+        map.save(&mut storage, "john", &data(2))?;
+        assert_eq!(map.may_load(&storage, "john")?, Some(data(2)));
+        map.entry(&mut storage, "jack")?.or_insert(data(3))?;
+        assert_eq!(map.load(&storage, "jack")?, data(3));
+
+        let store: AppendStore<Data, Borsh> = AppendStore::new_with_bucket_size("store", 2);
+        store.extend(&mut storage, [data(4), data(5), data(6)])?;
+        assert_eq!(store.get_at(&storage, 2)?, data(6));
+        assert_eq!(store.pop(&mut storage)?, data(6));
+
+        let deque: DequeStore<Data, Borsh> = DequeStore::new_with_bucket_size("deque", 2);
+        deque.push_back(&mut storage, &data(7))?;
+        deque.push_front(&mut storage, &data(8))?;
+        assert_eq!(deque.get_at(&storage, 0)?, data(8));
+        assert_eq!(deque.pop_back(&mut storage)?, data(7));
+        Ok(())
+    }
+
+    struct DataIndexes<'a> {
+        pub name: MultiIndex<'a, String, Data, String, Borsh>,
+        pub age: UniqueIndex<'a, u32, Data, String, Borsh>,
+    }
+
+    impl<'a> IndexList<Data> for DataIndexes<'a> {
+        fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Data>> + '_> {
+            let v: Vec<&dyn Index<Data>> = vec![&self.name, &self.age];
+            Box::new(v.into_iter())
+        }
+    }
+
+    #[test]
+    fn keyed_collections_store_borsh_types() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let env = mock_env();
+
+        let keymap: Keymap<u32, Data, Borsh> = Keymap::new("keymap");
+        keymap.insert(&mut storage, 1, &data(1))?;
+        keymap.insert(&mut storage, 2, &data(2))?;
+        assert_eq!(keymap.get(&storage, 2)?, Some(data(2)));
+        assert_eq!(
+            keymap.paging(&storage, 0, 5)?,
+            vec![(1, data(1)), (2, data(2))]
+        );
+
+        let sorted: SortedMap<u32, Data, Borsh> = SortedMap::new_with_node_size("sorted", 3);
+        for age in (0..10).rev() {
+            sorted.save(&mut storage, &age, &data(age))?;
+        }
+        assert_eq!(sorted.load(&storage, &4)?, data(4));
+        assert_eq!(sorted.paging(&storage, 0, 3)?.len(), 3);
+
+        let expiring: ExpiringItem<Data, Borsh> = ExpiringItem::new("expiring");
+        let expires = Expiration::AtTime(env.block.time.plus_seconds(10));
+        expiring.save(&mut storage, &data(3), expires)?;
+        assert_eq!(
+            expiring.may_load_with_expiration(&storage, &env)?,
+            Some((data(3), expires))
+        );
+
+        let expiring_map: ExpiringMap<&str, Data, Borsh> =
+            ExpiringMap::new("expiring_map", "expiring_map__index");
+        expiring_map.save(&mut storage, "john", &data(4), Expiration::AtHeight(1))?;
+        assert_eq!(expiring_map.may_load(&storage, &env, "john")?, None);
+
+        let snapshot: SnapshotItem<Data, Borsh> = SnapshotItem::new(
+            "snapshot",
+            "snapshot__check",
+            "snapshot__change",
+            Strategy::EveryBlock,
+        );
+        snapshot.save(&mut storage, &data(5), 1)?;
+        snapshot.save(&mut storage, &data(6), 2)?;
+        assert_eq!(snapshot.may_load_at_height(&storage, 2)?, Some(data(5)));
+        assert_eq!(snapshot.load(&storage)?, data(6));
+
+        let snapshot_map: SnapshotMap<&str, Data, Borsh> = SnapshotMap::new(
+            "snapshot_map",
+            "snapshot_map__check",
+            "snapshot_map__change",
+            Strategy::EveryBlock,
+        );
+        snapshot_map.save(&mut storage, "john", &data(7), 1)?;
+        snapshot_map.remove(&mut storage, "john", 2)?;
+        assert_eq!(
+            snapshot_map.may_load_at_height(&storage, "john", 2)?,
+            Some(data(7))
+        );
+        assert_eq!(snapshot_map.may_load(&storage, "john")?, None);
+
+        let versioned: VersionedItem<Data, Borsh> = VersionedItem::new("versioned", &[]);
+        versioned.save(&mut storage, &data(8))?;
+        assert_eq!(versioned.load(&storage)?, data(8));
+
+        let indexed = IndexedMap::<&str, Data, DataIndexes, Borsh>::new(
+            "indexed",
+            DataIndexes {
+                name: MultiIndex::new(|d| d.name.clone(), "indexed", "indexed__name"),
+                age: UniqueIndex::new(|d| d.age, "indexed__age"),
+            },
+        );
+        indexed.save(&mut storage, "1", &data(9))?;
+        indexed.save(&mut storage, "2", &data(10))?;
+        assert_eq!(
+            indexed
+                .idx
+                .name
+                .paging(&storage, "john".to_string(), 0, 5)?,
+            vec![("1".to_string(), data(9)), ("2".to_string(), data(10))]
+        );
+        assert_eq!(
+            indexed.idx.age.item(&storage, 10)?,
+            Some((b"2".to_vec(), data(10)))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use crate::Serde;
+use cosmwasm_std::{StdError, StdResult};
+use serde::{de::DeserializeOwned, Serialize};
+use std::any::type_name;
+
+/// Use CBOR for serialization.
+#[derive(Copy, Clone, Debug)]
+pub struct Cbor;
+
+impl Serde for Cbor {
+    fn serialize<T: Serialize>(obj: &T) -> StdResult<Vec<u8>> {
+        let mut data = vec![];
+        ciborium::ser::into_writer(obj, &mut data)
+            .map_err(|err| StdError::serialize_err(type_name::<T>(), err))?;
+        Ok(data)
+    }
+
+    fn deserialize<T: DeserializeOwned>(data: &[u8]) -> StdResult<T> {
+        ciborium::de::from_reader(data).map_err(|err| StdError::parse_err(type_name::<T>(), err))
+    }
+}
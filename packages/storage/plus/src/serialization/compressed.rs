@@ -0,0 +1,92 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use cosmwasm_std::{StdError, StdResult};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::Serde;
+
+const UNCOMPRESSED: u8 = 0;
+const LZ4: u8 = 1;
+
+/// Wraps another format and compresses its output with lz4 once it is longer than `THRESHOLD`
+/// bytes.
+///
+/// Every value starts with a header byte telling whether the rest of it is compressed, so values
+/// on both sides of the threshold are read back the same way. Values that don't shrink when
+/// compressed are stored uncompressed.
+#[derive(Copy, Clone, Debug)]
+pub struct Compressed<Ser, const THRESHOLD: usize = 128>(PhantomData<Ser>);
+
+impl<Ser: Serde, const THRESHOLD: usize> Serde for Compressed<Ser, THRESHOLD> {
+    fn serialize<T: Serialize>(obj: &T) -> StdResult<Vec<u8>> {
+        let data = Ser::serialize(obj)?;
+        if data.len() > THRESHOLD {
+            let compressed = lz4_flex::compress_prepend_size(&data);
+            if compressed.len() < data.len() {
+                return Ok(with_header(LZ4, &compressed));
+            }
+        }
+        Ok(with_header(UNCOMPRESSED, &data))
+    }
+
+    fn deserialize<T: DeserializeOwned>(data: &[u8]) -> StdResult<T> {
+        match data.split_first() {
+            Some((&UNCOMPRESSED, data)) => Ser::deserialize(data),
+            Some((&LZ4, data)) => {
+                let data = lz4_flex::decompress_size_prepended(data)
+                    .map_err(|err| StdError::parse_err(type_name::<T>(), err))?;
+                Ser::deserialize(&data)
+            }
+            _ => Err(StdError::parse_err(
+                type_name::<T>(),
+                "unknown compression header",
+            )),
+        }
+    }
+}
+
+fn with_header(header: u8, data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len() + 1);
+    result.push(header);
+    result.extend_from_slice(data);
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Json, Postcard};
+
+    #[test]
+    fn compresses_above_threshold() {
+        type Ser = Compressed<Json, 16>;
+
+        let small = "short".to_string();
+        let data = Ser::serialize(&small).unwrap();
+        assert_eq!(data, b"\x00\"short\"".to_vec());
+        assert_eq!(Ser::deserialize::<String>(&data).unwrap(), small);
+
+        let large = "repeat".repeat(50);
+        let data = Ser::serialize(&large).unwrap();
+        assert_eq!(data[0], LZ4);
+        assert!(data.len() < large.len());
+        assert_eq!(Ser::deserialize::<String>(&data).unwrap(), large);
+    }
+
+    #[test]
+    fn keeps_incompressible_values() {
+        type Ser = Compressed<Postcard, 0>;
+
+        let value: Vec<u8> = (0..=255).collect();
+        let data = Ser::serialize(&value).unwrap();
+        assert_eq!(data[0], UNCOMPRESSED);
+        assert_eq!(Ser::deserialize::<Vec<u8>>(&data).unwrap(), value);
+    }
+
+    #[test]
+    fn rejects_unknown_header() {
+        assert!(Compressed::<Json>::deserialize::<String>(b"").is_err());
+        assert!(Compressed::<Json>::deserialize::<String>(b"\x07\"short\"").is_err());
+    }
+}
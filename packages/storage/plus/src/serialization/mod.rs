@@ -2,7 +2,13 @@ use cosmwasm_std::StdResult;
 use serde::{de::DeserializeOwned, Serialize};
 
 mod bincode2;
+#[cfg(feature = "borsh")]
+pub(crate) mod borsh;
+mod cbor;
+mod compressed;
 mod json;
+mod padded;
+mod postcard;
 
 /// This trait represents the ability to both serialize and deserialize using a specific format.
 ///
@@ -15,5 +21,44 @@ pub trait Serde {
     fn deserialize<T: DeserializeOwned>(data: &[u8]) -> StdResult<T>;
 }
 
+/// The ability to serialize and deserialize values of type `T` using a specific format.
+///
+/// Collections are generic over this trait rather than over [`Serde`], so that formats that
+/// don't go through serde, like `Borsh`, can be used with them as well. Every [`Serde`] format
+/// implements it for all serde types.
+///
+/// The compound values a collection stores, like the buckets of an `AppendStore`, are encoded
+/// through the implementations for those types, e.g. `Format<Vec<T>>`.
+pub trait Format<T> {
+    fn encode(obj: &T) -> StdResult<Vec<u8>>;
+    fn decode(data: &[u8]) -> StdResult<T>;
+}
+
+impl<T, Ser> Format<T> for Ser
+where
+    T: Serialize + DeserializeOwned,
+    Ser: Serde,
+{
+    fn encode(obj: &T) -> StdResult<Vec<u8>> {
+        Ser::serialize(obj)
+    }
+
+    fn decode(data: &[u8]) -> StdResult<T> {
+        Ser::deserialize(data)
+    }
+}
+
+/// Returns an owned copy of `obj` by encoding and decoding it, so that a compound value can be
+/// built around a borrowed item without requiring `T: Clone`
+pub(crate) fn owned_copy<T, Ser: Format<T>>(obj: &T) -> StdResult<T> {
+    Ser::decode(&Ser::encode(obj)?)
+}
+
 pub use self::bincode2::Bincode2;
+#[cfg(feature = "borsh")]
+pub use self::borsh::Borsh;
+pub use self::cbor::Cbor;
+pub use self::compressed::Compressed;
 pub use self::json::Json;
+pub use self::padded::{Padded, SpaceDelimited};
+pub use self::postcard::Postcard;
@@ -0,0 +1,95 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::StdResult;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Json, Serde};
+
+/// Formats whose output never ends with a space, so that trailing spaces can be stripped from a
+/// value without a length prefix. Json is one, binary formats are not.
+pub trait SpaceDelimited: Serde {}
+
+impl SpaceDelimited for Json {}
+
+/// Wraps another format and pads its output with spaces up to a multiple of `BLOCK` bytes, so
+/// that the size of a stored value doesn't reveal its contents.
+///
+/// Values are padded exactly like `shade_toolkit::space_pad` pads messages, so a stored value is
+/// the output of `Ser` followed by spaces. Reading a value strips the trailing spaces before it
+/// reaches `Ser`, which is why `Ser` must be [`SpaceDelimited`].
+#[derive(Copy, Clone, Debug)]
+pub struct Padded<Ser, const BLOCK: usize>(PhantomData<Ser>);
+
+impl<Ser: SpaceDelimited, const BLOCK: usize> Serde for Padded<Ser, BLOCK> {
+    fn serialize<T: Serialize>(obj: &T) -> StdResult<Vec<u8>> {
+        let mut data = Ser::serialize(obj)?;
+        space_pad(&mut data, BLOCK);
+        Ok(data)
+    }
+
+    fn deserialize<T: DeserializeOwned>(data: &[u8]) -> StdResult<T> {
+        let end = data
+            .iter()
+            .rposition(|byte| *byte != b' ')
+            .map_or(0, |last| last + 1);
+        Ser::deserialize(&data[..end])
+    }
+}
+
+/// Pads the data up to a multiple of `block_size` with spaces at the end
+fn space_pad(data: &mut Vec<u8>, block_size: usize) {
+    if block_size == 0 {
+        return;
+    }
+    let surplus = data.len() % block_size;
+    if surplus != 0 {
+        data.resize(data.len() + block_size - surplus, b' ');
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pads_to_block_multiples() {
+        type Ser = Padded<Json, 32>;
+
+        let small = Ser::serialize(&1u128).unwrap();
+        let large = Ser::serialize(&u128::MAX).unwrap();
+        assert_eq!(small.len(), 32);
+        assert_eq!(large.len(), 64);
+        assert_eq!(Ser::deserialize::<u128>(&small).unwrap(), 1);
+        assert_eq!(Ser::deserialize::<u128>(&large).unwrap(), u128::MAX);
+
+        let long = "x".repeat(40);
+        let data = Ser::serialize(&long).unwrap();
+        assert_eq!(data.len(), 64);
+        assert_eq!(Ser::deserialize::<String>(&data).unwrap(), long);
+    }
+
+    #[test]
+    fn padding_is_spaces() {
+        let data = Padded::<Json, 16>::serialize(&"abc").unwrap();
+        assert_eq!(data, b"\"abc\"           ".to_vec());
+
+        // a value that already fills its blocks is left alone
+        let data = Padded::<Json, 5>::serialize(&"abc").unwrap();
+        assert_eq!(data, b"\"abc\"".to_vec());
+    }
+
+    #[test]
+    fn strips_only_the_padding() {
+        type Ser = Padded<Json, 16>;
+
+        // spaces inside the value are kept
+        let data = Ser::serialize(&"ab  ").unwrap();
+        assert_eq!(Ser::deserialize::<String>(&data).unwrap(), "ab  ");
+        // messages padded by the toolkit read back the same way
+        assert_eq!(
+            Ser::deserialize::<String>(b"\"abc\"           ").unwrap(),
+            "abc"
+        );
+        assert!(Ser::deserialize::<String>(b"                ").is_err());
+    }
+}
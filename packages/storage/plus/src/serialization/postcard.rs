@@ -0,0 +1,18 @@
+use crate::Serde;
+use cosmwasm_std::{StdError, StdResult};
+use serde::{de::DeserializeOwned, Serialize};
+use std::any::type_name;
+
+/// Use postcard for serialization.
+#[derive(Copy, Clone, Debug)]
+pub struct Postcard;
+
+impl Serde for Postcard {
+    fn serialize<T: Serialize>(obj: &T) -> StdResult<Vec<u8>> {
+        postcard::to_allocvec(obj).map_err(|err| StdError::serialize_err(type_name::<T>(), err))
+    }
+
+    fn deserialize<T: DeserializeOwned>(data: &[u8]) -> StdResult<T> {
+        postcard::from_bytes(data).map_err(|err| StdError::parse_err(type_name::<T>(), err))
+    }
+}
@@ -0,0 +1,303 @@
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::snapshot::{ChangeSet, Snapshot, SnapshotFormat};
+use crate::{AppendStore, Item, Json, Strategy};
+
+/// Item that maintains a snapshot of one or more checkpoints.
+/// We can query historical data as well as current state.
+/// What data is snapshotted depends on the Strategy.
+pub struct SnapshotItem<'a, T, Ser = Json> {
+    primary: Item<'a, T, Ser>,
+    snapshots: Snapshot<'a, T, Ser>,
+}
+
+impl<'a, T, Ser> SnapshotItem<'a, T, Ser> {
+    /// Example:
+    ///
+    /// ```rust
+    /// use secret_storage_plus::{SnapshotItem, Strategy};
+    ///
+    /// SnapshotItem::<'static, u64>::new(
+    ///     "every",
+    ///     "every__check",
+    ///     "every__change",
+    ///     Strategy::EveryBlock);
+    /// ```
+    pub const fn new(
+        storage_key: &'a str,
+        checkpoints: &'a str,
+        changelog: &'a str,
+        strategy: Strategy,
+    ) -> Self {
+        SnapshotItem {
+            primary: Item::new(storage_key),
+            snapshots: Snapshot::new(checkpoints, changelog, strategy),
+        }
+    }
+}
+
+impl<'a, T, Ser> SnapshotItem<'a, T, Ser>
+where
+    Ser: SnapshotFormat<T>,
+{
+    pub fn add_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        self.snapshots.add_checkpoint(store, height)
+    }
+
+    pub fn remove_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        self.snapshots.remove_checkpoint(store, height)
+    }
+
+    /// The recorded changes as `(height, ChangeSet)`, in order of increasing height.
+    pub fn changelog(&self) -> AppendStore<'a, (u64, ChangeSet<T>), Ser> {
+        self.snapshots.changelog(b"")
+    }
+
+    /// load old value and store changelog
+    fn write_change(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        // if there is already data in the changelog for this block, do not write more
+        if self.snapshots.has_changelog(store, b"", height)? {
+            return Ok(());
+        }
+        // otherwise, store the previous value
+        let old = self.primary.may_load(store)?;
+        self.snapshots.write_changelog(store, b"", height, old)
+    }
+
+    pub fn save(&self, store: &mut dyn Storage, data: &T, height: u64) -> StdResult<()> {
+        if self.snapshots.should_checkpoint(store, b"")? {
+            self.write_change(store, height)?;
+        }
+        self.primary.save(store, data)
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        if self.snapshots.should_checkpoint(store, b"")? {
+            self.write_change(store, height)?;
+        }
+        self.primary.remove(store);
+        Ok(())
+    }
+
+    /// load will return an error if no data is set, or on parse error
+    pub fn load(&self, store: &dyn Storage) -> StdResult<T> {
+        self.primary.load(store)
+    }
+
+    /// may_load will parse the data stored if present, returns Ok(None) if no data there.
+    /// returns an error on parsing issues
+    pub fn may_load(&self, store: &dyn Storage) -> StdResult<Option<T>> {
+        self.primary.may_load(store)
+    }
+
+    /// may_load_at_height returns the value the item had at the beginning of the given height.
+    /// Returns StdError::NotFound if the height was not checkpointed.
+    pub fn may_load_at_height(&self, store: &dyn Storage, height: u64) -> StdResult<Option<T>> {
+        let snapshot = self.snapshots.may_load_at_height(store, b"", height)?;
+
+        if let Some(r) = snapshot {
+            Ok(r)
+        } else {
+            // otherwise, return current value
+            self.may_load(store)
+        }
+    }
+
+    // If there is no checkpoint for that height, then we return StdError::NotFound
+    pub fn assert_checkpointed(&self, store: &dyn Storage, height: u64) -> StdResult<()> {
+        self.snapshots.assert_checkpointed(store, height)
+    }
+
+    /// Loads the data, perform the specified action, and store the result in the database.
+    /// This is a shorthand for some common sequences, which may be useful.
+    ///
+    /// If the data exists, `action(Some(value))` is called. Otherwise `action(None)` is called.
+    pub fn update<A, E>(&self, store: &mut dyn Storage, height: u64, action: A) -> Result<T, E>
+    where
+        A: FnOnce(Option<T>) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        let input = self.may_load(store)?;
+        let output = action(input)?;
+        self.save(store, &output, height)?;
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bincode2, Serde};
+    use cosmwasm_std::testing::MockStorage;
+    use rstest::*;
+
+    type TestItem<Ser> = SnapshotItem<'static, u64, Ser>;
+
+    const NEVER: TestItem<Json> =
+        SnapshotItem::new("never", "never__check", "never__change", Strategy::Never);
+    const EVERY: TestItem<Json> = SnapshotItem::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+    const SELECT: TestItem<Json> = SnapshotItem::new(
+        "select",
+        "select__check",
+        "select__change",
+        Strategy::Selected,
+    );
+    const B_NEVER: TestItem<Bincode2> =
+        SnapshotItem::new("never", "never__check", "never__change", Strategy::Never);
+    const B_EVERY: TestItem<Bincode2> = SnapshotItem::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+    const B_SELECT: TestItem<Bincode2> = SnapshotItem::new(
+        "select",
+        "select__check",
+        "select__change",
+        Strategy::Selected,
+    );
+
+    // Fills an item (u64) with the following writes:
+    // 1: 5
+    // 2: 7
+    // 3: 8
+    // 4: 1
+    // 5: None
+    // 6: 13
+    // 7: None
+    // 8: 22
+    // Final value: 22
+    // Value at beginning of 3 -> 7
+    // Value at beginning of 5 -> 1
+    fn init_data(item: &TestItem<impl Serde>, storage: &mut dyn Storage) {
+        item.save(storage, &5, 1).unwrap();
+        item.save(storage, &7, 2).unwrap();
+
+        // checkpoint 3
+        item.add_checkpoint(storage, 3).unwrap();
+
+        // also use update to set - to ensure this works
+        item.save(storage, &1, 3).unwrap();
+        item.update(storage, 3, |_| -> StdResult<u64> { Ok(8) })
+            .unwrap();
+
+        item.remove(storage, 4).unwrap();
+        item.save(storage, &13, 4).unwrap();
+
+        // checkpoint 5
+        item.add_checkpoint(storage, 5).unwrap();
+        item.remove(storage, 5).unwrap();
+        item.update(storage, 5, |_| -> StdResult<u64> { Ok(22) })
+            .unwrap();
+        // and delete it later (unknown if all data present)
+        item.remove_checkpoint(storage, 5).unwrap();
+    }
+
+    const FINAL_VALUE: Option<u64> = Some(22);
+
+    const VALUE_START_3: Option<u64> = Some(7);
+
+    const VALUE_START_5: Option<u64> = Some(13);
+
+    #[rstest]
+    fn never_works_like_normal_item(#[values(NEVER, B_NEVER)] item: TestItem<impl Serde>) {
+        let mut storage = MockStorage::new();
+        init_data(&item, &mut storage);
+        assert_eq!(FINAL_VALUE, item.may_load(&storage).unwrap());
+
+        // historical queries return error
+        assert!(item.may_load_at_height(&storage, 3).is_err());
+        assert!(item.may_load_at_height(&storage, 5).is_err());
+        assert!(item.changelog().is_empty(&storage).unwrap());
+    }
+
+    #[rstest]
+    fn every_blocks_stores_present_and_past(#[values(EVERY, B_EVERY)] item: TestItem<impl Serde>) {
+        let mut storage = MockStorage::new();
+        init_data(&item, &mut storage);
+        assert_eq!(FINAL_VALUE, item.may_load(&storage).unwrap());
+
+        // historical queries return historical values
+        assert_eq!(VALUE_START_3, item.may_load_at_height(&storage, 3).unwrap());
+        assert_eq!(VALUE_START_5, item.may_load_at_height(&storage, 5).unwrap());
+    }
+
+    #[rstest]
+    fn selected_shows_3_not_5(#[values(SELECT, B_SELECT)] item: TestItem<impl Serde>) {
+        let mut storage = MockStorage::new();
+        init_data(&item, &mut storage);
+        assert_eq!(FINAL_VALUE, item.may_load(&storage).unwrap());
+
+        // historical queries return historical values
+        assert_eq!(VALUE_START_3, item.may_load_at_height(&storage, 3).unwrap());
+        // never checkpointed
+        assert!(item.may_load_at_height(&storage, 1).is_err());
+        // deleted checkpoint
+        assert!(item.may_load_at_height(&storage, 5).is_err());
+    }
+
+    #[rstest]
+    fn handle_multiple_writes_in_one_block(#[values(EVERY, B_EVERY)] item: TestItem<impl Serde>) {
+        let mut storage = MockStorage::new();
+
+        item.save(&mut storage, &5, 1).unwrap();
+        item.save(&mut storage, &7, 2).unwrap();
+        item.save(&mut storage, &2, 2).unwrap();
+
+        // update and save - query at 3 => 2, at 4 => 12
+        item.update(&mut storage, 3, |_| -> StdResult<u64> { Ok(9) })
+            .unwrap();
+        item.save(&mut storage, &12, 3).unwrap();
+        assert_eq!(Some(5), item.may_load_at_height(&storage, 2).unwrap());
+        assert_eq!(Some(2), item.may_load_at_height(&storage, 3).unwrap());
+        assert_eq!(Some(12), item.may_load_at_height(&storage, 4).unwrap());
+
+        // save and remove - query at 4 => 1, at 5 => None
+        item.save(&mut storage, &17, 4).unwrap();
+        item.remove(&mut storage, 4).unwrap();
+        assert_eq!(Some(12), item.may_load_at_height(&storage, 4).unwrap());
+        assert_eq!(None, item.may_load_at_height(&storage, 5).unwrap());
+
+        // remove and update - query at 5 => 2, at 6 => 13
+        item.remove(&mut storage, 5).unwrap();
+        item.update(&mut storage, 5, |_| -> StdResult<u64> { Ok(2) })
+            .unwrap();
+        assert_eq!(None, item.may_load_at_height(&storage, 5).unwrap());
+        assert_eq!(Some(2), item.may_load_at_height(&storage, 6).unwrap());
+
+        // writing to an earlier height is rejected
+        assert!(item.save(&mut storage, &3, 4).is_err());
+    }
+
+    #[rstest]
+    fn changelog_paging_works(#[values(EVERY, B_EVERY)] item: TestItem<impl Serde>) {
+        let mut store = MockStorage::new();
+
+        item.save(&mut store, &5, 1).unwrap();
+        item.save(&mut store, &7, 2).unwrap();
+        item.update(&mut store, 3, |_| -> StdResult<u64> { Ok(8) })
+            .unwrap();
+        item.remove(&mut store, 4).unwrap();
+
+        let changelog = item.changelog();
+        assert_eq!(changelog.get_len(&store).unwrap(), 4);
+        assert_eq!(
+            changelog.paging(&store, 0, 10).unwrap(),
+            vec![
+                (1, ChangeSet { old: None }),
+                (2, ChangeSet { old: Some(5) }),
+                (3, ChangeSet { old: Some(7) }),
+                (4, ChangeSet { old: Some(8) })
+            ]
+        );
+        assert_eq!(
+            changelog.paging(&store, 1, 3).unwrap(),
+            vec![(4, ChangeSet { old: Some(8) })]
+        );
+    }
+}
@@ -0,0 +1,423 @@
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::keys::PrimaryKey;
+use crate::map::Map;
+use crate::path::Path;
+use crate::snapshot::{ChangeSet, Snapshot, SnapshotFormat};
+#[cfg(feature = "iterator")]
+use crate::{de::KeyDeserialize, prefix::Prefix, Bound};
+use crate::{AppendStore, Json, Strategy};
+#[cfg(feature = "iterator")]
+use cosmwasm_std::Order;
+
+/// Map that maintains a snapshots of one or more checkpoints.
+/// We can query historical data as well as current state.
+/// What data is snapshotted depends on the Strategy.
+pub struct SnapshotMap<'a, K, T, Ser = Json> {
+    primary: Map<'a, K, T, Ser>,
+    snapshots: Snapshot<'a, T, Ser>,
+}
+
+impl<'a, K, T, Ser> SnapshotMap<'a, K, T, Ser> {
+    /// Example:
+    ///
+    /// ```rust
+    /// use secret_storage_plus::{SnapshotMap, Strategy};
+    ///
+    /// SnapshotMap::<&[u8], &str>::new(
+    ///     "never",
+    ///     "never__check",
+    ///     "never__change",
+    ///     Strategy::EveryBlock
+    /// );
+    /// ```
+    pub const fn new(
+        pk: &'a str,
+        checkpoints: &'a str,
+        changelog: &'a str,
+        strategy: Strategy,
+    ) -> Self {
+        SnapshotMap {
+            primary: Map::new(pk),
+            snapshots: Snapshot::new(checkpoints, changelog, strategy),
+        }
+    }
+}
+
+impl<'a, K, T, Ser> SnapshotMap<'a, K, T, Ser>
+where
+    K: PrimaryKey<'a>,
+    Ser: SnapshotFormat<T>,
+{
+    pub fn add_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        self.snapshots.add_checkpoint(store, height)
+    }
+
+    pub fn remove_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        self.snapshots.remove_checkpoint(store, height)
+    }
+
+    /// The recorded changes of one key as `(height, ChangeSet)`, in order of increasing height.
+    pub fn changelog(&self, k: K) -> AppendStore<'a, (u64, ChangeSet<T>), Ser> {
+        self.snapshots.changelog(&k.joined_key())
+    }
+
+    pub fn key(&self, k: K) -> Path<T, Ser> {
+        self.primary.key(k)
+    }
+
+    /// load old value and store changelog
+    fn write_change(&self, store: &mut dyn Storage, k: K, height: u64) -> StdResult<()> {
+        let key = k.joined_key();
+        // if there is already data in the changelog for this key and block, do not write more
+        if self.snapshots.has_changelog(store, &key, height)? {
+            return Ok(());
+        }
+        // otherwise, store the previous value
+        let old = self.primary.may_load(store, k)?;
+        self.snapshots.write_changelog(store, &key, height, old)
+    }
+
+    pub fn save(&self, store: &mut dyn Storage, k: K, data: &T, height: u64) -> StdResult<()> {
+        if self.snapshots.should_checkpoint(store, &k.joined_key())? {
+            self.write_change(store, k.clone(), height)?;
+        }
+        self.primary.save(store, k, data)
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage, k: K, height: u64) -> StdResult<()> {
+        if self.snapshots.should_checkpoint(store, &k.joined_key())? {
+            self.write_change(store, k.clone(), height)?;
+        }
+        self.primary.remove(store, k);
+        Ok(())
+    }
+
+    /// load will return an error if no data is set at the given key, or on parse error
+    pub fn load(&self, store: &dyn Storage, k: K) -> StdResult<T> {
+        self.primary.load(store, k)
+    }
+
+    /// may_load will parse the data stored at the key if present, returns Ok(None) if no data there.
+    /// returns an error on issues parsing
+    pub fn may_load(&self, store: &dyn Storage, k: K) -> StdResult<Option<T>> {
+        self.primary.may_load(store, k)
+    }
+
+    pub fn has(&self, store: &dyn Storage, k: K) -> bool {
+        self.primary.has(store, k)
+    }
+
+    /// may_load_at_height returns the value the key had at the beginning of the given height.
+    /// Returns StdError::NotFound if the height was not checkpointed.
+    pub fn may_load_at_height(
+        &self,
+        store: &dyn Storage,
+        k: K,
+        height: u64,
+    ) -> StdResult<Option<T>> {
+        let snapshot = self
+            .snapshots
+            .may_load_at_height(store, &k.joined_key(), height)?;
+
+        if let Some(r) = snapshot {
+            Ok(r)
+        } else {
+            // otherwise, return current value
+            self.may_load(store, k)
+        }
+    }
+
+    pub fn assert_checkpointed(&self, store: &dyn Storage, height: u64) -> StdResult<()> {
+        self.snapshots.assert_checkpointed(store, height)
+    }
+
+    /// Loads the data, perform the specified action, and store the result
+    /// in the database. This is shorthand for some common sequences, which may be useful.
+    ///
+    /// If the data exists, `action(Some(value))` is called. Otherwise `action(None)` is called.
+    pub fn update<A, E>(
+        &self,
+        store: &mut dyn Storage,
+        k: K,
+        height: u64,
+        action: A,
+    ) -> Result<T, E>
+    where
+        A: FnOnce(Option<T>) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        let input = self.may_load(store, k.clone())?;
+        let output = action(input)?;
+        self.save(store, k, &output, height)?;
+        Ok(output)
+    }
+}
+
+#[cfg(feature = "iterator")]
+impl<'a, K, T, Ser> SnapshotMap<'a, K, T, Ser>
+where
+    K: PrimaryKey<'a>,
+    Ser: SnapshotFormat<T>,
+{
+    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix, Ser> {
+        self.primary.sub_prefix(p)
+    }
+
+    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix, Ser> {
+        self.primary.prefix(p)
+    }
+}
+
+#[cfg(feature = "iterator")]
+impl<'a, K, T, Ser> SnapshotMap<'a, K, T, Ser>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: SnapshotFormat<T>,
+{
+    pub fn range<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = StdResult<(K::Output, T)>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.primary.range(store, min, max, order)
+    }
+
+    pub fn keys<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = StdResult<K::Output>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.primary.keys(store, min, max, order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bincode2, Serde};
+    use cosmwasm_std::testing::MockStorage;
+    use rstest::*;
+
+    type TestMap<Ser> = SnapshotMap<'static, &'static str, u64, Ser>;
+    type TestMapCompositeKey<Ser> = SnapshotMap<'static, (&'static str, &'static str), u64, Ser>;
+
+    const NEVER: TestMap<Json> =
+        SnapshotMap::new("never", "never__check", "never__change", Strategy::Never);
+    const EVERY: TestMap<Json> = SnapshotMap::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+    const SELECT: TestMap<Json> = SnapshotMap::new(
+        "select",
+        "select__check",
+        "select__change",
+        Strategy::Selected,
+    );
+    const B_NEVER: TestMap<Bincode2> =
+        SnapshotMap::new("never", "never__check", "never__change", Strategy::Never);
+    const B_EVERY: TestMap<Bincode2> = SnapshotMap::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+    const B_SELECT: TestMap<Bincode2> = SnapshotMap::new(
+        "select",
+        "select__check",
+        "select__change",
+        Strategy::Selected,
+    );
+
+    const EVERY_COMPOSITE_KEY: TestMapCompositeKey<Json> = SnapshotMap::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+
+    // Fills a map &[u8] -> u64 with the following writes:
+    // 1: A = 5
+    // 2: B = 7
+    // 3: C = 1, A = 8
+    // 4: B = None, C = 13
+    // 5: A = None, D = 22
+    // Final values -> C = 13, D = 22
+    // Values at beginning of 3 -> A = 5, B = 7
+    // Values at beginning of 5 -> A = 8, C = 13
+    fn init_data(map: &TestMap<impl Serde>, storage: &mut dyn Storage) {
+        map.save(storage, "A", &5, 1).unwrap();
+        map.save(storage, "B", &7, 2).unwrap();
+
+        // checkpoint 3
+        map.add_checkpoint(storage, 3).unwrap();
+
+        // also use update to set - to ensure this works
+        map.save(storage, "C", &1, 3).unwrap();
+        map.update(storage, "A", 3, |_| -> StdResult<u64> { Ok(8) })
+            .unwrap();
+
+        map.remove(storage, "B", 4).unwrap();
+        map.save(storage, "C", &13, 4).unwrap();
+
+        // checkpoint 5
+        map.add_checkpoint(storage, 5).unwrap();
+        map.remove(storage, "A", 5).unwrap();
+        map.update(storage, "D", 5, |_| -> StdResult<u64> { Ok(22) })
+            .unwrap();
+        // and delete it later (unknown if all data present)
+        map.remove_checkpoint(storage, 5).unwrap();
+    }
+
+    const FINAL_VALUES: &[(&str, Option<u64>)] =
+        &[("A", None), ("B", None), ("C", Some(13)), ("D", Some(22))];
+
+    const VALUES_START_3: &[(&str, Option<u64>)] =
+        &[("A", Some(5)), ("B", Some(7)), ("C", None), ("D", None)];
+
+    const VALUES_START_5: &[(&str, Option<u64>)] =
+        &[("A", Some(8)), ("B", None), ("C", Some(13)), ("D", None)];
+
+    fn assert_final_values(map: &TestMap<impl Serde>, storage: &dyn Storage) {
+        for (k, v) in FINAL_VALUES.iter().cloned() {
+            assert_eq!(v, map.may_load(storage, k).unwrap());
+        }
+    }
+
+    fn assert_values_at_height(
+        map: &TestMap<impl Serde>,
+        storage: &dyn Storage,
+        height: u64,
+        values: &[(&str, Option<u64>)],
+    ) {
+        for (k, v) in values.iter().cloned() {
+            assert_eq!(v, map.may_load_at_height(storage, k, height).unwrap());
+        }
+    }
+
+    fn assert_missing_checkpoint(map: &TestMap<impl Serde>, storage: &dyn Storage, height: u64) {
+        for k in &["A", "B", "C", "D"] {
+            assert!(map.may_load_at_height(storage, *k, height).is_err());
+        }
+    }
+
+    #[rstest]
+    fn never_works_like_normal_map(#[values(NEVER, B_NEVER)] map: TestMap<impl Serde>) {
+        let mut storage = MockStorage::new();
+        init_data(&map, &mut storage);
+        assert_final_values(&map, &storage);
+
+        // historical queries return error
+        assert_missing_checkpoint(&map, &storage, 3);
+        assert_missing_checkpoint(&map, &storage, 5);
+    }
+
+    #[rstest]
+    fn every_blocks_stores_present_and_past(#[values(EVERY, B_EVERY)] map: TestMap<impl Serde>) {
+        let mut storage = MockStorage::new();
+        init_data(&map, &mut storage);
+        assert_final_values(&map, &storage);
+
+        // historical queries return historical values
+        assert_values_at_height(&map, &storage, 3, VALUES_START_3);
+        assert_values_at_height(&map, &storage, 5, VALUES_START_5);
+    }
+
+    #[rstest]
+    fn selected_shows_3_not_5(#[values(SELECT, B_SELECT)] map: TestMap<impl Serde>) {
+        let mut storage = MockStorage::new();
+        init_data(&map, &mut storage);
+        assert_final_values(&map, &storage);
+
+        // historical queries return historical values
+        assert_values_at_height(&map, &storage, 3, VALUES_START_3);
+        // never checkpointed
+        assert_missing_checkpoint(&map, &storage, 1);
+        // deleted checkpoint
+        assert_missing_checkpoint(&map, &storage, 5);
+    }
+
+    #[rstest]
+    fn handle_multiple_writes_in_one_block(#[values(EVERY, B_EVERY)] map: TestMap<impl Serde>) {
+        let mut storage = MockStorage::new();
+
+        map.save(&mut storage, "A", &5, 1).unwrap();
+        map.save(&mut storage, "B", &7, 2).unwrap();
+        map.save(&mut storage, "C", &2, 2).unwrap();
+
+        // also use update to set - to ensure this works
+        map.save(&mut storage, "A", &1, 3).unwrap();
+        map.update(&mut storage, "A", 3, |_| -> StdResult<u64> { Ok(8) })
+            .unwrap();
+
+        map.remove(&mut storage, "B", 4).unwrap();
+        map.save(&mut storage, "B", &13, 4).unwrap();
+
+        assert_eq!(Some(5), map.may_load_at_height(&storage, "A", 3).unwrap());
+        assert_eq!(Some(8), map.may_load_at_height(&storage, "A", 4).unwrap());
+        assert_eq!(Some(7), map.may_load_at_height(&storage, "B", 4).unwrap());
+        assert_eq!(Some(13), map.may_load_at_height(&storage, "B", 5).unwrap());
+        assert_eq!(None, map.may_load_at_height(&storage, "C", 2).unwrap());
+        assert_eq!(Some(2), map.may_load_at_height(&storage, "C", 3).unwrap());
+
+        // the changelog only keeps the first write of every height
+        assert_eq!(
+            map.changelog("A").paging(&storage, 0, 10).unwrap(),
+            vec![
+                (1, ChangeSet { old: None }),
+                (3, ChangeSet { old: Some(5) })
+            ]
+        );
+    }
+
+    #[test]
+    fn composite_keys_keep_separate_history() {
+        let mut storage = MockStorage::new();
+        let map = EVERY_COMPOSITE_KEY;
+
+        map.save(&mut storage, ("A", "B"), &5, 1).unwrap();
+        map.save(&mut storage, ("AB", ""), &7, 1).unwrap();
+        map.save(&mut storage, ("A", "B"), &8, 2).unwrap();
+
+        assert_eq!(
+            Some(5),
+            map.may_load_at_height(&storage, ("A", "B"), 2).unwrap()
+        );
+        assert_eq!(
+            Some(7),
+            map.may_load_at_height(&storage, ("AB", ""), 2).unwrap()
+        );
+        assert_eq!(
+            None,
+            map.may_load_at_height(&storage, ("AB", ""), 1).unwrap()
+        );
+        assert_eq!(map.changelog(("AB", "")).get_len(&storage).unwrap(), 1);
+    }
+
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_ignores_changelog(#[values(EVERY, B_EVERY)] map: TestMap<impl Serde>) {
+        let mut storage = MockStorage::new();
+        init_data(&map, &mut storage);
+
+        let all: StdResult<Vec<_>> = map.range(&storage, None, None, Order::Ascending).collect();
+        assert_eq!(
+            all.unwrap(),
+            vec![("C".to_string(), 13), ("D".to_string(), 22)]
+        );
+    }
+}
@@ -0,0 +1,461 @@
+//! Snapshots keep the history of an `Item` or a `Map` so that past values can be read back by
+//! block height, e.g. to look up voting power at the height a proposal was created.
+//!
+//! Every write records the previous value in a per-key changelog, an append store of
+//! `(height, ChangeSet)` entries, so no storage iteration is needed to read the history back.
+//! Checkpoints are kept as a reference count per height next to a sorted list of the heights.
+mod item;
+mod map;
+
+pub use item::SnapshotItem;
+pub use map::SnapshotMap;
+
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::helpers::namespaces_with_key;
+use crate::{AppendStore, AppendStoreFormat, DequeStore, DequeStoreFormat, Format, Json, Map};
+
+/// The formats a [`SnapshotItem`] or [`SnapshotMap`] of `T` can be stored with. Besides the values,
+/// the format encodes the changelogs and the checkpoints. Every [`Serde`](crate::Serde) format
+/// implements it for serde values.
+pub trait SnapshotFormat<T>:
+    Format<T> + AppendStoreFormat<(u64, ChangeSet<T>)> + DequeStoreFormat<u64> + Format<u32>
+{
+}
+
+impl<T, Ser> SnapshotFormat<T> for Ser where
+    Ser: Format<T> + AppendStoreFormat<(u64, ChangeSet<T>)> + DequeStoreFormat<u64> + Format<u32>
+{
+}
+
+/// Structure holding a map of checkpoints composited from
+/// height (as u64) and counter of how many times it has
+/// been checkpointed (as u32).
+/// Stores all changes in changelog.
+pub(crate) struct Snapshot<'a, T, Ser = Json> {
+    checkpoints: Map<'a, u64, u32, Ser>,
+    checkpoint_heights: DequeStore<'a, u64, Ser>,
+    changelog_namespace: &'a str,
+    strategy: Strategy,
+    data_type: PhantomData<T>,
+}
+
+impl<'a, T, Ser> Snapshot<'a, T, Ser> {
+    pub const fn new(checkpoints: &'a str, changelog: &'a str, strategy: Strategy) -> Self {
+        Self {
+            checkpoints: Map::new(checkpoints),
+            checkpoint_heights: DequeStore::new(checkpoints),
+            changelog_namespace: changelog,
+            strategy,
+            data_type: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, Ser> Snapshot<'a, T, Ser>
+where
+    Ser: SnapshotFormat<T>,
+{
+    pub fn add_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        let count = self
+            .checkpoints
+            .may_load(store, height)?
+            .unwrap_or_default();
+        if count == 0 {
+            self.insert_height(store, height)?;
+        }
+        self.checkpoints.save(store, height, &(count + 1))
+    }
+
+    pub fn remove_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        let count = self
+            .checkpoints
+            .may_load(store, height)?
+            .unwrap_or_default();
+        if count <= 1 {
+            self.checkpoints.remove(store, height);
+            if count == 1 {
+                self.remove_height(store, height)?;
+            }
+            Ok(())
+        } else {
+            self.checkpoints.save(store, height, &(count - 1))
+        }
+    }
+
+    /// The changelog of a single key, in order of increasing height.
+    pub fn changelog(&self, key: &[u8]) -> AppendStore<'a, (u64, ChangeSet<T>), Ser> {
+        AppendStore::prefixed(namespaces_with_key(
+            &[self.changelog_namespace.as_bytes(), key],
+            b"",
+        ))
+    }
+
+    /// should_checkpoint looks at the strategy and determines if we want to checkpoint
+    pub fn should_checkpoint(&self, store: &dyn Storage, key: &[u8]) -> StdResult<bool> {
+        match self.strategy {
+            Strategy::EveryBlock => Ok(true),
+            Strategy::Never => Ok(false),
+            Strategy::Selected => self.should_checkpoint_selected(store, key),
+        }
+    }
+
+    /// this is just pulled out from above for the selected block
+    fn should_checkpoint_selected(&self, store: &dyn Storage, key: &[u8]) -> StdResult<bool> {
+        // most recent checkpoint
+        let checkpoint = match self.latest_checkpoint(store)? {
+            Some(height) => height,
+            None => return Ok(false),
+        };
+        // if there is no changelog for this key since then, we need to write one
+        let last = self.last_change_height(store, key)?;
+        Ok(last.map_or(true, |height| height < checkpoint))
+    }
+
+    /// assert_checkpointed will return Ok if the height is checkpointed,
+    /// or if the strategy doesn't require explicit checkpoints
+    pub fn assert_checkpointed(&self, store: &dyn Storage, height: u64) -> StdResult<()> {
+        let has = match self.strategy {
+            Strategy::EveryBlock => true,
+            Strategy::Never => false,
+            Strategy::Selected => self.checkpoints.may_load(store, height)?.is_some(),
+        };
+        match has {
+            true => Ok(()),
+            false => Err(StdError::not_found("checkpoint")),
+        }
+    }
+
+    /// has_changelog returns true if there is already a changelog for this key at this height.
+    /// It errors if the key was changed at a later height, as the history can only be appended to.
+    pub fn has_changelog(&self, store: &dyn Storage, key: &[u8], height: u64) -> StdResult<bool> {
+        match self.last_change_height(store, key)? {
+            Some(last) if last > height => Err(StdError::generic_err(format!(
+                "Snapshot changes must be written in order of height: got {} after {}",
+                height, last
+            ))),
+            Some(last) => Ok(last == height),
+            None => Ok(false),
+        }
+    }
+
+    /// write_changelog records the value of the key before the change made at this height
+    pub fn write_changelog(
+        &self,
+        store: &mut dyn Storage,
+        key: &[u8],
+        height: u64,
+        old: Option<T>,
+    ) -> StdResult<()> {
+        self.changelog(key)
+            .push(store, &(height, ChangeSet { old }))
+    }
+
+    /// may_load_at_height reads historical data from given checkpoints.
+    /// Returns StdError::NotFound if we have no checkpoint, and can give no data.
+    /// Returns Ok(None) if there is a checkpoint, but no cached data (no changes since the
+    /// checkpoint. Caller should query current state).
+    /// Return Ok(Some(x)) if there is a checkpoint and data written to changelog, returning the state at that time
+    pub fn may_load_at_height(
+        &self,
+        store: &dyn Storage,
+        key: &[u8],
+        height: u64,
+    ) -> StdResult<Option<Option<T>>> {
+        self.assert_checkpointed(store, height)?;
+
+        // the first change at or after the given height holds the value we had at that height
+        let changelog = self.changelog(key);
+        let len = changelog.get_len(store)?;
+        let (mut low, mut high) = (0, len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if changelog.get_at_unchecked(store, mid)?.0 < height {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low == len {
+            return Ok(None);
+        }
+        let (_, change) = changelog.get_at_unchecked(store, low)?;
+        Ok(Some(change.old))
+    }
+
+    fn last_change_height(&self, store: &dyn Storage, key: &[u8]) -> StdResult<Option<u64>> {
+        let changelog = self.changelog(key);
+        let len = changelog.get_len(store)?;
+        if len == 0 {
+            return Ok(None);
+        }
+        Ok(Some(changelog.get_at_unchecked(store, len - 1)?.0))
+    }
+
+    fn latest_checkpoint(&self, store: &dyn Storage) -> StdResult<Option<u64>> {
+        let len = self.checkpoint_heights.get_len(store)?;
+        if len == 0 {
+            return Ok(None);
+        }
+        Ok(Some(self.checkpoint_heights.get_at(store, len - 1)?))
+    }
+
+    /// inserts a new height into the sorted list of checkpoint heights
+    fn insert_height(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        let heights = &self.checkpoint_heights;
+        let len = heights.get_len(store)?;
+        if len == 0 || heights.get_at(store, len - 1)? < height {
+            return heights.push_back(store, &height);
+        }
+        if heights.get_at(store, 0)? > height {
+            return heights.push_front(store, &height);
+        }
+        // checkpoints are usually added in order, so shifting from the back is cheap
+        heights.push_back(store, &height)?;
+        let mut pos = len;
+        while pos > 0 {
+            let prev = heights.get_at(store, pos - 1)?;
+            if prev < height {
+                break;
+            }
+            heights.set_at(store, pos, &prev)?;
+            pos -= 1;
+        }
+        heights.set_at(store, pos, &height)
+    }
+
+    /// removes a height from the sorted list of checkpoint heights
+    fn remove_height(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        let heights = &self.checkpoint_heights;
+        let off = heights.get_off(store)?;
+        let (mut low, mut high) = (0, heights.get_len(store)?);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match heights.get_at_with_off(store, off, mid)? {
+                h if h < height => low = mid + 1,
+                h if h > height => high = mid,
+                _ => {
+                    heights.remove(store, mid)?;
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    EveryBlock,
+    Never,
+    /// Only writes for linked blocks - does a few more reads to save some writes.
+    /// Probably uses more gas, but less total disk usage.
+    ///
+    /// Note that you need a trusted source (eg. own contract) to set/remove checkpoints.
+    /// Useful when the checkpoint setting happens in the same contract as the snapshotting.
+    Selected,
+}
+
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh_derive::BorshSerialize, borsh_derive::BorshDeserialize)
+)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeSet<T> {
+    pub old: Option<T>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    type TestSnapshot = Snapshot<'static, u64>;
+
+    const NEVER: TestSnapshot = Snapshot::new("never__check", "never__change", Strategy::Never);
+    const EVERY: TestSnapshot =
+        Snapshot::new("every__check", "every__change", Strategy::EveryBlock);
+    const SELECT: TestSnapshot =
+        Snapshot::new("select__check", "select__change", Strategy::Selected);
+
+    const DUMMY_KEY: &[u8] = b"dummy";
+
+    #[test]
+    fn should_checkpoint() {
+        let storage = MockStorage::new();
+
+        assert_eq!(NEVER.should_checkpoint(&storage, DUMMY_KEY), Ok(false));
+        assert_eq!(EVERY.should_checkpoint(&storage, DUMMY_KEY), Ok(true));
+        assert_eq!(SELECT.should_checkpoint(&storage, DUMMY_KEY), Ok(false));
+    }
+
+    #[test]
+    fn assert_checkpointed() {
+        let mut storage = MockStorage::new();
+
+        assert_eq!(
+            NEVER.assert_checkpointed(&storage, 1),
+            Err(StdError::not_found("checkpoint"))
+        );
+        assert_eq!(EVERY.assert_checkpointed(&storage, 1), Ok(()));
+        assert_eq!(
+            SELECT.assert_checkpointed(&storage, 1),
+            Err(StdError::not_found("checkpoint"))
+        );
+
+        // Add a checkpoint at 1
+        NEVER.add_checkpoint(&mut storage, 1).unwrap();
+        EVERY.add_checkpoint(&mut storage, 1).unwrap();
+        SELECT.add_checkpoint(&mut storage, 1).unwrap();
+
+        assert_eq!(
+            NEVER.assert_checkpointed(&storage, 1),
+            Err(StdError::not_found("checkpoint"))
+        );
+        assert_eq!(EVERY.assert_checkpointed(&storage, 1), Ok(()));
+        assert_eq!(SELECT.assert_checkpointed(&storage, 1), Ok(()));
+
+        // Remove checkpoint
+        NEVER.remove_checkpoint(&mut storage, 1).unwrap();
+        EVERY.remove_checkpoint(&mut storage, 1).unwrap();
+        SELECT.remove_checkpoint(&mut storage, 1).unwrap();
+
+        assert_eq!(
+            NEVER.assert_checkpointed(&storage, 1),
+            Err(StdError::not_found("checkpoint"))
+        );
+        assert_eq!(EVERY.assert_checkpointed(&storage, 1), Ok(()));
+        assert_eq!(
+            SELECT.assert_checkpointed(&storage, 1),
+            Err(StdError::not_found("checkpoint"))
+        );
+    }
+
+    #[test]
+    fn checkpoint_heights_stay_sorted() {
+        let mut storage = MockStorage::new();
+
+        for height in [5, 2, 9, 7, 7, 1] {
+            SELECT.add_checkpoint(&mut storage, height).unwrap();
+        }
+        let heights = SELECT.checkpoint_heights.paging(&storage, 0, 10).unwrap();
+        assert_eq!(heights, vec![1, 2, 5, 7, 9]);
+        assert_eq!(SELECT.latest_checkpoint(&storage), Ok(Some(9)));
+
+        // 7 was added twice, so it survives one removal
+        SELECT.remove_checkpoint(&mut storage, 7).unwrap();
+        SELECT.remove_checkpoint(&mut storage, 9).unwrap();
+        SELECT.remove_checkpoint(&mut storage, 2).unwrap();
+        let heights = SELECT.checkpoint_heights.paging(&storage, 0, 10).unwrap();
+        assert_eq!(heights, vec![1, 5, 7]);
+        assert_eq!(SELECT.latest_checkpoint(&storage), Ok(Some(7)));
+    }
+
+    #[test]
+    fn has_changelog() {
+        let mut storage = MockStorage::new();
+
+        assert_eq!(NEVER.has_changelog(&storage, DUMMY_KEY, 1), Ok(false));
+        assert_eq!(EVERY.has_changelog(&storage, DUMMY_KEY, 1), Ok(false));
+        assert_eq!(SELECT.has_changelog(&storage, DUMMY_KEY, 1), Ok(false));
+
+        // Write a changelog at 2
+        NEVER
+            .write_changelog(&mut storage, DUMMY_KEY, 2, Some(3))
+            .unwrap();
+        EVERY
+            .write_changelog(&mut storage, DUMMY_KEY, 2, Some(4))
+            .unwrap();
+        SELECT
+            .write_changelog(&mut storage, DUMMY_KEY, 2, Some(5))
+            .unwrap();
+
+        assert_eq!(NEVER.has_changelog(&storage, DUMMY_KEY, 2), Ok(true));
+        assert_eq!(EVERY.has_changelog(&storage, DUMMY_KEY, 2), Ok(true));
+        assert_eq!(SELECT.has_changelog(&storage, DUMMY_KEY, 2), Ok(true));
+
+        assert_eq!(NEVER.has_changelog(&storage, DUMMY_KEY, 3), Ok(false));
+        assert_eq!(EVERY.has_changelog(&storage, DUMMY_KEY, 3), Ok(false));
+        assert_eq!(SELECT.has_changelog(&storage, DUMMY_KEY, 3), Ok(false));
+
+        // going back in time is not allowed
+        assert!(EVERY.has_changelog(&storage, DUMMY_KEY, 1).is_err());
+    }
+
+    #[test]
+    fn may_load_at_height() {
+        let mut storage = MockStorage::new();
+
+        assert_eq!(
+            NEVER.may_load_at_height(&storage, DUMMY_KEY, 3),
+            Err(StdError::not_found("checkpoint"))
+        );
+        assert_eq!(EVERY.may_load_at_height(&storage, DUMMY_KEY, 3), Ok(None));
+        assert_eq!(
+            SELECT.may_load_at_height(&storage, DUMMY_KEY, 3),
+            Err(StdError::not_found("checkpoint"))
+        );
+
+        // Add a checkpoint at 3
+        EVERY.add_checkpoint(&mut storage, 3).unwrap();
+        SELECT.add_checkpoint(&mut storage, 3).unwrap();
+        assert_eq!(EVERY.may_load_at_height(&storage, DUMMY_KEY, 3), Ok(None));
+        assert_eq!(SELECT.may_load_at_height(&storage, DUMMY_KEY, 3), Ok(None));
+
+        // Write a changelog at 3
+        EVERY
+            .write_changelog(&mut storage, DUMMY_KEY, 3, Some(101))
+            .unwrap();
+        SELECT
+            .write_changelog(&mut storage, DUMMY_KEY, 3, Some(102))
+            .unwrap();
+        assert_eq!(
+            EVERY.may_load_at_height(&storage, DUMMY_KEY, 3),
+            Ok(Some(Some(101)))
+        );
+        assert_eq!(
+            SELECT.may_load_at_height(&storage, DUMMY_KEY, 3),
+            Ok(Some(Some(102)))
+        );
+        // a previous height returns the first change after it
+        assert_eq!(
+            EVERY.may_load_at_height(&storage, DUMMY_KEY, 2),
+            Ok(Some(Some(101)))
+        );
+
+        // Write a changelog at 4, removing the value, and checkpoint it
+        EVERY
+            .write_changelog(&mut storage, DUMMY_KEY, 4, None)
+            .unwrap();
+        SELECT
+            .write_changelog(&mut storage, DUMMY_KEY, 4, None)
+            .unwrap();
+        EVERY.add_checkpoint(&mut storage, 4).unwrap();
+        SELECT.add_checkpoint(&mut storage, 4).unwrap();
+
+        assert_eq!(
+            EVERY.may_load_at_height(&storage, DUMMY_KEY, 4),
+            Ok(Some(None))
+        );
+        assert_eq!(
+            SELECT.may_load_at_height(&storage, DUMMY_KEY, 4),
+            Ok(Some(None))
+        );
+
+        // Confirm old value at 3
+        assert_eq!(
+            EVERY.may_load_at_height(&storage, DUMMY_KEY, 3),
+            Ok(Some(Some(101)))
+        );
+        assert_eq!(
+            SELECT.may_load_at_height(&storage, DUMMY_KEY, 3),
+            Ok(Some(Some(102)))
+        );
+        // nothing changed after 4
+        assert_eq!(EVERY.may_load_at_height(&storage, DUMMY_KEY, 5), Ok(None));
+    }
+}
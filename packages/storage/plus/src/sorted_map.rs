@@ -0,0 +1,700 @@
+//! A map that keeps its keys in order, to answer range, first/last and floor/ceil queries without
+//! the `iterator` feature.
+//!
+//! Values are stored on their own under the serialized key, so loading or overwriting the value
+//! of a key is a single storage access. The keys are also kept in a B+ tree with one storage
+//! entry per node. The leaves hold the keys and are linked to their neighbours, so a range is
+//! walked one leaf at a time in either direction.
+//!
+//! Nodes are split when they overflow, but they are only removed once they are empty rather than
+//! merged with their neighbours, so a map that shrinks a lot keeps some sparsely filled nodes.
+use std::any::type_name;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::helpers::{may_deserialize, namespaces_with_key};
+use crate::{Format, Json};
+
+const META_KEY: &[u8] = b"meta";
+const NODE_NAMESPACE: &[u8] = b"node";
+const VALUE_NAMESPACE: &[u8] = b"val";
+const DEFAULT_NODE_SIZE: u32 = 32;
+
+// The tree is stored with the format of the map, so these types show up in its `Format` bounds
+// and have to be `pub`. They aren't exported from the crate.
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh_derive::BorshSerialize, borsh_derive::BorshDeserialize)
+)]
+#[derive(Serialize, Deserialize, Default)]
+pub struct Meta {
+    root: Option<u32>,
+    /// id of the next node to be created
+    next_node: u32,
+    len: u32,
+}
+
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh_derive::BorshSerialize, borsh_derive::BorshDeserialize)
+)]
+#[derive(Serialize, Deserialize)]
+pub struct Leaf<K> {
+    keys: Vec<K>,
+    prev: Option<u32>,
+    next: Option<u32>,
+}
+
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh_derive::BorshSerialize, borsh_derive::BorshDeserialize)
+)]
+#[derive(Serialize, Deserialize)]
+pub struct Internal<K> {
+    /// `keys[i]` is the smallest key that may be stored under `children[i + 1]`
+    keys: Vec<K>,
+    children: Vec<u32>,
+}
+
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh_derive::BorshSerialize, borsh_derive::BorshDeserialize)
+)]
+#[derive(Serialize, Deserialize)]
+pub enum Node<K> {
+    Leaf(Leaf<K>),
+    Internal(Internal<K>),
+}
+
+/// The formats a [`SortedMap`] with keys `K` and values `V` can be stored with. Besides the keys
+/// and values, the format encodes the nodes of the tree. Every [`Serde`](crate::Serde) format
+/// implements it for serde keys and values.
+pub trait SortedMapFormat<K, V>: Format<K> + Format<V> + Format<Meta> + Format<Node<K>> {}
+
+impl<K, V, Ser> SortedMapFormat<K, V> for Ser where
+    Ser: Format<K> + Format<V> + Format<Meta> + Format<Node<K>>
+{
+}
+
+/// An internal node passed on the way down to a leaf, with the index of the child that was taken
+type Step<K> = (u32, Internal<K>, usize);
+
+pub struct SortedMap<'a, K, V, Ser = Json> {
+    namespace: &'a [u8],
+    /// maximum number of keys held by a node
+    node_size: u32,
+    key_type: PhantomData<K>,
+    value_type: PhantomData<V>,
+    serialization_type: PhantomData<Ser>,
+}
+
+impl<'a, K, V, Ser> SortedMap<'a, K, V, Ser> {
+    /// constructor
+    pub const fn new(namespace: &'a str) -> Self {
+        Self::new_with_node_size(namespace, DEFAULT_NODE_SIZE)
+    }
+    /// constructor for a map whose tree nodes hold up to `node_size` keys.
+    ///
+    /// The node size only decides when nodes are split, so it can be changed later on. Larger
+    /// nodes make the tree shallower, but every node that is read or written is larger.
+    pub const fn new_with_node_size(namespace: &'a str, node_size: u32) -> Self {
+        assert!(node_size >= 3, "node size must be at least 3");
+        Self {
+            namespace: namespace.as_bytes(),
+            node_size,
+            key_type: PhantomData,
+            value_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, Ser> SortedMap<'a, K, V, Ser>
+where
+    K: Ord + Clone,
+    Ser: SortedMapFormat<K, V>,
+{
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        Ok(self.meta(storage)?.len)
+    }
+
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    pub fn has(&self, storage: &dyn Storage, k: &K) -> StdResult<bool> {
+        Ok(storage.get(&self.value_key(k)?).is_some())
+    }
+
+    pub fn load(&self, storage: &dyn Storage, k: &K) -> StdResult<V> {
+        self.may_load(storage, k)?
+            .ok_or_else(|| StdError::not_found(type_name::<V>()))
+    }
+
+    pub fn may_load(&self, storage: &dyn Storage, k: &K) -> StdResult<Option<V>> {
+        may_deserialize::<V, Ser>(&storage.get(&self.value_key(k)?))
+    }
+
+    /// Stores the value of a key. Only a new key is added to the tree, overwriting the value of
+    /// an existing key doesn't touch it.
+    pub fn save(&self, storage: &mut dyn Storage, k: &K, value: &V) -> StdResult<()> {
+        let value_key = self.value_key(k)?;
+        if storage.get(&value_key).is_none() {
+            self.insert_key(storage, k)?;
+        }
+        storage.set(&value_key, &<Ser as Format<V>>::encode(value)?);
+        Ok(())
+    }
+
+    pub fn remove(&self, storage: &mut dyn Storage, k: &K) -> StdResult<()> {
+        let value_key = self.value_key(k)?;
+        if storage.get(&value_key).is_some() {
+            self.remove_key(storage, k)?;
+            storage.remove(&value_key);
+        }
+        Ok(())
+    }
+
+    /// Returns the entry with the smallest key
+    pub fn first(&self, storage: &dyn Storage) -> StdResult<Option<(K, V)>> {
+        self.range(storage, ..)?.next().transpose()
+    }
+
+    /// Returns the entry with the largest key
+    pub fn last(&self, storage: &dyn Storage) -> StdResult<Option<(K, V)>> {
+        self.rev_range(storage, ..)?.next().transpose()
+    }
+
+    /// Returns the entry with the largest key that is smaller than or equal to `k`
+    pub fn floor(&self, storage: &dyn Storage, k: &K) -> StdResult<Option<(K, V)>> {
+        self.rev_range(storage, ..=k.clone())?.next().transpose()
+    }
+
+    /// Returns the entry with the smallest key that is larger than or equal to `k`
+    pub fn ceil(&self, storage: &dyn Storage, k: &K) -> StdResult<Option<(K, V)>> {
+        self.range(storage, k.clone()..)?.next().transpose()
+    }
+
+    /// Iterates over the entries with a key in `range`, in ascending order
+    pub fn range<'b, R: RangeBounds<K>>(
+        &'b self,
+        storage: &'b dyn Storage,
+        range: R,
+    ) -> StdResult<SortedMapIter<'a, 'b, K, V, Ser>> {
+        self.iter_from(
+            storage,
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+            false,
+        )
+    }
+
+    /// Iterates over the entries with a key in `range`, in descending order
+    pub fn rev_range<'b, R: RangeBounds<K>>(
+        &'b self,
+        storage: &'b dyn Storage,
+        range: R,
+    ) -> StdResult<SortedMapIter<'a, 'b, K, V, Ser>> {
+        self.iter_from(
+            storage,
+            range.end_bound().cloned(),
+            range.start_bound().cloned(),
+            true,
+        )
+    }
+
+    /// does paging with the given parameters, in ascending order of the keys.
+    ///
+    /// The keys of the skipped pages are still read, so continuing from the last key of the
+    /// previous page with `range` is cheaper for later pages.
+    pub fn paging(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<(K, V)>> {
+        self.range(storage, ..)?
+            .skip((start_page as usize) * (size as usize))
+            .take(size as usize)
+            .collect()
+    }
+}
+
+impl<'a, K, V, Ser> SortedMap<'a, K, V, Ser>
+where
+    K: Ord + Clone,
+    Ser: SortedMapFormat<K, V>,
+{
+    fn iter_from<'b>(
+        &'b self,
+        storage: &'b dyn Storage,
+        start: Bound<K>,
+        stop: Bound<K>,
+        reverse: bool,
+    ) -> StdResult<SortedMapIter<'a, 'b, K, V, Ser>> {
+        let mut iter = SortedMapIter {
+            map: self,
+            storage,
+            keys: vec![],
+            next_leaf: None,
+            stop,
+            reverse,
+        };
+        let root = match self.meta(storage)?.root {
+            Some(root) => root,
+            None => return Ok(iter),
+        };
+        let (_, _, leaf) = match &start {
+            Bound::Included(k) | Bound::Excluded(k) => self.descend(storage, root, |node| {
+                node.keys.partition_point(|separator| separator <= k)
+            })?,
+            Bound::Unbounded if reverse => {
+                self.descend(storage, root, |node| node.children.len() - 1)?
+            }
+            Bound::Unbounded => self.descend(storage, root, |_| 0)?,
+        };
+        iter.next_leaf = if reverse { leaf.prev } else { leaf.next };
+        iter.keys = leaf
+            .keys
+            .into_iter()
+            .filter(|key| within(key, &start, reverse))
+            .collect();
+        if !reverse {
+            iter.keys.reverse();
+        }
+        Ok(iter)
+    }
+
+    fn insert_key(&self, storage: &mut dyn Storage, k: &K) -> StdResult<()> {
+        let mut meta = self.meta(storage)?;
+        meta.len += 1;
+        let root = match meta.root {
+            Some(root) => root,
+            None => {
+                let id = meta.allocate();
+                meta.root = Some(id);
+                let leaf = Leaf {
+                    keys: vec![k.clone()],
+                    prev: None,
+                    next: None,
+                };
+                self.save_node(storage, id, &Node::Leaf(leaf))?;
+                return self.set_meta(storage, &meta);
+            }
+        };
+
+        let (mut path, leaf_id, mut leaf) = self.descend(storage, root, |node| {
+            node.keys.partition_point(|separator| separator <= k)
+        })?;
+        let pos = leaf.keys.partition_point(|key| key < k);
+        leaf.keys.insert(pos, k.clone());
+        if leaf.keys.len() <= self.node_size as usize {
+            self.save_node(storage, leaf_id, &Node::Leaf(leaf))?;
+            return self.set_meta(storage, &meta);
+        }
+
+        // split the leaf, linking the new right half between it and its next leaf
+        let right_id = meta.allocate();
+        let right = Leaf {
+            keys: leaf.keys.split_off(leaf.keys.len() / 2),
+            prev: Some(leaf_id),
+            next: leaf.next,
+        };
+        if let Some(next_id) = leaf.next {
+            let mut next = self.load_leaf(storage, next_id)?;
+            next.prev = Some(right_id);
+            self.save_node(storage, next_id, &Node::Leaf(next))?;
+        }
+        leaf.next = Some(right_id);
+        let mut separator = right.keys[0].clone();
+        self.save_node(storage, leaf_id, &Node::Leaf(leaf))?;
+        self.save_node(storage, right_id, &Node::Leaf(right))?;
+
+        // add the new node to its parent, splitting the parents for as long as they overflow
+        let (mut left_id, mut right_id) = (leaf_id, right_id);
+        loop {
+            let (id, mut node, index) = match path.pop() {
+                Some(step) => step,
+                None => {
+                    // the root was split
+                    let root_id = meta.allocate();
+                    meta.root = Some(root_id);
+                    let root = Internal {
+                        keys: vec![separator],
+                        children: vec![left_id, right_id],
+                    };
+                    self.save_node(storage, root_id, &Node::Internal(root))?;
+                    break;
+                }
+            };
+            node.keys.insert(index, separator);
+            node.children.insert(index + 1, right_id);
+            if node.keys.len() <= self.node_size as usize {
+                self.save_node(storage, id, &Node::Internal(node))?;
+                break;
+            }
+            let mid = node.keys.len() / 2;
+            let right = Internal {
+                keys: node.keys.split_off(mid + 1),
+                children: node.children.split_off(mid + 1),
+            };
+            separator = node.keys.remove(mid);
+            left_id = id;
+            right_id = meta.allocate();
+            self.save_node(storage, left_id, &Node::Internal(node))?;
+            self.save_node(storage, right_id, &Node::Internal(right))?;
+        }
+        self.set_meta(storage, &meta)
+    }
+
+    fn remove_key(&self, storage: &mut dyn Storage, k: &K) -> StdResult<()> {
+        let mut meta = self.meta(storage)?;
+        let root = match meta.root {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+        let (mut path, leaf_id, mut leaf) = self.descend(storage, root, |node| {
+            node.keys.partition_point(|separator| separator <= k)
+        })?;
+        let pos = match leaf.keys.binary_search(k) {
+            Ok(pos) => pos,
+            Err(_) => return Ok(()),
+        };
+        leaf.keys.remove(pos);
+        meta.len -= 1;
+        if !leaf.keys.is_empty() {
+            self.save_node(storage, leaf_id, &Node::Leaf(leaf))?;
+            return self.set_meta(storage, &meta);
+        }
+
+        // unlink the empty leaf from its neighbours
+        if let Some(prev_id) = leaf.prev {
+            let mut prev = self.load_leaf(storage, prev_id)?;
+            prev.next = leaf.next;
+            self.save_node(storage, prev_id, &Node::Leaf(prev))?;
+        }
+        if let Some(next_id) = leaf.next {
+            let mut next = self.load_leaf(storage, next_id)?;
+            next.prev = leaf.prev;
+            self.save_node(storage, next_id, &Node::Leaf(next))?;
+        }
+        storage.remove(&self.node_key(leaf_id));
+
+        // remove it from its parent, along with the parents that are left without children
+        loop {
+            let (id, mut node, index) = match path.pop() {
+                Some(step) => step,
+                None => {
+                    meta.root = None;
+                    break;
+                }
+            };
+            node.children.remove(index);
+            if node.children.is_empty() {
+                storage.remove(&self.node_key(id));
+                continue;
+            }
+            node.keys.remove(index.saturating_sub(1));
+            self.save_node(storage, id, &Node::Internal(node))?;
+            break;
+        }
+
+        // a root with a single child is replaced by that child
+        while let Some(root) = meta.root {
+            match self.load_node(storage, root)? {
+                Node::Internal(node) if node.children.len() == 1 => {
+                    storage.remove(&self.node_key(root));
+                    meta.root = Some(node.children[0]);
+                }
+                _ => break,
+            }
+        }
+        self.set_meta(storage, &meta)
+    }
+
+    /// Walks down from the root to a leaf, taking the child chosen by `choose` in every internal
+    /// node. Returns the internal nodes passed on the way, and the leaf.
+    fn descend(
+        &self,
+        storage: &dyn Storage,
+        root: u32,
+        choose: impl Fn(&Internal<K>) -> usize,
+    ) -> StdResult<(Vec<Step<K>>, u32, Leaf<K>)> {
+        let mut path = vec![];
+        let mut id = root;
+        loop {
+            match self.load_node(storage, id)? {
+                Node::Leaf(leaf) => return Ok((path, id, leaf)),
+                Node::Internal(node) => {
+                    let index = choose(&node);
+                    let child = node.children[index];
+                    path.push((id, node, index));
+                    id = child;
+                }
+            }
+        }
+    }
+
+    fn meta(&self, storage: &dyn Storage) -> StdResult<Meta> {
+        let key = namespaces_with_key(&[self.namespace], META_KEY);
+        Ok(may_deserialize::<Meta, Ser>(&storage.get(&key))?.unwrap_or_default())
+    }
+
+    fn set_meta(&self, storage: &mut dyn Storage, meta: &Meta) -> StdResult<()> {
+        let key = namespaces_with_key(&[self.namespace], META_KEY);
+        storage.set(&key, &<Ser as Format<Meta>>::encode(meta)?);
+        Ok(())
+    }
+
+    fn node_key(&self, id: u32) -> Vec<u8> {
+        namespaces_with_key(&[self.namespace, NODE_NAMESPACE], &id.to_be_bytes())
+    }
+
+    fn value_key(&self, k: &K) -> StdResult<Vec<u8>> {
+        Ok(namespaces_with_key(
+            &[self.namespace, VALUE_NAMESPACE],
+            &<Ser as Format<K>>::encode(k)?,
+        ))
+    }
+
+    fn load_node(&self, storage: &dyn Storage, id: u32) -> StdResult<Node<K>> {
+        match storage.get(&self.node_key(id)) {
+            Some(bytes) => <Ser as Format<Node<K>>>::decode(&bytes),
+            None => Err(StdError::not_found("SortedMap node")),
+        }
+    }
+
+    fn load_leaf(&self, storage: &dyn Storage, id: u32) -> StdResult<Leaf<K>> {
+        match self.load_node(storage, id)? {
+            Node::Leaf(leaf) => Ok(leaf),
+            Node::Internal(_) => Err(StdError::generic_err("SortedMap node is not a leaf")),
+        }
+    }
+
+    fn save_node(&self, storage: &mut dyn Storage, id: u32, node: &Node<K>) -> StdResult<()> {
+        storage.set(&self.node_key(id), &<Ser as Format<Node<K>>>::encode(node)?);
+        Ok(())
+    }
+}
+
+impl Meta {
+    fn allocate(&mut self) -> u32 {
+        let id = self.next_node;
+        self.next_node += 1;
+        id
+    }
+}
+
+/// Returns true if `key` is on the inner side of `bound`, which is an upper bound when `upper`
+/// is true and a lower bound otherwise
+fn within<K: Ord>(key: &K, bound: &Bound<K>, upper: bool) -> bool {
+    match (bound, upper) {
+        (Bound::Unbounded, _) => true,
+        (Bound::Included(bound), true) => key <= bound,
+        (Bound::Excluded(bound), true) => key < bound,
+        (Bound::Included(bound), false) => key >= bound,
+        (Bound::Excluded(bound), false) => key > bound,
+    }
+}
+
+/// An iterator over a range of a sorted map, reading one leaf of keys at a time
+pub struct SortedMapIter<'a, 'b, K, V, Ser> {
+    map: &'b SortedMap<'a, K, V, Ser>,
+    storage: &'b dyn Storage,
+    /// the keys of the current leaf that are still to be returned, the next one last
+    keys: Vec<K>,
+    /// the leaf to read once the current one is exhausted
+    next_leaf: Option<u32>,
+    /// the bound where the iteration stops: the end of the range, or its start when iterating
+    /// in descending order
+    stop: Bound<K>,
+    reverse: bool,
+}
+
+impl<'a, 'b, K, V, Ser> SortedMapIter<'a, 'b, K, V, Ser>
+where
+    K: Ord + Clone,
+    Ser: SortedMapFormat<K, V>,
+{
+    pub(crate) fn next_key(&mut self) -> Option<StdResult<K>> {
+        loop {
+            if let Some(key) = self.keys.pop() {
+                if !within(&key, &self.stop, !self.reverse) {
+                    self.keys.clear();
+                    self.next_leaf = None;
+                    return None;
+                }
+                return Some(Ok(key));
+            }
+            let leaf = match self.map.load_leaf(self.storage, self.next_leaf?) {
+                Ok(leaf) => leaf,
+                Err(err) => {
+                    self.next_leaf = None;
+                    return Some(Err(err));
+                }
+            };
+            if self.reverse {
+                self.next_leaf = leaf.prev;
+                self.keys = leaf.keys;
+            } else {
+                self.next_leaf = leaf.next;
+                self.keys = leaf.keys.into_iter().rev().collect();
+            }
+        }
+    }
+}
+
+impl<'a, 'b, K, V, Ser> Iterator for SortedMapIter<'a, 'b, K, V, Ser>
+where
+    K: Ord + Clone,
+    Ser: SortedMapFormat<K, V>,
+{
+    type Item = StdResult<(K, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = match self.next_key()? {
+            Ok(key) => key,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(self.map.load(self.storage, &key).map(|value| (key, value)))
+    }
+
+    // skipped entries don't need their value to be loaded
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        for _ in 0..n {
+            if let Err(err) = self.next_key()? {
+                return Some(Err(err));
+            }
+        }
+        self.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::Bincode2;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn save_load_remove() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let map: SortedMap<String, u64> = SortedMap::new("map");
+        assert!(map.is_empty(&storage)?);
+        assert_eq!(map.first(&storage)?, None);
+
+        map.save(&mut storage, &"b".to_string(), &2)?;
+        map.save(&mut storage, &"a".to_string(), &1)?;
+        map.save(&mut storage, &"b".to_string(), &20)?;
+        assert_eq!(map.len(&storage)?, 2);
+        assert_eq!(map.load(&storage, &"b".to_string())?, 20);
+        assert!(map.has(&storage, &"a".to_string())?);
+        assert_eq!(map.may_load(&storage, &"c".to_string())?, None);
+        assert!(map.load(&storage, &"c".to_string()).is_err());
+
+        map.remove(&mut storage, &"a".to_string())?;
+        map.remove(&mut storage, &"c".to_string())?;
+        assert_eq!(map.len(&storage)?, 1);
+        assert_eq!(map.first(&storage)?, Some(("b".to_string(), 20)));
+
+        map.remove(&mut storage, &"b".to_string())?;
+        assert!(map.is_empty(&storage)?);
+        assert_eq!(map.last(&storage)?, None);
+        Ok(())
+    }
+
+    fn keys(iter: SortedMapIter<u32, u32, Bincode2>) -> StdResult<Vec<u32>> {
+        iter.map(|entry| entry.map(|(key, _)| key)).collect()
+    }
+
+    #[test]
+    fn ordered_queries() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let map: SortedMap<u32, u32, Bincode2> = SortedMap::new_with_node_size("map", 3);
+        // the even numbers below 100, in scrambled order
+        for i in 0..50 {
+            let key = (i * 17) % 50 * 2;
+            map.save(&mut storage, &key, &(key * 10))?;
+        }
+        assert_eq!(map.len(&storage)?, 50);
+
+        assert_eq!(
+            keys(map.range(&storage, ..)?)?,
+            (0..50).map(|i| i * 2).collect::<Vec<_>>()
+        );
+        assert_eq!(keys(map.range(&storage, 10..16)?)?, vec![10, 12, 14]);
+        assert_eq!(keys(map.range(&storage, 11..=16)?)?, vec![12, 14, 16]);
+        assert_eq!(
+            keys(map.range(&storage, (Bound::Excluded(94), Bound::Unbounded))?)?,
+            vec![96, 98]
+        );
+        assert_eq!(keys(map.rev_range(&storage, ..5)?)?, vec![4, 2, 0]);
+        assert_eq!(keys(map.rev_range(&storage, 91..=96)?)?, vec![96, 94, 92]);
+        assert_eq!(keys(map.range(&storage, 200..)?)?, vec![]);
+
+        assert_eq!(map.first(&storage)?, Some((0, 0)));
+        assert_eq!(map.last(&storage)?, Some((98, 980)));
+        assert_eq!(map.floor(&storage, &51)?, Some((50, 500)));
+        assert_eq!(map.floor(&storage, &50)?, Some((50, 500)));
+        assert_eq!(map.ceil(&storage, &51)?, Some((52, 520)));
+        assert_eq!(map.ceil(&storage, &99)?, None);
+
+        assert_eq!(
+            map.paging(&storage, 2, 4)?,
+            vec![(16, 160), (18, 180), (20, 200), (22, 220)]
+        );
+        assert_eq!(map.paging(&storage, 12, 4)?, vec![(96, 960), (98, 980)]);
+        Ok(())
+    }
+
+    #[test]
+    fn matches_btree_map() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let map: SortedMap<(u16, u8), u32> = SortedMap::new_with_node_size("map", 4);
+        let mut expected = BTreeMap::new();
+
+        // a simple linear congruential generator, to mix inserts, updates and removals
+        let mut seed = 12345_u32;
+        for step in 0..2000 {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let key = ((seed >> 16) as u16 % 300, (seed >> 8) as u8 % 3);
+            if seed % 3 == 0 {
+                map.remove(&mut storage, &key)?;
+                expected.remove(&key);
+            } else {
+                map.save(&mut storage, &key, &step)?;
+                expected.insert(key, step);
+            }
+
+            if step % 100 == 0 {
+                assert_eq!(map.len(&storage)? as usize, expected.len());
+                assert_eq!(
+                    map.range(&storage, ..)?.collect::<StdResult<Vec<_>>>()?,
+                    expected.clone().into_iter().collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    map.rev_range(&storage, ..)?
+                        .collect::<StdResult<Vec<_>>>()?,
+                    expected.clone().into_iter().rev().collect::<Vec<_>>()
+                );
+            }
+        }
+
+        // removing every key leaves an empty tree that can be reused
+        for key in expected.keys() {
+            map.remove(&mut storage, key)?;
+        }
+        assert!(map.is_empty(&storage)?);
+        assert_eq!(map.range(&storage, ..)?.count(), 0);
+        assert!(map.meta(&storage)?.root.is_none());
+        map.save(&mut storage, &(1, 1), &1)?;
+        assert_eq!(map.first(&storage)?, Some(((1, 1), 1)));
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::versioned::{Migration, Versions};
+use crate::{Format, Json};
+
+/// Item that stores its value together with the version it was written with, and upgrades
+/// values of older versions through `migrations` when loading them.
+pub struct VersionedItem<'a, T, Ser = Json> {
+    storage_key: &'a [u8],
+    versions: Versions<'a>,
+    // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
+    data_type: PhantomData<T>,
+    serialization_type: PhantomData<*const Ser>,
+}
+
+impl<'a, T, Ser> VersionedItem<'a, T, Ser> {
+    /// `migrations[i]` upgrades values of version `i + 1` to version `i + 2`. New values are
+    /// written with version `migrations.len() + 1`.
+    pub const fn new(storage_key: &'a str, migrations: &'a [Migration]) -> Self {
+        VersionedItem {
+            storage_key: storage_key.as_bytes(),
+            versions: Versions::new(migrations),
+            data_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+
+    /// The version new values are written with
+    pub const fn version(&self) -> u16 {
+        self.versions.current()
+    }
+}
+
+impl<'a, T, Ser> VersionedItem<'a, T, Ser>
+where
+    Ser: Format<T>,
+{
+    // this gets the path of the data to use elsewhere
+    pub fn as_slice(&self) -> &[u8] {
+        self.storage_key
+    }
+
+    /// save will serialize the model and store it with the current version
+    pub fn save(&self, store: &mut dyn Storage, data: &T) -> StdResult<()> {
+        store.set(self.storage_key, &self.versions.encode::<T, Ser>(data)?);
+        Ok(())
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage) {
+        store.remove(self.storage_key);
+    }
+
+    /// load will return an error if no data is set at the given key, or on parse error.
+    /// Values of older versions are upgraded, but not written back. A value written by a plain
+    /// [`Item`](crate::Item) has to be adopted with [`Self::adopt_unversioned`] first.
+    pub fn load(&self, store: &dyn Storage) -> StdResult<T> {
+        self.may_load(store)?
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))
+    }
+
+    /// may_load will parse the data stored at the key if present, returns `Ok(None)` if no data there.
+    /// returns an error on issues parsing
+    pub fn may_load(&self, store: &dyn Storage) -> StdResult<Option<T>> {
+        store
+            .get(self.storage_key)
+            .map(|value| self.versions.decode::<T, Ser>(&value))
+            .transpose()
+    }
+
+    /// The version the stored value was written with, if there is one
+    pub fn stored_version(&self, store: &dyn Storage) -> StdResult<Option<u16>> {
+        store
+            .get(self.storage_key)
+            .map(|value| Versions::split(&value).map(|(version, _)| version))
+            .transpose()
+    }
+
+    /// Upgrades the stored value to the current version and writes it back.
+    /// Returns true if the value was rewritten.
+    pub fn migrate(&self, store: &mut dyn Storage) -> StdResult<bool> {
+        let upgraded = match store.get(self.storage_key) {
+            Some(value) => self.versions.upgrade(&value)?,
+            None => None,
+        };
+        match upgraded {
+            Some(value) => {
+                store.set(self.storage_key, &value);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Takes over a value written by a plain [`Item`](crate::Item) as `Old` with `OldSer`, where
+    /// `Old` is the type of version 1. The value is re-saved with a version and upgraded to the
+    /// current one. Returns true if there was a value to adopt.
+    ///
+    /// Nothing in storage tells an untagged value from a tagged one, so this must run exactly
+    /// once, in the `migrate` that replaces the `Item` with this `VersionedItem`.
+    pub fn adopt_unversioned<Old, OldSer>(&self, store: &mut dyn Storage) -> StdResult<bool>
+    where
+        OldSer: Format<Old>,
+        Ser: Format<Old>,
+    {
+        match store.get(self.storage_key) {
+            Some(data) => {
+                let value = self.versions.adopt::<Old, OldSer, Ser>(&data)?;
+                store.set(self.storage_key, &value);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Loads the data, perform the specified action, and store the result
+    /// in the database. This is shorthand for some common sequences, which may be useful.
+    ///
+    /// It assumes, that data was initialized before, and if it doesn't exist, `Err(StdError::NotFound)`
+    /// is returned.
+    pub fn update<A, E>(&self, store: &mut dyn Storage, action: A) -> Result<T, E>
+    where
+        A: FnOnce(T) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        let input = self.load(store)?;
+        let output = action(input)?;
+        self.save(store, &output)?;
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::versioned::migrate;
+    use crate::{Bincode2, Item, Serde};
+    use cosmwasm_std::testing::MockStorage;
+    use rstest::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ConfigV1 {
+        owner: String,
+        max_tokens: u32,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ConfigV2 {
+        owner: String,
+        max_tokens: u64,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ConfigV3 {
+        owners: Vec<String>,
+        max_tokens: u64,
+        paused: bool,
+    }
+
+    fn v1_to_v2<Ser: Serde>(data: &[u8]) -> StdResult<Vec<u8>> {
+        migrate::<ConfigV1, ConfigV2, Ser>(data, |old| ConfigV2 {
+            owner: old.owner,
+            max_tokens: old.max_tokens.into(),
+        })
+    }
+
+    fn v2_to_v3<Ser: Serde>(data: &[u8]) -> StdResult<Vec<u8>> {
+        migrate::<ConfigV2, ConfigV3, Ser>(data, |old| ConfigV3 {
+            owners: vec![old.owner],
+            max_tokens: old.max_tokens,
+            paused: false,
+        })
+    }
+
+    const V1: VersionedItem<ConfigV1> = VersionedItem::new("config", &[]);
+    const V2: VersionedItem<ConfigV2> = VersionedItem::new("config", &[v1_to_v2::<Json>]);
+    const V3: VersionedItem<ConfigV3> =
+        VersionedItem::new("config", &[v1_to_v2::<Json>, v2_to_v3::<Json>]);
+    const B_V1: VersionedItem<ConfigV1, Bincode2> = VersionedItem::new("config", &[]);
+    const B_V2: VersionedItem<ConfigV2, Bincode2> =
+        VersionedItem::new("config", &[v1_to_v2::<Bincode2>]);
+    const B_V3: VersionedItem<ConfigV3, Bincode2> =
+        VersionedItem::new("config", &[v1_to_v2::<Bincode2>, v2_to_v3::<Bincode2>]);
+
+    fn config_v1() -> ConfigV1 {
+        ConfigV1 {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        }
+    }
+
+    fn config_v3() -> ConfigV3 {
+        ConfigV3 {
+            owners: vec!["admin".to_string()],
+            max_tokens: 1234,
+            paused: false,
+        }
+    }
+
+    #[rstest]
+    #[case(V1, V2, V3)]
+    #[case(B_V1, B_V2, B_V3)]
+    fn upgrades_on_load<Ser: Serde>(
+        #[case] v1: VersionedItem<ConfigV1, Ser>,
+        #[case] v2: VersionedItem<ConfigV2, Ser>,
+        #[case] v3: VersionedItem<ConfigV3, Ser>,
+    ) {
+        let mut store = MockStorage::new();
+        assert_eq!(v3.version(), 3);
+        assert_eq!(v3.may_load(&store).unwrap(), None);
+        assert!(v3.load(&store).is_err());
+
+        v1.save(&mut store, &config_v1()).unwrap();
+        assert_eq!(v3.stored_version(&store).unwrap(), Some(1));
+        assert_eq!(v3.load(&store).unwrap(), config_v3());
+        assert_eq!(
+            v2.load(&store).unwrap(),
+            ConfigV2 {
+                owner: "admin".to_string(),
+                max_tokens: 1234
+            }
+        );
+        // loading doesn't write the upgraded value back
+        assert_eq!(v3.stored_version(&store).unwrap(), Some(1));
+
+        // a version with later migrations than the stored value can't read it
+        v3.save(&mut store, &config_v3()).unwrap();
+        assert!(v2.load(&store).is_err());
+        assert!(v1.load(&store).is_err());
+    }
+
+    #[rstest]
+    #[case(V1, V3)]
+    #[case(B_V1, B_V3)]
+    fn migrate_writes_back<Ser: Serde>(
+        #[case] v1: VersionedItem<ConfigV1, Ser>,
+        #[case] v3: VersionedItem<ConfigV3, Ser>,
+    ) {
+        let mut store = MockStorage::new();
+        assert!(!v3.migrate(&mut store).unwrap());
+
+        v1.save(&mut store, &config_v1()).unwrap();
+        assert!(v3.migrate(&mut store).unwrap());
+        assert_eq!(v3.stored_version(&store).unwrap(), Some(3));
+        assert_eq!(v3.load(&store).unwrap(), config_v3());
+
+        // already at the current version
+        assert!(!v3.migrate(&mut store).unwrap());
+
+        let updated = v3
+            .update(&mut store, |mut config| -> StdResult<_> {
+                config.paused = true;
+                Ok(config)
+            })
+            .unwrap();
+        assert!(updated.paused);
+        assert_eq!(v3.load(&store).unwrap(), updated);
+
+        v3.remove(&mut store);
+        assert_eq!(v3.stored_version(&store).unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_unversioned_values() {
+        let mut store = MockStorage::new();
+        // a plain item stores no version
+        let item: Item<u8> = Item::new("config");
+        item.save(&mut store, &1).unwrap();
+        assert!(V1.load(&store).is_err());
+    }
+
+    #[rstest]
+    #[case(Item::new("config"), V3)]
+    #[case(Item::new("config"), B_V3)]
+    fn adopts_unversioned_values<Ser: Serde>(
+        #[case] legacy: Item<ConfigV1, Ser>,
+        #[case] v3: VersionedItem<ConfigV3, Ser>,
+    ) {
+        let mut store = MockStorage::new();
+        assert!(!v3.adopt_unversioned::<ConfigV1, Ser>(&mut store).unwrap());
+
+        legacy.save(&mut store, &config_v1()).unwrap();
+        assert!(v3.adopt_unversioned::<ConfigV1, Ser>(&mut store).unwrap());
+        assert_eq!(v3.stored_version(&store).unwrap(), Some(3));
+        assert_eq!(v3.load(&store).unwrap(), config_v3());
+    }
+
+    #[test]
+    fn adopts_values_of_another_format() {
+        let mut store = MockStorage::new();
+        let legacy: Item<ConfigV1, Bincode2> = Item::new("config");
+        legacy.save(&mut store, &config_v1()).unwrap();
+
+        assert!(V1
+            .adopt_unversioned::<ConfigV1, Bincode2>(&mut store)
+            .unwrap());
+        assert_eq!(V1.stored_version(&store).unwrap(), Some(1));
+        assert_eq!(V1.load(&store).unwrap(), config_v1());
+    }
+}
@@ -0,0 +1,455 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::de::KeyDeserialize;
+use crate::helpers::namespaces_with_key;
+use crate::keys::PrimaryKey;
+use crate::versioned::{Migration, Versions};
+use crate::{Format, Json, Keymap};
+
+/// Map that stores every value together with the version it was written with, and upgrades
+/// values of older versions through `migrations` when loading them.
+pub struct VersionedMap<'a, K, T, Ser = Json> {
+    namespace: &'a [u8],
+    versions: Versions<'a>,
+    // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
+    key_type: PhantomData<K>,
+    data_type: PhantomData<T>,
+    serialization_type: PhantomData<*const Ser>,
+}
+
+impl<'a, K, T, Ser> VersionedMap<'a, K, T, Ser> {
+    /// `migrations[i]` upgrades values of version `i + 1` to version `i + 2`. New values are
+    /// written with version `migrations.len() + 1`.
+    pub const fn new(namespace: &'a str, migrations: &'a [Migration]) -> Self {
+        VersionedMap {
+            namespace: namespace.as_bytes(),
+            versions: Versions::new(migrations),
+            key_type: PhantomData,
+            data_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+
+    pub fn namespace(&self) -> &'a [u8] {
+        self.namespace
+    }
+
+    /// The version new values are written with
+    pub const fn version(&self) -> u16 {
+        self.versions.current()
+    }
+}
+
+impl<'a, K, T, Ser> VersionedMap<'a, K, T, Ser>
+where
+    K: PrimaryKey<'a>,
+    Ser: Format<T>,
+{
+    fn storage_key(&self, joined_key: &[u8]) -> Vec<u8> {
+        namespaces_with_key(&[self.namespace], joined_key)
+    }
+
+    pub fn save(&self, store: &mut dyn Storage, k: K, data: &T) -> StdResult<()> {
+        store.set(
+            &self.storage_key(&k.joined_key()),
+            &self.versions.encode::<T, Ser>(data)?,
+        );
+        Ok(())
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage, k: K) {
+        store.remove(&self.storage_key(&k.joined_key()));
+    }
+
+    /// load will return an error if no data is set at the given key, or on parse error.
+    /// Values of older versions are upgraded, but not written back. Values written by a plain
+    /// [`Map`](crate::Map) have to be adopted with [`Self::adopt_unversioned`] first.
+    pub fn load(&self, store: &dyn Storage, k: K) -> StdResult<T> {
+        self.may_load(store, k)?
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))
+    }
+
+    /// may_load will parse the data stored at the key if present, returns Ok(None) if no data there.
+    /// returns an error on issues parsing
+    pub fn may_load(&self, store: &dyn Storage, k: K) -> StdResult<Option<T>> {
+        store
+            .get(&self.storage_key(&k.joined_key()))
+            .map(|value| self.versions.decode::<T, Ser>(&value))
+            .transpose()
+    }
+
+    /// has returns true or false if any data is at this key, without parsing or interpreting the
+    /// contents.
+    pub fn has(&self, store: &dyn Storage, k: K) -> bool {
+        store.get(&self.storage_key(&k.joined_key())).is_some()
+    }
+
+    /// The version the value at the key was written with, if there is one
+    pub fn stored_version(&self, store: &dyn Storage, k: K) -> StdResult<Option<u16>> {
+        store
+            .get(&self.storage_key(&k.joined_key()))
+            .map(|value| Versions::split(&value).map(|(version, _)| version))
+            .transpose()
+    }
+
+    /// Loads the data, perform the specified action, and store the result
+    /// in the database. This is shorthand for some common sequences, which may be useful.
+    ///
+    /// If the data exists, `action(Some(value))` is called. Otherwise `action(None)` is called.
+    pub fn update<A, E>(&self, store: &mut dyn Storage, k: K, action: A) -> Result<T, E>
+    where
+        A: FnOnce(Option<T>) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        let input = self.may_load(store, k.clone())?;
+        let output = action(input)?;
+        self.save(store, k, &output)?;
+        Ok(output)
+    }
+
+    /// Upgrades the value at the key to the current version and writes it back.
+    /// Returns true if the value was rewritten.
+    pub fn migrate(&self, store: &mut dyn Storage, k: K) -> StdResult<bool> {
+        self.migrate_raw(store, &k.joined_key())
+    }
+
+    /// Upgrades the values at the given keys and returns how many of them were rewritten.
+    pub fn migrate_keys(
+        &self,
+        store: &mut dyn Storage,
+        keys: impl IntoIterator<Item = K>,
+    ) -> StdResult<u32> {
+        let mut migrated = 0;
+        for k in keys {
+            if self.migrate(store, k)? {
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    }
+
+    /// Upgrades the values at the keys of one page of `keymap`, and returns how many of them
+    /// were rewritten. The keymap is only used as an index of the keys of this map, so
+    /// its own values are left untouched. Migrating one page per transaction keeps the gas used
+    /// by large maps bounded.
+    pub fn migrate_keymap_page<'k, KK, V, KSer>(
+        &self,
+        store: &mut dyn Storage,
+        keymap: &Keymap<'k, KK, V, KSer>,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<u32>
+    where
+        KK: PrimaryKey<'k> + KeyDeserialize,
+        KSer: Format<V>,
+    {
+        let mut migrated = 0;
+        for key in keymap.paging_raw_keys(store, start_page, size)? {
+            if self.migrate_raw(store, &key)? {
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    }
+
+    /// Takes over the value at the key written by a plain [`Map`](crate::Map) as `Old` with
+    /// `OldSer`, where `Old` is the type of version 1. The value is re-saved with a version and
+    /// upgraded to the current one. Returns true if there was a value to adopt.
+    ///
+    /// Nothing in storage tells an untagged value from a tagged one, so every key must be adopted
+    /// exactly once, by the `migrate` that replaces the `Map` with this `VersionedMap`.
+    pub fn adopt_unversioned<Old, OldSer>(&self, store: &mut dyn Storage, k: K) -> StdResult<bool>
+    where
+        OldSer: Format<Old>,
+        Ser: Format<Old>,
+    {
+        self.adopt_raw::<Old, OldSer>(store, &k.joined_key())
+    }
+
+    /// Adopts the untagged values at the given keys, see [`Self::adopt_unversioned`]. Returns
+    /// how many of them were adopted.
+    pub fn adopt_unversioned_keys<Old, OldSer>(
+        &self,
+        store: &mut dyn Storage,
+        keys: impl IntoIterator<Item = K>,
+    ) -> StdResult<u32>
+    where
+        OldSer: Format<Old>,
+        Ser: Format<Old>,
+    {
+        let mut adopted = 0;
+        for k in keys {
+            if self.adopt_unversioned::<Old, OldSer>(store, k)? {
+                adopted += 1;
+            }
+        }
+        Ok(adopted)
+    }
+
+    /// Adopts the untagged values at the keys of one page of `keymap`, see
+    /// [`Self::adopt_unversioned`] and [`Self::migrate_keymap_page`]. Returns how many of them
+    /// were adopted.
+    pub fn adopt_unversioned_keymap_page<'k, Old, OldSer, KK, V, KSer>(
+        &self,
+        store: &mut dyn Storage,
+        keymap: &Keymap<'k, KK, V, KSer>,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<u32>
+    where
+        OldSer: Format<Old>,
+        Ser: Format<Old>,
+        KK: PrimaryKey<'k> + KeyDeserialize,
+        KSer: Format<V>,
+    {
+        let mut adopted = 0;
+        for key in keymap.paging_raw_keys(store, start_page, size)? {
+            if self.adopt_raw::<Old, OldSer>(store, &key)? {
+                adopted += 1;
+            }
+        }
+        Ok(adopted)
+    }
+
+    fn adopt_raw<Old, OldSer>(&self, store: &mut dyn Storage, joined_key: &[u8]) -> StdResult<bool>
+    where
+        OldSer: Format<Old>,
+        Ser: Format<Old>,
+    {
+        let storage_key = self.storage_key(joined_key);
+        match store.get(&storage_key) {
+            Some(data) => {
+                let value = self.versions.adopt::<Old, OldSer, Ser>(&data)?;
+                store.set(&storage_key, &value);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn migrate_raw(&self, store: &mut dyn Storage, joined_key: &[u8]) -> StdResult<bool> {
+        let storage_key = self.storage_key(joined_key);
+        let upgraded = match store.get(&storage_key) {
+            Some(value) => self.versions.upgrade(&value)?,
+            None => None,
+        };
+        match upgraded {
+            Some(value) => {
+                store.set(&storage_key, &value);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::versioned::migrate;
+    use crate::{Bincode2, Map, Serde};
+    use cosmwasm_std::testing::MockStorage;
+    use rstest::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct BalanceV1 {
+        amount: u32,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct BalanceV2 {
+        amount: u128,
+        locked: u128,
+    }
+
+    fn v1_to_v2<Ser: Serde>(data: &[u8]) -> StdResult<Vec<u8>> {
+        migrate::<BalanceV1, BalanceV2, Ser>(data, |old| BalanceV2 {
+            amount: old.amount.into(),
+            locked: 0,
+        })
+    }
+
+    type V1<'a, Ser> = VersionedMap<'a, (&'a str, u32), BalanceV1, Ser>;
+    type V2<'a, Ser> = VersionedMap<'a, (&'a str, u32), BalanceV2, Ser>;
+
+    const BALANCES_V1: V1<Json> = VersionedMap::new("balances", &[]);
+    const BALANCES_V2: V2<Json> = VersionedMap::new("balances", &[v1_to_v2::<Json>]);
+    const B_BALANCES_V1: V1<Bincode2> = VersionedMap::new("balances", &[]);
+    const B_BALANCES_V2: V2<Bincode2> = VersionedMap::new("balances", &[v1_to_v2::<Bincode2>]);
+
+    #[rstest]
+    #[case(BALANCES_V1, BALANCES_V2)]
+    #[case(B_BALANCES_V1, B_BALANCES_V2)]
+    fn upgrades_on_load<Ser: Serde>(#[case] v1: V1<Ser>, #[case] v2: V2<Ser>) {
+        let mut store = MockStorage::new();
+        assert!(!v2.has(&store, ("alice", 1)));
+        assert_eq!(v2.may_load(&store, ("alice", 1)).unwrap(), None);
+
+        v1.save(&mut store, ("alice", 1), &BalanceV1 { amount: 10 })
+            .unwrap();
+        assert!(v2.has(&store, ("alice", 1)));
+        assert_eq!(v2.stored_version(&store, ("alice", 1)).unwrap(), Some(1));
+        assert_eq!(
+            v2.load(&store, ("alice", 1)).unwrap(),
+            BalanceV2 {
+                amount: 10,
+                locked: 0
+            }
+        );
+
+        let updated = v2
+            .update(&mut store, ("alice", 1), |balance| -> StdResult<_> {
+                let mut balance = balance.unwrap();
+                balance.locked = 5;
+                Ok(balance)
+            })
+            .unwrap();
+        assert_eq!(updated.locked, 5);
+        assert_eq!(v2.stored_version(&store, ("alice", 1)).unwrap(), Some(2));
+        assert!(v1.load(&store, ("alice", 1)).is_err());
+
+        v2.remove(&mut store, ("alice", 1));
+        assert!(!v2.has(&store, ("alice", 1)));
+    }
+
+    #[rstest]
+    #[case(BALANCES_V1, BALANCES_V2)]
+    #[case(B_BALANCES_V1, B_BALANCES_V2)]
+    fn migrate_keymap_pages<Ser: Serde>(#[case] v1: V1<Ser>, #[case] v2: V2<Ser>) {
+        let mut store = MockStorage::new();
+        let index: Keymap<(String, u32), ()> = Keymap::new("balance_keys");
+
+        let names = ["alice", "bob", "carol", "dave", "erin"];
+        for (i, name) in names.iter().enumerate() {
+            v1.save(
+                &mut store,
+                (*name, i as u32),
+                &BalanceV1 { amount: i as u32 },
+            )
+            .unwrap();
+            index
+                .insert(&mut store, (name.to_string(), i as u32), &())
+                .unwrap();
+        }
+
+        assert_eq!(v2.migrate_keymap_page(&mut store, &index, 0, 2).unwrap(), 2);
+        assert_eq!(v2.stored_version(&store, ("alice", 0)).unwrap(), Some(2));
+        assert_eq!(v2.stored_version(&store, ("bob", 1)).unwrap(), Some(2));
+        assert_eq!(v2.stored_version(&store, ("carol", 2)).unwrap(), Some(1));
+
+        // migrating a page twice doesn't rewrite anything
+        assert_eq!(v2.migrate_keymap_page(&mut store, &index, 0, 2).unwrap(), 0);
+        assert_eq!(v2.migrate_keymap_page(&mut store, &index, 1, 2).unwrap(), 2);
+        assert_eq!(v2.migrate_keymap_page(&mut store, &index, 2, 2).unwrap(), 1);
+
+        for (i, name) in names.iter().enumerate() {
+            assert_eq!(
+                v2.stored_version(&store, (*name, i as u32)).unwrap(),
+                Some(2)
+            );
+            assert_eq!(
+                v2.load(&store, (*name, i as u32)).unwrap(),
+                BalanceV2 {
+                    amount: i as u128,
+                    locked: 0
+                }
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(BALANCES_V1, BALANCES_V2)]
+    #[case(B_BALANCES_V1, B_BALANCES_V2)]
+    fn migrate_given_keys<Ser: Serde>(#[case] v1: V1<Ser>, #[case] v2: V2<Ser>) {
+        let mut store = MockStorage::new();
+        v1.save(&mut store, ("alice", 1), &BalanceV1 { amount: 1 })
+            .unwrap();
+        v2.save(
+            &mut store,
+            ("bob", 2),
+            &BalanceV2 {
+                amount: 2,
+                locked: 1,
+            },
+        )
+        .unwrap();
+
+        // missing keys and values at the current version are skipped
+        assert_eq!(
+            v2.migrate_keys(&mut store, [("alice", 1), ("bob", 2), ("carol", 3)])
+                .unwrap(),
+            1
+        );
+        assert!(!v2.migrate(&mut store, ("alice", 1)).unwrap());
+        assert_eq!(v2.load(&store, ("bob", 2)).unwrap().locked, 1);
+    }
+
+    #[rstest]
+    #[case(Map::new("balances"), BALANCES_V2)]
+    #[case(Map::new("balances"), B_BALANCES_V2)]
+    fn adopts_unversioned_values<Ser: Serde>(
+        #[case] legacy: Map<(&str, u32), BalanceV1, Ser>,
+        #[case] v2: V2<Ser>,
+    ) {
+        let mut store = MockStorage::new();
+        let index: Keymap<(String, u32), ()> = Keymap::new("balance_keys");
+
+        let names = ["alice", "bob", "carol"];
+        for (i, name) in names.iter().enumerate() {
+            legacy
+                .save(
+                    &mut store,
+                    (*name, i as u32),
+                    &BalanceV1 { amount: i as u32 },
+                )
+                .unwrap();
+            index
+                .insert(&mut store, (name.to_string(), i as u32), &())
+                .unwrap();
+        }
+
+        assert!(v2
+            .adopt_unversioned::<BalanceV1, Ser>(&mut store, ("alice", 0))
+            .unwrap());
+        assert_eq!(
+            v2.adopt_unversioned_keys::<BalanceV1, Ser>(&mut store, [("bob", 1), ("dave", 3)])
+                .unwrap(),
+            1
+        );
+        // the keymap page only covers carol, the first page was adopted above
+        assert_eq!(
+            v2.adopt_unversioned_keymap_page::<BalanceV1, Ser, _, _, _>(&mut store, &index, 1, 2)
+                .unwrap(),
+            1
+        );
+
+        for (i, name) in names.iter().enumerate() {
+            assert_eq!(
+                v2.stored_version(&store, (*name, i as u32)).unwrap(),
+                Some(2)
+            );
+            assert_eq!(
+                v2.load(&store, (*name, i as u32)).unwrap(),
+                BalanceV2 {
+                    amount: i as u128,
+                    locked: 0
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn shares_keys_with_map() {
+        let mut store = MockStorage::new();
+        BALANCES_V1
+            .save(&mut store, ("alice", 1), &BalanceV1 { amount: 7 })
+            .unwrap();
+        // the same keys are used as a Map with the same namespace
+        let map: Map<(&str, u32), Vec<u8>> = Map::new("balances");
+        assert!(map.has(&store, ("alice", 1)));
+    }
+}
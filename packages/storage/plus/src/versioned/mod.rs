@@ -0,0 +1,147 @@
+//! Versioned storage keeps a version next to every stored value, so that a contract can change
+//! the type of an `Item` or a `Map` and still read the values written by its older versions.
+//!
+//! Each value is prefixed with the version it was written with, as a big-endian u16. When an
+//! older value is loaded, the migrations registered after its version are applied in order
+//! before it is decoded. `migrate` methods write the upgraded value back, so a contract can
+//! upgrade its state in bulk during `migrate` instead of on every read.
+//!
+//! Values written by a plain `Item` or `Map` carry no version and must never be loaded through a
+//! versioned collection directly: their first two bytes would be read as a version, which fails
+//! for most values but silently misreads others (a Bincode2 value starting with `0x00 0x01` looks
+//! like version 1). Switch such state over with `adopt_unversioned` once, in the `migrate` that
+//! introduces the versioned collection.
+mod item;
+mod map;
+
+pub use item::VersionedItem;
+pub use map::VersionedMap;
+
+use cosmwasm_std::{StdError, StdResult};
+
+use crate::Format;
+
+/// Upgrades a value serialized by one version to the serialization of the next version.
+///
+/// Migrations are plain functions so that versioned collections keep their const constructors.
+/// Most of them just decode the old type and build the new one with [`migrate`]:
+///
+/// ```rust
+/// use secret_storage_plus::{migrate, Json, Migration, VersionedItem};
+/// # use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct ConfigV1 {
+///     owner: String,
+/// }
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Config {
+///     owner: String,
+///     paused: bool,
+/// }
+///
+/// const MIGRATIONS: &[Migration] = &[|data| {
+///     migrate::<ConfigV1, Config, Json>(data, |old| Config {
+///         owner: old.owner,
+///         paused: false,
+///     })
+/// }];
+/// const CONFIG: VersionedItem<Config> = VersionedItem::new("config", MIGRATIONS);
+/// ```
+pub type Migration = fn(&[u8]) -> StdResult<Vec<u8>>;
+
+/// Decodes `data` as `Old`, converts it with `convert` and serializes the result, all with `Ser`.
+pub fn migrate<Old, New, Ser>(data: &[u8], convert: impl FnOnce(Old) -> New) -> StdResult<Vec<u8>>
+where
+    Ser: Format<Old> + Format<New>,
+{
+    <Ser as Format<New>>::encode(&convert(<Ser as Format<Old>>::decode(data)?))
+}
+
+/// The chain of migrations of a versioned collection. Values are written with version 1 until
+/// the first migration is registered, and every migration adds a version.
+#[derive(Clone, Copy)]
+pub(crate) struct Versions<'a> {
+    migrations: &'a [Migration],
+}
+
+impl<'a> Versions<'a> {
+    pub const fn new(migrations: &'a [Migration]) -> Self {
+        Self { migrations }
+    }
+
+    pub const fn current(&self) -> u16 {
+        self.migrations.len() as u16 + 1
+    }
+
+    /// Serializes the value, prefixed with the current version
+    pub fn encode<T, Ser: Format<T>>(&self, data: &T) -> StdResult<Vec<u8>> {
+        let data = Ser::encode(data)?;
+        let mut value = Vec::with_capacity(data.len() + 2);
+        value.extend_from_slice(&self.current().to_be_bytes());
+        value.extend_from_slice(&data);
+        Ok(value)
+    }
+
+    /// Decodes a stored value, upgrading it to the current version first if needed
+    pub fn decode<T, Ser: Format<T>>(&self, value: &[u8]) -> StdResult<T> {
+        match self.upgrade(value)? {
+            Some(upgraded) => Ser::decode(&upgraded[2..]),
+            None => Ser::decode(&value[2..]),
+        }
+    }
+
+    /// Decodes an untagged value written as `Old` with `OldSer`, tags it as version 1 and upgrades
+    /// it to the current version
+    pub fn adopt<Old, OldSer, Ser>(&self, data: &[u8]) -> StdResult<Vec<u8>>
+    where
+        OldSer: Format<Old>,
+        Ser: Format<Old>,
+    {
+        let data = Ser::encode(&OldSer::decode(data)?)?;
+        let mut value = Vec::with_capacity(data.len() + 2);
+        value.extend_from_slice(&1u16.to_be_bytes());
+        value.extend_from_slice(&data);
+        match self.upgrade(&value)? {
+            Some(upgraded) => Ok(upgraded),
+            None => Ok(value),
+        }
+    }
+
+    /// Runs the migrations registered after the version the value was written with. Returns
+    /// `None` if the value already is at the current version.
+    pub fn upgrade(&self, value: &[u8]) -> StdResult<Option<Vec<u8>>> {
+        let (version, data) = Self::split(value)?;
+        let current = self.current();
+        if version == current {
+            return Ok(None);
+        }
+        if version == 0 || version > current {
+            return Err(StdError::generic_err(format!(
+                "Unsupported version {} of a value, the current version is {}",
+                version, current
+            )));
+        }
+
+        let mut data = data.to_vec();
+        for migration in &self.migrations[version as usize - 1..] {
+            data = migration(&data)?;
+        }
+        let mut upgraded = Vec::with_capacity(data.len() + 2);
+        upgraded.extend_from_slice(&current.to_be_bytes());
+        upgraded.extend_from_slice(&data);
+        Ok(Some(upgraded))
+    }
+
+    /// Returns the version a value was written with and its serialized data
+    pub fn split(value: &[u8]) -> StdResult<(u16, &[u8])> {
+        match value {
+            [high, low, data @ ..] => Ok((u16::from_be_bytes([*high, *low]), data)),
+            _ => Err(StdError::parse_err(
+                "versioned value",
+                "value is missing its version",
+            )),
+        }
+    }
+}